@@ -2,11 +2,16 @@ use std::time::Duration;
 use eframe::{egui, Frame};
 use log::{info, warn};
 
-use crate::types::{DataPoint, AudioData, DatabaseTask, SaveResult};
+use crate::types::{DatabaseTask, ExportType};
 use crate::database::generate_session_id;
 use crate::config::ConfigManager;
 use crate::audio::AudioPlayer;
-use super::state::AppState;
+use super::state::{AppState, GuiChannels};
+
+/// 退出时向数据库线程发送每个任务的最长等待时间
+const EXIT_TASK_SEND_TIMEOUT: Duration = Duration::from_secs(5);
+/// 退出时等待自动导出完成的最长时间，超时后不再等待直接退出
+const EXIT_EXPORT_TIMEOUT: Duration = Duration::from_secs(120);
 
 pub struct SensorDataApp {
     // 统一的状态管理
@@ -20,30 +25,16 @@ pub struct SensorDataApp {
 }
 
 impl SensorDataApp {
-    pub fn new(
-        data_receiver: crossbeam_channel::Receiver<DataPoint>,
-        audio_receiver: crossbeam_channel::Receiver<AudioData>,
-        db_task_sender: crossbeam_channel::Sender<DatabaseTask>,
-        save_result_receiver: crossbeam_channel::Receiver<SaveResult>
-    ) -> Self {
-        // 创建配置管理器
-        let config = ConfigManager::new();
-
+    pub fn new(channels: GuiChannels, config: ConfigManager) -> Self {
         // 创建应用状态
-        let mut state = AppState::new(
-            data_receiver,
-            audio_receiver,
-            db_task_sender,
-            save_result_receiver,
-            config.get_config(),
-        );
+        let mut state = AppState::new(channels, config.get_config());
 
         // 初始化会话ID
         state.collection.current_session_id = generate_session_id();
 
-        // 初始化自动保存间隔为窗口长度
+        // 初始化自动保存间隔为保存窗口长度
         let plot_config = config.get_config();
-        state.collection.auto_save_interval_ms = (plot_config.plot.window_duration_seconds * 1000.0) as u64;
+        state.collection.auto_save_interval_ms = (plot_config.plot.save_window_duration_seconds * 1000.0) as u64;
 
         // 初始化音频播放器
         let audio_player = match AudioPlayer::new() {
@@ -63,11 +54,26 @@ impl SensorDataApp {
             audio_player,
         };
 
-        // 加载文本文件
-        if let Err(e) = app.state.load_text_file("documents/chinese.txt") {
-            warn!("Failed to load text file: {}", e);
-        } else {
-            info!("Text file loaded successfully");
+        // 应用初始场景的配置覆盖
+        app.apply_scenario_config();
+
+        // 加载用户名/场景下拉列表
+        app.state.request_recent_identities();
+
+        // 加载上次打开的文本文件
+        if let Some(file_path) = app.config.get_config().text_reader.last_file_path.clone() {
+            if let Err(e) = app.state.load_text_file(&file_path) {
+                warn!("Failed to load text file {}: {}", file_path.display(), e);
+            } else {
+                info!("Text file {} loaded successfully", file_path.display());
+            }
+        }
+
+        // --session <id> 启动后直接打开指定session的历史数据
+        if let Some(session_id) = session_from_args() {
+            info!("Opening session {} from command line", session_id);
+            app.state.history.show_history_panel = true;
+            crate::app::ui::history_controls::open_session_by_id(&mut app, &session_id);
         }
 
         // 打印启动信息
@@ -75,6 +81,106 @@ impl SensorDataApp {
 
         app
     }
+
+    /// 仅配置了y坐标时，在获取到显示器尺寸后将窗口水平居中
+    fn apply_pending_window_position(&mut self, ctx: &egui::Context) {
+        let Some(y) = self.state.pending_center_y else {
+            return;
+        };
+
+        let (monitor_size, outer_rect) = ctx.input(|i| (i.viewport().monitor_size, i.viewport().outer_rect));
+        let Some(monitor_size) = monitor_size else {
+            // 部分平台首帧尚未报告显示器尺寸，下一帧再试
+            return;
+        };
+
+        let window_width = outer_rect
+            .map(|rect| rect.width())
+            .unwrap_or(self.config.get_config().window.width);
+        let x = ((monitor_size.x - window_width) / 2.0).max(0.0);
+
+        ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(egui::pos2(x, y)));
+        self.state.pending_center_y = None;
+        info!("窗口已按显示器宽度 {:.0}px 水平居中", monitor_size.x);
+    }
+
+    /// 按当前场景应用配置覆盖；窗口长度等波形缓冲区参数在下次校准后生效
+    pub fn apply_scenario_config(&mut self) {
+        if self.config.apply_scenario(&self.state.collection.scenario) {
+            let save_window_seconds = self.config.get_config().plot.save_window_duration_seconds;
+            self.state.collection.auto_save_interval_ms = (save_window_seconds * 1000.0) as u64;
+        }
+    }
+
+    /// 退出时记住当前的用户名/场景，下次启动时使用；只写回配置文件的 [session] 节
+    fn remember_identity(&mut self) {
+        let collection = &self.state.collection;
+        let session = &mut self.config.get_config_mut().session;
+        session.last_username = Some(collection.username.clone());
+        session.last_scenario = Some(collection.scenario.clone());
+        if let Err(e) = self.config.save_session() {
+            warn!("Failed to remember username/scenario: {}", e);
+        }
+    }
+
+    /// 退出前按顺序发出队列满时暂存的保存任务，数据库线程无响应时放弃剩余的任务
+    fn flush_pending_saves_on_exit(&mut self) {
+        let database = &mut self.state.database;
+        if database.pending_saves.0.is_empty() {
+            return;
+        }
+
+        info!("Sending {} deferred database tasks before exit...", database.pending_saves.0.len());
+        while let Some(task) = database.pending_saves.0.pop_front() {
+            if let Err(e) = database.db_task_sender.send_timeout(task, EXIT_TASK_SEND_TIMEOUT) {
+                warn!("Failed to send deferred database task on exit, {} more dropped: {}", database.pending_saves.0.len(), e);
+                database.pending_saves.0.clear();
+                return;
+            }
+        }
+    }
+
+    /// 退出时按配置导出所有未导出的session，阻塞等待仍在运行的数据库线程返回结果
+    pub fn export_all_on_exit(&mut self) {
+        let export_config = self.config.get_config().export.clone();
+        if !export_config.auto_export_on_exit {
+            return;
+        }
+
+        info!("Auto-exporting new sessions before exit...");
+        let (response_sender, response_receiver) = crossbeam_channel::bounded(1);
+        let task = DatabaseTask::Export {
+            export_type: ExportType::NewSessions,
+            options: export_config,
+            response_sender,
+        };
+
+        if let Err(e) = self.state.database.db_task_sender.send_timeout(task, EXIT_TASK_SEND_TIMEOUT) {
+            warn!("Failed to send auto-export task on exit: {}", e);
+            return;
+        }
+
+        match response_receiver.recv_timeout(EXIT_EXPORT_TIMEOUT) {
+            Ok(result) => info!("Auto-export on exit finished: {} succeeded, {} failed ({})",
+                                result.success_count, result.error_count, result.message),
+            Err(crossbeam_channel::RecvTimeoutError::Timeout) => warn!("Auto-export on exit did not finish within {:?}, exiting anyway", EXIT_EXPORT_TIMEOUT),
+            Err(crossbeam_channel::RecvTimeoutError::Disconnected) => warn!("Database thread stopped before auto-export on exit finished"),
+        }
+    }
+}
+
+/// 读取 `--session <id>` 或 `--session=<id>` 命令行参数
+fn session_from_args() -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--session" {
+            return args.next().filter(|id| !id.trim().is_empty());
+        }
+        if let Some(id) = arg.strip_prefix("--session=") {
+            return Some(id.to_string()).filter(|id| !id.trim().is_empty());
+        }
+    }
+    None
 }
 
 impl eframe::App for SensorDataApp {
@@ -82,18 +188,25 @@ impl eframe::App for SensorDataApp {
         // 设置明亮模式主题
         ctx.set_visuals(egui::Visuals::light());
 
+        // 按实际显示器宽度水平居中窗口
+        self.apply_pending_window_position(ctx);
+
         // 渲染UI组件
         crate::app::ui::render_status_bar(self, ctx);
         crate::app::ui::render_bottom_status_bar(self, ctx);
         crate::app::ui::render_history_panel(self, ctx);
         crate::app::ui::render_main_panel(self, ctx);
         crate::app::ui::render_export_dialog(self, ctx);
+        crate::app::ui::render_settings_panel(self, ctx);
+        crate::app::ui::render_about_dialog(self, ctx);
 
         // 处理各种结果
         self.handle_save_results();
         self.handle_export_results();
         self.handle_sessions_results();
         self.handle_history_results();
+        self.handle_recent_identity_results();
+        self.handle_screenshot_results(ctx);
 
         // 处理数据：校准、采集或丢弃
         self.handle_data_processing();
@@ -106,4 +219,14 @@ impl eframe::App for SensorDataApp {
 
         ctx.request_repaint_after(Duration::from_millis(150));
     }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // 暂存的保存任务先于导出发出，使自动导出包含这些窗口
+        self.flush_pending_saves_on_exit();
+        self.export_all_on_exit();
+        self.remember_identity();
+
+        let audio_monitor = &self.state.channels.audio_monitor;
+        info!("Audio channel high-water mark: {}/{}", audio_monitor.high_water_mark(), audio_monitor.capacity());
+    }
 }
\ No newline at end of file
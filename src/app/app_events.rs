@@ -10,47 +10,149 @@ impl SensorDataApp {
                 self.state.collection.save_status = error;
             } else if result.acc_saved > 0 || result.audio_saved > 0 {
                 self.state.collection.save_status = format!("Saved: {} ACC points, {} audio records", result.acc_saved, result.audio_saved);
+                if result.acc_truncated > 0 {
+                    self.state.collection.save_status.push_str(&format!(" ({} old ACC rows truncated)", result.acc_truncated));
+                }
                 info!("Data saved successfully: {} ACC, {} audio", result.acc_saved, result.audio_saved);
 
                 // 生成新的session ID for next save
                 self.state.collection.current_session_id = crate::database::generate_session_id();
+
+                // 新保存的用户名/场景加入下拉列表
+                self.state.request_recent_identities();
             } else {
                 self.state.collection.save_status = "No data saved".to_string();
             }
+            let status = self.state.collection.save_status.clone();
+            self.state.record_status(&status);
         }
     }
 
     pub fn handle_export_results(&mut self) {
+        // 只保留最新的导出进度
+        if let Some(receiver) = &self.state.export.export_progress_receiver {
+            while let Ok(progress) = receiver.try_recv() {
+                self.state.export.export_progress = Some(progress);
+            }
+        }
+
         if let Some(receiver) = &self.state.export.export_result_receiver {
             if let Ok(result) = receiver.try_recv() {
+                self.state.record_status(&result.message);
                 self.state.export.export_status = result.message;
                 self.state.export.export_result_receiver = None; // 清除接收器
+                self.state.export.export_progress_receiver = None;
+                self.state.export.export_progress = None;
+                self.state.export.failed_exports = result.failures;
                 info!("Export completed: {} succeeded, {} failed", result.success_count, result.error_count);
             }
         }
+
+        // 历史面板中直接导出的结果
+        if let Some(receiver) = &self.state.history.export_result_receiver {
+            if let Ok(result) = receiver.try_recv() {
+                self.state.record_status(&result.message);
+                self.state.history.loading_status = result.message;
+                self.state.history.export_result_receiver = None; // 清除接收器
+                info!("History export completed: {} succeeded, {} failed", result.success_count, result.error_count);
+            }
+        }
+    }
+
+    pub fn handle_recent_identity_results(&mut self) {
+        let collection = &mut self.state.collection;
+        if let Some(receiver) = &collection.recent_usernames_receiver {
+            if let Ok(usernames) = receiver.try_recv() {
+                collection.recent_usernames = usernames;
+                collection.recent_usernames_receiver = None;
+            }
+        }
+        if let Some(receiver) = &collection.recent_scenarios_receiver {
+            if let Ok(scenarios) = receiver.try_recv() {
+                collection.recent_scenarios = scenarios;
+                collection.recent_scenarios_receiver = None;
+            }
+        }
+    }
+
+    /// 请求整个窗口的截图，结果在之后的帧中以 Event::Screenshot 返回
+    pub fn request_screenshot(&mut self, ctx: &egui::Context) {
+        if self.state.screenshot_pending {
+            return;
+        }
+        self.state.screenshot_pending = true;
+        ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot(egui::UserData::default()));
+        ctx.request_repaint();
+    }
+
+    /// 把返回的截图保存为数据目录下带时间戳的PNG
+    pub fn handle_screenshot_results(&mut self, ctx: &egui::Context) {
+        if !self.state.screenshot_pending {
+            return;
+        }
+
+        let image = ctx.input(|i| {
+            i.raw.events.iter().find_map(|event| match event {
+                egui::Event::Screenshot { image, .. } => Some(image.clone()),
+                _ => None,
+            })
+        });
+        let Some(image) = image else {
+            return;
+        };
+        self.state.screenshot_pending = false;
+
+        let directory = self.config.get_config().get_data_directory();
+        let path = directory.join(format!("screenshot_{}.png", chrono::Local::now().format("%Y%m%d_%H%M%S")));
+        let [width, height] = image.size;
+        let result = std::fs::create_dir_all(&directory)
+            .map_err(|e| e.to_string())
+            .and_then(|()| {
+                image::RgbaImage::from_raw(width as u32, height as u32, image.as_raw().to_vec())
+                    .ok_or_else(|| "Screenshot buffer size does not match its dimensions".to_string())
+            })
+            .and_then(|buffer| buffer.save(&path).map_err(|e| e.to_string()));
+
+        let status = match result {
+            Ok(()) => {
+                info!("Saved screenshot to {}", path.display());
+                format!("Screenshot saved: {}", path.display())
+            }
+            Err(e) => {
+                warn!("Failed to save screenshot to {}: {}", path.display(), e);
+                format!("Failed to save screenshot: {}", e)
+            }
+        };
+        self.state.record_status(&status);
+        self.state.collection.save_status = status;
     }
 
     pub fn handle_sessions_results(&mut self) {
-        if let Some(receiver) = &self.state.export.sessions_result_receiver {
-            if let Ok(sessions_with_status) = receiver.try_recv() {
-                self.state.export.sessions_with_export_status = sessions_with_status.clone();
+        // 接收导出大小估算结果
+        while let Ok(estimate) = self.state.export.size_estimate_receiver.try_recv() {
+            self.state.export.size_estimates.insert(estimate.session_id.clone(), estimate);
+        }
 
+        if let Some(receiver) = &self.state.export.sessions_result_receiver {
+            if let Ok(sessions) = receiver.try_recv() {
                 // 提取所有session ID
-                self.state.export.available_sessions = sessions_with_status.iter()
-                    .map(|(session_id, _)| session_id.clone())
+                self.state.export.available_sessions = sessions.iter()
+                    .map(|session| session.session_id.clone())
                     .collect();
 
                 // 统计已导出和未导出的session数量
-                let exported_count = sessions_with_status.iter().filter(|(_, is_exported)| *is_exported).count();
-                let unexported_count = sessions_with_status.len() - exported_count;
+                let exported_count = sessions.iter().filter(|session| session.is_exported).count();
+                let unexported_count = sessions.len() - exported_count;
+                let total_count = sessions.len();
+                self.state.export.sessions_with_metadata = sessions;
 
                 self.state.export.export_status = format!(
                     "Found {} sessions ({} exported, {} unexported)",
-                    sessions_with_status.len(), exported_count, unexported_count
+                    total_count, exported_count, unexported_count
                 );
                 self.state.export.sessions_result_receiver = None; // 清除接收器
                 info!("Refreshed sessions: found {} total ({} exported, {} unexported)",
-                      sessions_with_status.len(), exported_count, unexported_count);
+                      total_count, exported_count, unexported_count);
             }
         }
     }
@@ -101,6 +203,23 @@ impl SensorDataApp {
             }
         }
 
+        // Handle node id list results
+        if let Some(receiver) = &self.state.history.node_ids_result_receiver {
+            if let Ok(node_ids) = receiver.try_recv() {
+                self.state.history.node_ids_result_receiver = None; // Clear receiver
+
+                // 之前选择的节点已不存在时，恢复为不过滤
+                if let Some(selected) = &self.state.history.selected_node_id {
+                    if !node_ids.contains(selected) {
+                        self.state.history.selected_node_id = None;
+                    }
+                }
+                self.state.history.available_node_ids = node_ids;
+
+                info!("Refreshed node ids: found {}", self.state.history.available_node_ids.len());
+            }
+        }
+
         // Handle session list results
         if let Some(receiver) = &self.state.history.sessions_result_receiver {
             if let Ok(sessions) = receiver.try_recv() {
@@ -108,6 +227,9 @@ impl SensorDataApp {
                 self.state.history.loading_status = format!("Found {} history sessions for selected user", self.state.history.history_sessions.len());
                 self.state.history.sessions_result_receiver = None; // Clear receiver
 
+                // 请求排序所需的session统计信息
+                crate::app::ui::history_controls::request_session_summaries(self);
+
                 // 自动选择第一个session（如果列表不为空且当前没有选择）
                 if !self.state.history.history_sessions.is_empty() && self.state.history.selected_session.is_none() {
                     let first_session = self.state.history.history_sessions[0].clone();
@@ -123,6 +245,75 @@ impl SensorDataApp {
             }
         }
 
+        // Handle reviewed sessions results
+        if let Some(receiver) = &self.state.history.reviewed_sessions_receiver {
+            if let Ok(reviewed) = receiver.try_recv() {
+                self.state.history.reviewed_sessions_receiver = None;
+                self.state.history.reviewed_sessions = reviewed.into_iter().collect();
+            }
+        }
+
+        // Handle reviewed flag update results
+        if let Some(receiver) = &self.state.history.set_reviewed_result_receiver {
+            if let Ok(result) = receiver.try_recv() {
+                self.state.history.set_reviewed_result_receiver = None;
+                if let Err(e) = result {
+                    // 写入失败时重新读取数据库中的审阅标记，撤销界面上的修改
+                    warn!("Failed to save reviewed flag: {}", e);
+                    let (sender, receiver) = crossbeam_channel::unbounded();
+                    let task = crate::types::DatabaseTask::GetReviewed { response_sender: sender };
+                    if let Ok(()) = self.state.database.db_task_sender.try_send(task) {
+                        self.state.history.reviewed_sessions_receiver = Some(receiver);
+                    }
+                }
+            }
+        }
+
+        // Handle session summary results
+        if let Some(receiver) = &self.state.history.session_summaries_result_receiver {
+            if let Ok(summaries) = receiver.try_recv() {
+                self.state.history.session_summaries_result_receiver = None; // Clear receiver
+                self.state.history.session_summaries = summaries
+                    .into_iter()
+                    .map(|summary| (summary.session_id.clone(), summary))
+                    .collect();
+                self.state.sort_history_sessions();
+            }
+        }
+
+        // Handle session statistics results
+        if let Some(receiver) = &self.state.history.session_statistics_receiver {
+            if let Ok(statistics) = receiver.try_recv() {
+                self.state.history.session_statistics = Some(statistics);
+                self.state.history.session_statistics_receiver = None; // Clear receiver
+            }
+        }
+
+        // Handle audio sample rate results
+        if let Some(receiver) = &self.state.history.audio_sample_rate_receiver {
+            if let Ok(sample_rate) = receiver.try_recv() {
+                self.state.history.audio_sample_rate = sample_rate;
+                self.state.history.audio_playback.sample_rate = sample_rate as f32;
+                self.state.history.audio_sample_rate_receiver = None; // Clear receiver
+            }
+        }
+
+        // Handle session lookup results (open session by id)
+        if let Some((session_id, receiver)) = &self.state.history.session_lookup_receiver {
+            if let Ok(result) = receiver.try_recv() {
+                let session_id = session_id.clone();
+                self.state.history.session_lookup_receiver = None; // Clear receiver
+                match result {
+                    Some((username, scenario)) => {
+                        crate::app::ui::history_controls::select_resolved_session(self, &session_id, username, scenario);
+                    }
+                    None => {
+                        self.state.history.loading_status = format!("Session not found: {}", session_id);
+                    }
+                }
+            }
+        }
+
         // Handle history data loading results (original data)
         if let Some(receiver) = &self.state.history.history_result_receiver {
             if let Ok((acc_data, audio_data)) = receiver.try_recv() {
@@ -134,6 +325,8 @@ impl SensorDataApp {
                 if !self.state.history.show_aligned_data {
                     self.state.history.loaded_history_data = acc_data;
                     self.state.history.loaded_audio_data = audio_data;
+                    self.state.history.audio_waveform_cache.invalidate();
+                    self.state.history.history_load_completed = true;
                     self.state.history.loading_status = format!(
                         "Loaded original data: {} acc points, {} audio samples",
                         self.state.history.loaded_history_data.len(),
@@ -160,6 +353,8 @@ impl SensorDataApp {
                 if self.state.history.show_aligned_data {
                     self.state.history.loaded_history_data = acc_data.clone();
                     self.state.history.loaded_audio_data = audio_data.clone();
+                    self.state.history.audio_waveform_cache.invalidate();
+                    self.state.history.history_load_completed = true;
                     self.state.history.loading_status = format!(
                         "Loaded aligned data: {} acc points, {} audio samples",
                         self.state.history.loaded_history_data.len(),
@@ -175,32 +370,45 @@ impl SensorDataApp {
             }
         }
 
+        // 原始和对齐数据都已返回，加载结束
+        if self.state.history.history_result_receiver.is_none() && self.state.history.aligned_history_result_receiver.is_none() {
+            self.state.history.load_progress = None;
+        }
+
         // Handle delete session results
         if let Some(receiver) = &self.state.history.delete_result_receiver {
             if let Ok(result) = receiver.try_recv() {
                 match result {
                     Ok(()) => {
-                        self.state.history.loading_status = "Session删除成功".to_string();
+                        let deleted_sessions = std::mem::take(&mut self.state.history.sessions_to_delete);
+                        self.state.history.loading_status = if deleted_sessions.len() > 1 {
+                            format!("已删除 {} 个session", deleted_sessions.len())
+                        } else {
+                            "Session删除成功".to_string()
+                        };
 
                         // 清除相关状态
-                        if let Some(deleted_session) = &self.state.history.session_to_delete {
-                            // 如果删除的是当前选中的session，需要重新选择session
-                            if self.state.history.selected_session.as_ref() == Some(deleted_session) {
+                        if !deleted_sessions.is_empty() {
+                            // 如果删除的包含当前选中的session，需要重新选择session
+                            if self.state.history.selected_session.as_ref().is_some_and(|s| deleted_sessions.contains(s)) {
                                 // 先清除当前选中的session
                                 self.state.history.selected_session = None;
+                                self.state.history.history_load_completed = false;
                                 self.state.history.loaded_history_data.clear();
                                 self.state.history.loaded_audio_data.clear();
+                                self.state.history.audio_waveform_cache.invalidate();
                                 self.state.history.original_history_data.clear();
                                 self.state.history.original_audio_data.clear();
                                 self.state.history.aligned_history_data.clear();
                                 self.state.history.aligned_audio_data.clear();
                             }
 
-                            // 记录删除前的索引位置
-                            let deleted_index = self.state.history.history_sessions.iter().position(|s| s == deleted_session);
+                            // 记录删除前的索引位置（批量删除时取最靠前的一个）
+                            let deleted_index = self.state.history.history_sessions.iter().position(|s| deleted_sessions.contains(s));
                             
-                            // 从session列表中移除已删除的session
-                            self.state.history.history_sessions.retain(|s| s != deleted_session);
+                            // 从session列表和多选集合中移除已删除的session
+                            self.state.history.history_sessions.retain(|s| !deleted_sessions.contains(s));
+                            self.state.history.multi_selected_sessions.retain(|s| !deleted_sessions.contains(s));
                             
                             // 如果还有剩余的sessions，选择合适的session
                             if !self.state.history.history_sessions.is_empty() {
@@ -227,21 +435,98 @@ impl SensorDataApp {
                             }
                         }
 
-                        self.state.history.session_to_delete = None;
-                        info!("Session deleted successfully");
+                        info!("Deleted {} session(s) successfully", deleted_sessions.len());
                     }
                     Err(error_msg) => {
                         self.state.history.loading_status = format!("删除失败: {}", error_msg);
-                        self.state.history.session_to_delete = None;
+                        self.state.history.sessions_to_delete.clear();
                     }
                 }
 
                 self.state.history.delete_result_receiver = None;
             }
         }
+
+        // Handle rename scenario results
+        if let Some(receiver) = &self.state.history.rename_scenario_result_receiver {
+            if let Ok(result) = receiver.try_recv() {
+                match result {
+                    Ok(updated_count) => {
+                        let new_name = self.state.history.rename_scenario_new_name.trim().to_string();
+                        self.state.history.selected_scenario = Some(new_name.clone());
+
+                        // 重新加载场景和session列表
+                        if let Some(username) = self.state.history.selected_username.clone() {
+                            crate::app::ui::history_controls::load_scenarios_for_username_from_main(self, &username);
+                            crate::app::ui::history_controls::load_sessions_for_username_from_main(self, &username);
+                        }
+
+                        self.state.history.loading_status = format!("场景已重命名为 '{}'，更新 {} 条记录", new_name, updated_count);
+                        info!("Scenario renamed to '{}': {} records updated", new_name, updated_count);
+                    }
+                    Err(error_msg) => {
+                        self.state.history.loading_status = format!("重命名失败: {}", error_msg);
+                    }
+                }
+
+                self.state.history.rename_scenario_result_receiver = None;
+            }
+        }
+
+        // Handle duplicate session results
+        if let Some(receiver) = &self.state.history.duplicate_result_receiver {
+            if let Ok(result) = receiver.try_recv() {
+                match result {
+                    Ok(new_session_id) => {
+                        self.state.history.loading_status = format!("Session已复制为 {}", new_session_id);
+                        info!("Session duplicated as {}", new_session_id);
+
+                        // 重新加载session列表，使副本出现在列表中
+                        if let Some(username) = self.state.history.selected_username.clone() {
+                            crate::app::ui::history_controls::load_sessions_for_username_from_main(self, &username);
+                        }
+                    }
+                    Err(error_msg) => {
+                        self.state.history.loading_status = format!("复制失败: {}", error_msg);
+                    }
+                }
+
+                self.state.history.duplicate_result_receiver = None;
+            }
+        }
+
+        // 处理数据库完整性检查结果
+        if let Some(receiver) = &self.state.history.integrity_report_receiver {
+            if let Ok(result) = receiver.try_recv() {
+                match result {
+                    Ok(report) => {
+                        self.state.history.loading_status = format!(
+                            "Integrity check: {} issues in {} of {} sessions",
+                            report.issues.len(),
+                            report.affected_sessions().len(),
+                            report.sessions_checked
+                        );
+                        self.state.history.integrity_report = Some(report);
+                    }
+                    Err(error_msg) => {
+                        self.state.history.loading_status = format!("Integrity check failed: {}", error_msg);
+                    }
+                }
+
+                self.state.history.integrity_report_receiver = None;
+            }
+        }
     }
 
     pub fn handle_data_processing(&mut self) {
+        // 在消费之前检查音频通道的积压情况
+        let audio_receiver = &self.state.channels.audio_receiver;
+        self.state.channels.audio_monitor.poll(audio_receiver.len(), audio_receiver.capacity());
+        self.state.adopt_restarted_database_channels();
+        self.state.refresh_channel_stats();
+        self.state.refresh_database_size();
+        self.retry_pending_saves();
+
         if self.state.calibration.is_calibrating {
             crate::app::handlers::CalibrationHandler::handle_calibration(self);
         } else if self.state.collection.is_collecting {
@@ -250,6 +535,9 @@ impl SensorDataApp {
                 while let Ok(_) = self.state.channels.data_receiver.try_recv() {
                     // 丢弃数据
                 }
+                while self.state.channels.display_receiver.try_recv().is_ok() {
+                    // 丢弃显示数据
+                }
                 while let Ok(_) = self.state.channels.audio_receiver.try_recv() {
                     // 丢弃音频数据
                 }
@@ -265,6 +553,9 @@ impl SensorDataApp {
             while let Ok(_) = self.state.channels.data_receiver.try_recv() {
                 // 丢弃数据
             }
+            while self.state.channels.display_receiver.try_recv().is_ok() {
+                // 丢弃显示数据
+            }
             while let Ok(_) = self.state.channels.audio_receiver.try_recv() {
                 // 丢弃音频数据
             }
@@ -272,6 +563,34 @@ impl SensorDataApp {
     }
 
     pub fn handle_keyboard_input(&mut self, ctx: &egui::Context) {
+        // J/K 或 PageDown/PageUp - 历史面板中切换下一个/上一个session（文本框获得焦点时不处理）
+        // 不使用左右箭头，避免与文本阅读器的翻行冲突
+        if self.state.history.show_history_panel && !ctx.wants_keyboard_input() {
+            let (next_pressed, previous_pressed) = ctx.input(|i| {
+                (
+                    i.key_pressed(egui::Key::J) || i.key_pressed(egui::Key::PageDown),
+                    i.key_pressed(egui::Key::K) || i.key_pressed(egui::Key::PageUp),
+                )
+            });
+
+            let target_session = if next_pressed {
+                self.state.next_session()
+            } else if previous_pressed {
+                self.state.previous_session()
+            } else {
+                None
+            };
+
+            if let Some(session) = target_session {
+                crate::app::ui::history_controls::load_both_data_types_from_main(self, &session);
+            }
+        }
+
+        // F12 - 保存整个窗口的截图（在input闭包外发送视口命令，避免重复加锁）
+        if ctx.input(|i| i.key_pressed(egui::Key::F12)) {
+            self.request_screenshot(ctx);
+        }
+
         ctx.input(|i| {
             if i.key_pressed(egui::Key::Space) {
                 // 空格键同时处理文本切换和数据保存
@@ -294,6 +613,23 @@ impl SensorDataApp {
                 }
             }
 
+            // Ctrl+S - 保存当前窗口并立即导出该session
+            if i.modifiers.command && i.key_pressed(egui::Key::S) {
+                if self.state.is_actively_collecting() {
+                    // 保存未能排队时不导出，否则导出的文件会缺少这个窗口
+                    let session_id = self.state.collection.current_session_id.clone();
+                    if self.save_current_window_data_async() {
+                        if let Err(e) = self.state.export_session_immediately(&session_id, self.config.get_config().export.clone()) {
+                            warn!("Save-and-export failed for session {}: {}", session_id, e);
+                        }
+                    }
+                } else if self.state.collection.is_paused {
+                    self.state.collection.save_status = "Data collection is paused".to_string();
+                } else {
+                    self.state.collection.save_status = "Not collecting data".to_string();
+                }
+            }
+
             // 左箭头键 - 上一行文本
             if i.key_pressed(egui::Key::ArrowLeft) && self.state.text_reader.is_enabled {
                 self.state.previous_text_line();
@@ -310,8 +646,19 @@ impl SensorDataApp {
     pub fn play_history_audio(&mut self) {
         if let Some(ref mut player) = self.audio_player {
             if !self.state.history.loaded_audio_data.is_empty() {
-                // 加载音频数据到播放器
-                player.load_audio_data(&self.state.history.loaded_audio_data, 16000.0);
+                // 加载音频数据到播放器，手动覆盖采样率时只影响播放音调，不影响波形横轴
+                let playback = &self.state.history.audio_playback;
+                let sample_rate = if playback.override_sample_rate {
+                    playback.sample_rate_override_hz
+                } else {
+                    self.state.history.audio_sample_rate
+                };
+                player.set_preferred_output_rate(self.config.get_config().audio.preferred_output_rate);
+                player.load_audio_data(
+                    &self.state.history.loaded_audio_data,
+                    sample_rate as f32,
+                    playback.normalize,
+                );
 
                 // 开始播放
                 match player.play() {
@@ -360,6 +707,7 @@ impl SensorDataApp {
 
             let state = player.get_state();
             self.state.history.audio_playback.is_available = player.is_available();
+            self.state.history.audio_playback.position_samples = player.get_position_samples();
 
             match state {
                 PlaybackState::Playing => {
@@ -375,6 +723,10 @@ impl SensorDataApp {
                     self.state.history.audio_playback.is_paused = false;
                 }
             }
+
+            let playback = &self.state.history.audio_playback;
+            self.state.history.time_cursor_ms = (playback.is_playing || playback.is_paused)
+                .then(|| playback.position_samples as f64 * 1000.0 / playback.sample_rate as f64);
         }
     }
 }
\ No newline at end of file
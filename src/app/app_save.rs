@@ -1,34 +1,29 @@
 use std::time::{Duration, Instant};
 use log::{info, warn, error};
 
-use crate::types::{DataPoint, DatabaseTask};
+use crate::config::DbQueueFullPolicy;
+use crate::types::DatabaseTask;
 use super::app_core::SensorDataApp;
 
 impl SensorDataApp {
-    pub fn save_current_window_data_async(&mut self) {
-        // 获取当前窗口的加速度数据
-        let acc_data = self.state.waveform_plot.get_current_accelerometer_data();
+    /// 保存当前窗口，返回保存任务是否已发送或已暂存等待发送
+    pub fn save_current_window_data_async(&mut self) -> bool {
+        let username = self.state.collection.username.clone();
+        let scenario = self.state.collection.scenario.clone();
+        self.save_current_window_data_as(username, scenario)
+    }
+
+    /// 按指定的用户名/场景保存当前窗口，返回保存任务是否已发送或已暂存等待发送
+    fn save_current_window_data_as(&mut self, username: String, scenario: String) -> bool {
+        // 获取当前窗口的加速度数据（使用发送过来的真实时间戳）
+        let acc_points = self.state.waveform_plot.get_current_accelerometer_data();
         let audio_data = self.state.waveform_plot.get_current_audio_data();
 
-        if acc_data.is_empty() && audio_data.is_empty() {
+        if acc_points.is_empty() && audio_data.is_empty() {
             self.state.collection.save_status = "No data to save".to_string();
-            return;
+            return false;
         }
 
-        // 转换加速度数据为DataPoint格式，使用发送过来的真实时间戳
-        let acc_points: Vec<DataPoint> = acc_data
-            .into_iter()
-            .map(|(x, y, z, gx, gy, gz, timestamp)| DataPoint {
-                x,
-                y,
-                z,
-                gx,
-                gy,
-                gz,
-                timestamp, // 直接使用发送过来的时间戳
-            })
-            .collect();
-
         // 获取当前窗口内第一个和最后一个音频数据点的时间戳
         let audio_start_timestamp = self.state.waveform_plot.get_current_audio_first_timestamp();
         let audio_end_timestamp = self.state.waveform_plot.get_current_audio_last_timestamp();
@@ -41,27 +36,107 @@ impl SensorDataApp {
             audio_start_timestamp,
             audio_end_timestamp,
             session_id: self.state.collection.current_session_id.clone(),
-            username: self.state.collection.username.clone(),
-            scenario: self.state.collection.scenario.clone(),
+            username,
+            scenario,
         };
 
-        // 发送保存任务到后台线程
-        match self.state.database.db_task_sender.try_send(save_task) {
+        // 发送保存任务到后台线程，按配置决定队列满时是否短暂等待
+        let channels = &self.config.get_config().channels;
+        let policy = channels.db_queue_full_policy;
+        let send_result = match policy {
+            DbQueueFullPolicy::Block => {
+                // 在GUI线程上等待，无论配置如何都不超过MAX_BLOCK_TIMEOUT_MS
+                let timeout = Duration::from_millis(channels.db_queue_block_timeout_ms.min(DbQueueFullPolicy::MAX_BLOCK_TIMEOUT_MS));
+                self.state.database.db_task_sender.send_timeout(save_task, timeout).map_err(|e| match e {
+                    crossbeam_channel::SendTimeoutError::Timeout(task) => crossbeam_channel::TrySendError::Full(task),
+                    crossbeam_channel::SendTimeoutError::Disconnected(task) => crossbeam_channel::TrySendError::Disconnected(task),
+                })
+            }
+            DbQueueFullPolicy::Drop | DbQueueFullPolicy::PauseCollection => {
+                self.state.database.db_task_sender.try_send(save_task)
+            }
+        };
+
+        match send_result {
             Ok(()) => {
                 self.state.collection.save_status = "Saving data...".to_string();
                 info!("Save task sent to background thread");
+                true
             }
-            Err(crossbeam_channel::TrySendError::Full(_)) => {
-                self.state.collection.save_status = "Database queue is full, try again later".to_string();
-                warn!("Database task queue is full, task not sent");
+            Err(crossbeam_channel::TrySendError::Full(save_task)) => {
+                self.state.collection.save_queue_full_count += 1;
+                if policy == DbQueueFullPolicy::PauseCollection {
+                    // 暂存任务并暂停采集，由retry_pending_saves在队列空出后重新发送
+                    self.state.database.pending_saves.0.push_back(save_task);
+                    if self.state.is_actively_collecting() {
+                        self.state.pause_collection();
+                        self.state.collection.paused_for_db_backlog = true;
+                    }
+                    self.state.collection.save_status = "Database queue is full, collection paused until the backlog clears".to_string();
+                    warn!("Database task queue is full, save deferred and collection paused");
+                    true
+                } else {
+                    self.state.collection.save_status = "Database queue is full, window not saved".to_string();
+                    warn!("Database task queue is full, task not sent");
+                    false
+                }
             }
             Err(crossbeam_channel::TrySendError::Disconnected(_)) => {
                 self.state.collection.save_status = "Database connection lost! Please restart the application.".to_string();
                 error!("Database task channel disconnected - database thread may have crashed");
+                false
             }
         }
     }
 
+    /// 采集中修改用户名/场景前调用：按原用户名/场景保存当前窗口并清空缓冲区，
+    /// 避免已缓冲的数据在下次保存时被记到新的用户名/场景下。每次编辑只执行一次
+    pub fn flush_window_before_identity_change(&mut self, previous_username: String, previous_scenario: String) {
+        if !self.config.get_config().database.save_on_identity_change
+            || !self.state.collection.is_collecting
+            || self.state.collection.identity_change_flushed
+        {
+            return;
+        }
+        self.state.collection.identity_change_flushed = true;
+
+        info!("User/scenario changed during collection, saving current window as {}/{}", previous_username, previous_scenario);
+        if self.save_current_window_data_as(previous_username, previous_scenario) {
+            self.state.waveform_plot.clear_accelerometer_only();
+            self.state.waveform_plot.clear_audio_only();
+            self.state.collection.auto_save_last_time = None;
+        }
+    }
+
+    /// 按顺序重新发送队列满时暂存的保存任务（及排在其后的导出任务）；全部发出且队列降到一半以下后恢复因积压而暂停的采集
+    pub fn retry_pending_saves(&mut self) {
+        let database = &mut self.state.database;
+        while let Some(save_task) = database.pending_saves.0.pop_front() {
+            match database.db_task_sender.try_send(save_task) {
+                Ok(()) => info!("Deferred database task sent to background thread"),
+                Err(crossbeam_channel::TrySendError::Full(save_task)) => {
+                    database.pending_saves.0.push_front(save_task);
+                    return;
+                }
+                Err(crossbeam_channel::TrySendError::Disconnected(_)) => {
+                    database.pending_saves.0.clear();
+                    self.state.collection.save_status = "Database connection lost! Please restart the application.".to_string();
+                    error!("Database task channel disconnected - deferred saves lost");
+                    return;
+                }
+            }
+        }
+
+        let sender = &database.db_task_sender;
+        let backlog_cleared = sender.capacity().is_none_or(|capacity| sender.len() <= capacity / 2);
+        if self.state.collection.paused_for_db_backlog && backlog_cleared {
+            self.state.collection.paused_for_db_backlog = false;
+            self.state.resume_collection();
+            self.state.collection.save_status = "Database backlog cleared, collection resumed".to_string();
+            info!("Database backlog cleared, collection resumed");
+        }
+    }
+
     /// 检查是否需要自动保存
     pub fn check_auto_save(&mut self) {
         if !self.state.collection.auto_save_enabled {
@@ -1,7 +1,11 @@
-use log::info;
+use log::{info, warn, error};
 use crate::app::app_core::SensorDataApp;
 use crate::types::DataPoint;
 
+/// 校准结果的合理采样率范围（Hz）
+pub const MIN_PLAUSIBLE_SAMPLE_RATE: f64 = 50.0;
+pub const MAX_PLAUSIBLE_SAMPLE_RATE: f64 = 2000.0;
+
 pub struct CalibrationHandler;
 
 impl CalibrationHandler {
@@ -10,6 +14,8 @@ impl CalibrationHandler {
         while let Ok(data) = app.state.channels.data_receiver.try_recv() {
             Self::process_calibration_data(app, data);
         }
+        // 校准只需要完整的时间戳，显示通道的数据直接丢弃
+        while app.state.channels.display_receiver.try_recv().is_ok() {}
 
         // 检查是否达到校准持续时间（校准结束）
         if let Some(start_time) = app.state.calibration.calibration_start_time {
@@ -20,12 +26,34 @@ impl CalibrationHandler {
             }
         }
 
-        // 校准期间丢弃音频数据
-        while let Ok(_) = app.state.channels.audio_receiver.try_recv() {
-            // 丢弃音频数据
+        // 校准期间根据配置缓存或丢弃音频数据
+        let buffer_audio = app.config.get_config().calibration.buffer_audio;
+        while let Ok(audio_data) = app.state.channels.audio_receiver.try_recv() {
+            if buffer_audio {
+                app.state.calibration.buffered_audio.push(audio_data);
+            }
         }
     }
 
+    /// 跳过自动校准，直接按配置的初始采样率开始采集
+    /// 用于采样率已知或自动校准耗时过长的快速测试
+    pub fn quick_start(app: &mut SensorDataApp) {
+        if !app.state.calibration.is_calibrating {
+            return;
+        }
+
+        let sample_rate = app.config.get_config().calibration.initial_sample_rate as f64;
+        info!("跳过校准，按初始采样率 {:.2} Hz 开始采集", sample_rate);
+
+        // 与自动校准完成时一致：开始采集后丢弃前N个样本
+        app.state.collection.discard_remaining = app.config.get_config().calibration.discard_initial_samples;
+        app.state.collection.discard_until_timestamp = None;
+
+        app.state.complete_calibration(sample_rate, &app.config.get_config().plot);
+
+        info!("开始正常数据采集模式");
+    }
+
     fn process_calibration_data(app: &mut SensorDataApp, data: DataPoint) {
         // 如果这是第一个样本，开始计时
         if app.state.calibration.calibration_start_time.is_none() {
@@ -64,11 +92,34 @@ impl CalibrationHandler {
         let sample_count = app.state.calibration.calibration_data.len() as f64;
 
         if time_diff_ms > 0 {
-            let sample_rate = (sample_count - 1.0) * 1000.0 / time_diff_ms as f64;
+            let mut sample_rate = (sample_count - 1.0) * 1000.0 / time_diff_ms as f64;
 
             info!("校准完成: {} 个样本 (2-8秒数据), 时间差 {}ms, 计算采样率: {:.2} Hz", 
                   sample_count, time_diff_ms, sample_rate);
 
+            // 瞬时的MQTT突发可能导致不合理的采样率，此时重新校准；
+            // 重试次数用完（或关闭了自动重新校准）时退回配置的初始采样率
+            if !(MIN_PLAUSIBLE_SAMPLE_RATE..=MAX_PLAUSIBLE_SAMPLE_RATE).contains(&sample_rate) {
+                let max_retries = app.config.get_config().calibration.max_retries;
+                if app.state.auto_calibration_restart && app.state.calibration.retry_count < max_retries {
+                    app.state.calibration.retry_count += 1;
+                    warn!("校准得到的采样率 {:.2} Hz 不在合理范围 ({}-{} Hz) 内，重新开始校准 ({}/{})",
+                          sample_rate, MIN_PLAUSIBLE_SAMPLE_RATE, MAX_PLAUSIBLE_SAMPLE_RATE,
+                          app.state.calibration.retry_count, max_retries);
+                    app.state.reset_calibration();
+                    return;
+                }
+
+                let fallback_rate = app.config.get_config().calibration.initial_sample_rate as f64;
+                error!("校准得到的采样率 {:.2} Hz 不合理，已重试 {} 次，改用初始采样率 {:.2} Hz",
+                       sample_rate, app.state.calibration.retry_count, fallback_rate);
+                sample_rate = fallback_rate;
+            }
+
+            // 开始采集后丢弃前N个样本，避免记录以瞬态开头
+            app.state.collection.discard_remaining = app.config.get_config().calibration.discard_initial_samples;
+            app.state.collection.discard_until_timestamp = None;
+
             // 使用新的状态管理方法完成校准
             app.state.complete_calibration(sample_rate, &app.config.get_config().plot);
 
@@ -0,0 +1,70 @@
+use log::{info, warn, error};
+
+/// 超过该占用比例时记录警告
+const AUDIO_CHANNEL_WARNING_RATIO: f64 = 0.5;
+/// 超过该占用比例时记录错误并在状态栏提示
+const AUDIO_CHANNEL_CRITICAL_RATIO: f64 = 0.9;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum FillLevel {
+    #[default]
+    Normal,
+    Warning,
+    Critical,
+}
+
+/// 音频通道占用监控：每帧读取通道长度，只在占用等级变化时写日志，并记录最高占用
+#[derive(Debug, Default)]
+pub struct AudioChannelMonitor {
+    level: FillLevel,
+    last_len: usize,
+    capacity: usize,
+    high_water_mark: usize,
+}
+
+impl AudioChannelMonitor {
+    pub fn poll(&mut self, len: usize, capacity: Option<usize>) {
+        let Some(capacity) = capacity.filter(|capacity| *capacity > 0) else {
+            return;
+        };
+
+        self.last_len = len;
+        self.capacity = capacity;
+        self.high_water_mark = self.high_water_mark.max(len);
+
+        let ratio = len as f64 / capacity as f64;
+        let level = if ratio > AUDIO_CHANNEL_CRITICAL_RATIO {
+            FillLevel::Critical
+        } else if ratio > AUDIO_CHANNEL_WARNING_RATIO {
+            FillLevel::Warning
+        } else {
+            FillLevel::Normal
+        };
+
+        if level > self.level {
+            match level {
+                FillLevel::Critical => error!("Audio channel is {}/{} ({:.0}%) full, audio data will be dropped soon", len, capacity, ratio * 100.0),
+                FillLevel::Warning => warn!("Audio channel is {}/{} ({:.0}%) full", len, capacity, ratio * 100.0),
+                FillLevel::Normal => {}
+            }
+        } else if level == FillLevel::Normal && self.level != FillLevel::Normal {
+            info!("Audio channel usage back to {}/{}", len, capacity);
+        }
+        self.level = level;
+    }
+
+    /// 占用超过90%时返回状态栏提示
+    pub fn status_message(&self) -> Option<String> {
+        (self.level == FillLevel::Critical).then(|| {
+            format!("⚠ Audio queue {}/{} full", self.last_len, self.capacity)
+        })
+    }
+
+    pub fn high_water_mark(&self) -> usize {
+        self.high_water_mark
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
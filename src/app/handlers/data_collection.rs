@@ -5,43 +5,139 @@ pub struct DataCollectionHandler;
 
 impl DataCollectionHandler {
     pub fn handle_collection(app: &mut SensorDataApp) {
+        // 零偏只用于实时波形显示，缓冲区和保存的数据保持原始值
+        let axis_offsets = app.config.get_config().calibration.axis_offsets;
+        app.state.waveform_plot.set_axis_offsets(axis_offsets);
+
         // 正常采集模式
         while let Ok(data) = app.state.channels.data_receiver.try_recv() {
+            app.state.collection.last_data_time = Some(std::time::Instant::now());
             // info!("ACC data - x: {:.3}, y: {:.3}, z: {:.3}, time: {}", 
             //       data.x, data.y, data.z, format_timestamp(data.timestamp));
-            app.state.waveform_plot.add_data(data.x, data.y, data.z, data.gx, data.gy, data.gz, data.timestamp);
+            if Self::should_discard_sample(app, data.timestamp) {
+                continue;
+            }
+            app.state.waveform_plot.add_data(&data);
+        }
+
+        // 显示通道的数据已按ingest_decimation抽样，只用于绘图
+        while let Ok(data) = app.state.channels.display_receiver.try_recv() {
+            if Self::in_discarded_range(app, data.timestamp) {
+                continue;
+            }
+            app.state.waveform_plot.add_display_data(&data);
         }
         
         // 处理音频数据
         while let Ok(audio_data) = app.state.channels.audio_receiver.try_recv() {
             // info!("Audio data - samples: {}, time: {}", 
             //       audio_data.samples, format_timestamp(audio_data.timestamp));
-            
-            app.state.database.last_audio_metadata = Some(audio_data.clone());
+            if Self::in_discarded_range(app, audio_data.timestamp) {
+                continue;
+            }
+
+            app.state.database.last_audio_metadata = Some(mono_audio_metadata(&audio_data));
             Self::process_audio_data(app, &audio_data);
         }
+
+        Self::check_silence_auto_stop(app);
     }
     
+    /// 传感器长时间无数据时自动停止采集
+    fn check_silence_auto_stop(app: &mut SensorDataApp) {
+        let silence_limit_secs = app.config.get_config().mqtt.auto_stop_after_silence_secs;
+        if silence_limit_secs == 0 {
+            return;
+        }
+
+        let Some(last_data_time) = app.state.collection.last_data_time else {
+            return;
+        };
+
+        let silence = last_data_time.elapsed();
+        if silence.as_secs() >= silence_limit_secs {
+            log::warn!("已 {:.1} 秒未收到传感器数据，自动停止采集", silence.as_secs_f64());
+            app.state.stop_collection();
+            app.state.collection.save_status = format!(
+                "Collection auto-stopped: no sensor data for {}s", silence_limit_secs
+            );
+        }
+    }
+
+    /// 开始采集后丢弃前N个加速度样本，并记录被丢弃数据的最后时间戳
+    fn should_discard_sample(app: &mut SensorDataApp, timestamp: i64) -> bool {
+        let collection = &mut app.state.collection;
+        if collection.discard_remaining == 0 {
+            return false;
+        }
+
+        collection.discard_remaining -= 1;
+        collection.discard_until_timestamp = Some(timestamp);
+        if collection.discard_remaining == 0 {
+            log::info!("已丢弃起始瞬态样本，截止时间戳 {}", timestamp);
+        }
+        true
+    }
+
+    /// 是否落在被丢弃加速度样本的时间范围内，同一范围内的音频和显示数据也丢弃
+    fn in_discarded_range(app: &SensorDataApp, timestamp: i64) -> bool {
+        let collection = &app.state.collection;
+        if collection.discard_remaining > 0 {
+            return true;
+        }
+
+        collection.discard_until_timestamp.is_some_and(|until| timestamp <= until)
+    }
+
     fn process_audio_data(app: &mut SensorDataApp, audio_data: &crate::types::AudioData) {
-        // 解码Base64音频数据
-        match general_purpose::STANDARD.decode(&audio_data.audio_data) {
-            Ok(decoded_bytes) => {
-                // 将字节数据转换为i16样本
-                let mut samples = Vec::new();
-                for chunk in decoded_bytes.chunks_exact(2) {
-                    let sample = i16::from_le_bytes([chunk[0], chunk[1]]);
-                    samples.push(sample);
-                }
-                
-                // 将音频样本添加到波形绘制器
-                if !samples.is_empty() {
-                    // 直接使用原始音频样本，不进行下采样
-                    app.state.waveform_plot.add_audio_samples(&samples, audio_data.timestamp, audio_data.sample_rate);
-                }
+        // 将音频样本添加到波形绘制器
+        if let Some(samples) = decode_pcm16_samples(audio_data) {
+            if !samples.is_empty() {
+                // 直接使用原始音频样本，不进行下采样
+                app.state.waveform_plot.add_audio_samples(&samples, audio_data.timestamp, audio_data.sample_rate);
             }
-            Err(e) => {
-                log::warn!("Failed to decode audio data: {}", e);
+        }
+    }
+}
+
+/// 解码Base64编码的PCM_16音频数据为i16样本，双声道数据会混合为单声道
+pub fn decode_pcm16_samples(audio_data: &crate::types::AudioData) -> Option<Vec<i16>> {
+    match general_purpose::STANDARD.decode(&audio_data.audio_data) {
+        Ok(decoded_bytes) => {
+            if audio_data.channels == 2 {
+                return Some(downmix_stereo_pcm16(&decoded_bytes));
+            }
+
+            // 将字节数据转换为i16样本
+            let mut samples = Vec::new();
+            for chunk in decoded_bytes.chunks_exact(2) {
+                let sample = i16::from_le_bytes([chunk[0], chunk[1]]);
+                samples.push(sample);
             }
+            Some(samples)
+        }
+        Err(e) => {
+            log::warn!("Failed to decode audio data: {}", e);
+            None
         }
     }
 }
+
+/// 将交错存储的双声道PCM_16字节（每4字节为一对左右声道样本）取平均混合为单声道
+fn downmix_stereo_pcm16(bytes: &[u8]) -> Vec<i16> {
+    bytes
+        .chunks_exact(4)
+        .map(|frame| {
+            let left = i16::from_le_bytes([frame[0], frame[1]]) as i32;
+            let right = i16::from_le_bytes([frame[2], frame[3]]) as i32;
+            ((left + right) / 2) as i16
+        })
+        .collect()
+}
+
+/// 保存用的音频元数据：波形缓冲区中的音频已统一为单声道
+pub fn mono_audio_metadata(audio_data: &crate::types::AudioData) -> crate::types::AudioData {
+    let mut metadata = audio_data.clone();
+    metadata.channels = 1;
+    metadata
+}
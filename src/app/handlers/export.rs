@@ -7,12 +7,15 @@ pub struct ExportHandler;
 impl ExportHandler {
     pub fn refresh_sessions(app: &mut SensorDataApp) {
         let (response_sender, response_receiver) = crossbeam_channel::bounded(1);
-        let task = DatabaseTask::GetAllSessionsWithExportStatus { response_sender };
+        let task = DatabaseTask::GetSessionsWithMetadata { response_sender };
         
         match app.state.database.db_task_sender.try_send(task) {
             Ok(()) => {
                 app.state.export.export_status = "Refreshing sessions list...".to_string();
                 app.state.export.sessions_result_receiver = Some(response_receiver);
+                // session数据可能已变化，重新估算大小
+                app.state.export.size_estimates.clear();
+                app.state.export.size_estimates_requested.clear();
             }
             Err(e) => {
                 app.state.export.export_status = format!("Failed to request sessions list: {}", e);
@@ -20,6 +23,23 @@ impl ExportHandler {
         }
     }
 
+    /// 请求指定session的导出大小估算（每个session只请求一次）
+    pub fn request_size_estimate(app: &mut SensorDataApp, session_id: &str) {
+        if app.state.export.size_estimates_requested.contains(session_id) {
+            return;
+        }
+
+        let task = DatabaseTask::GetSessionSizeEstimate {
+            session_id: session_id.to_string(),
+            response_sender: app.state.export.size_estimate_sender.clone(),
+        };
+
+        // 队列满时不记录，下一帧重试
+        if app.state.database.db_task_sender.try_send(task).is_ok() {
+            app.state.export.size_estimates_requested.insert(session_id.to_string());
+        }
+    }
+
     pub fn export_selected_sessions(app: &mut SensorDataApp) {
         if app.state.export.selected_sessions.is_empty() {
             app.state.export.export_status = "Please select sessions to export first".to_string();
@@ -27,16 +47,73 @@ impl ExportHandler {
         }
 
         let session_ids: Vec<String> = app.state.export.selected_sessions.iter().cloned().collect();
+        if Self::start_sessions_export(app, session_ids, "Exporting selected sessions...") {
+            app.state.export.selected_sessions.clear();
+        }
+    }
+
+    /// 只重新导出上一次导出失败的session
+    pub fn retry_failed_exports(app: &mut SensorDataApp) {
+        let session_ids: Vec<String> = app.state.export.failed_exports.iter()
+            .map(|(session_id, _)| session_id.clone())
+            .collect();
+        if session_ids.is_empty() {
+            return;
+        }
+
+        let status = format!("Retrying {} failed sessions...", session_ids.len());
+        if Self::start_sessions_export(app, session_ids, &status) {
+            app.state.export.failed_exports.clear();
+        }
+    }
+
+    /// 发送逐session导出任务并显示进度，返回任务是否已发送
+    fn start_sessions_export(app: &mut SensorDataApp, session_ids: Vec<String>, status: &str) -> bool {
         let (response_sender, response_receiver) = crossbeam_channel::bounded(1);
+        let (progress_sender, progress_receiver) = crossbeam_channel::unbounded();
         
-        let task = DatabaseTask::Export {
+        let task = DatabaseTask::ExportWithProgress {
             export_type: ExportType::SelectedSessions(session_ids),
+            options: app.config.get_config().export.clone(),
+            progress_sender,
             response_sender,
         };
         
         match app.state.database.db_task_sender.try_send(task) {
             Ok(()) => {
-                app.state.export.export_status = "Exporting selected sessions...".to_string();
+                app.state.export.export_status = status.to_string();
+                app.state.export.export_result_receiver = Some(response_receiver);
+                app.state.export.export_progress_receiver = Some(progress_receiver);
+                app.state.export.export_progress = None;
+                true
+            }
+            Err(e) => {
+                app.state.export.export_status = format!("Failed to start export: {}", e);
+                false
+            }
+        }
+    }
+
+    pub fn export_comparison(app: &mut SensorDataApp) {
+        if app.state.export.selected_sessions.len() < 2 {
+            app.state.export.export_status = "Please select at least 2 sessions to compare".to_string();
+            return;
+        }
+
+        // session ID以时间命名，排序后即按采集时间排列
+        let mut session_ids: Vec<String> = app.state.export.selected_sessions.iter().cloned().collect();
+        session_ids.sort();
+        let (response_sender, response_receiver) = crossbeam_channel::bounded(1);
+
+        let task = DatabaseTask::Export {
+            export_type: ExportType::Comparison(session_ids),
+            options: app.config.get_config().export.clone(),
+            response_sender,
+        };
+
+        match app.state.database.db_task_sender.try_send(task) {
+            Ok(()) => {
+                app.state.export.export_status = "Exporting session comparison...".to_string();
                 app.state.export.selected_sessions.clear();
                 app.state.export.export_result_receiver = Some(response_receiver);
             }
@@ -48,9 +125,12 @@ impl ExportHandler {
 
     pub fn export_new_sessions_only(app: &mut SensorDataApp) {
         let (response_sender, response_receiver) = crossbeam_channel::bounded(1);
+        let (progress_sender, progress_receiver) = crossbeam_channel::unbounded();
         
-        let task = DatabaseTask::Export {
+        let task = DatabaseTask::ExportWithProgress {
             export_type: ExportType::NewSessions,
+            options: app.config.get_config().export.clone(),
+            progress_sender,
             response_sender,
         };
         
@@ -58,6 +138,8 @@ impl ExportHandler {
             Ok(()) => {
                 app.state.export.export_status = "Exporting new sessions...".to_string();
                 app.state.export.export_result_receiver = Some(response_receiver);
+                app.state.export.export_progress_receiver = Some(progress_receiver);
+                app.state.export.export_progress = None;
             }
             Err(e) => {
                 app.state.export.export_status = format!("Failed to start export: {}", e);
@@ -1,7 +1,9 @@
 pub mod calibration;
+pub mod channel_monitor;
 pub mod data_collection;
 pub mod export;
 
 pub use calibration::CalibrationHandler;
+pub use channel_monitor::AudioChannelMonitor;
 pub use data_collection::DataCollectionHandler;
 pub use export::ExportHandler;
@@ -6,3 +6,4 @@ pub mod handlers;
 pub mod state;
 
 pub use app_core::SensorDataApp;
+pub use state::GuiChannels;
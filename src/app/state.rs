@@ -1,8 +1,12 @@
-use std::collections::HashSet;
-use std::time::Instant;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use crossbeam_channel::{Receiver, Sender};
-use crate::types::{DataPoint, AudioData, DatabaseTask, SaveResult, ExportResult};
+use crate::types::{DataPoint, AudioData, DatabaseTask, SaveResult, ExportResult, ExportProgress, ExportType, SessionSizeEstimate, SessionInfo, SessionSummary, SessionStatistics, IntegrityReport, LoadProgress, SessionLookup, DEFAULT_AUDIO_SAMPLE_RATE};
 use crate::plotter::WaveformPlot;
+use crate::mqtt::{ChannelDropCounters, MqttControl, MqttStats};
+use crate::database::{DatabaseChannelHandoff, SharedDatabaseStatus};
 
 /// 应用状态管理模块
 /// 将原本分散在SensorDataApp中的状态分离到独立的结构体中
@@ -12,6 +16,7 @@ use crate::plotter::WaveformPlot;
 pub struct CollectionState {
     pub is_collecting: bool,
     pub is_paused: bool,
+    pub collection_started_at: Option<Instant>, // 本次采集开始的时间，用于状态栏的录制计时
     pub current_session_id: String,
     pub save_status: String,
     pub username: String,
@@ -21,6 +26,22 @@ pub struct CollectionState {
     pub auto_save_last_time: Option<Instant>,
     pub auto_save_interval_ms: u64,
     pub auto_save_count: u32,
+    // 开始采集时丢弃的瞬态样本
+    pub discard_remaining: usize,
+    pub discard_until_timestamp: Option<i64>,
+    // 最近一次收到传感器数据的时间，用于静默自动停止
+    pub last_data_time: Option<Instant>,
+    // 本次编辑用户名/场景时是否已按原身份保存过窗口，输入框失去焦点后清除
+    pub identity_change_flushed: bool,
+    // 数据库队列满导致保存被丢弃或暂存的次数，状态栏持续提示直到用户清除
+    pub save_queue_full_count: u32,
+    // 因数据库积压而自动暂停采集，积压处理完后自动恢复
+    pub paused_for_db_backlog: bool,
+    // 数据库中已有的用户名/场景，供输入框旁的下拉列表选择，避免手误产生相近的名字
+    pub recent_usernames: Vec<String>,
+    pub recent_scenarios: Vec<String>,
+    pub recent_usernames_receiver: Option<crossbeam_channel::Receiver<Vec<String>>>,
+    pub recent_scenarios_receiver: Option<crossbeam_channel::Receiver<Vec<String>>>,
 }
 
 impl Default for CollectionState {
@@ -28,6 +49,7 @@ impl Default for CollectionState {
         Self {
             is_collecting: false,
             is_paused: false,
+            collection_started_at: None,
             current_session_id: String::new(),
             save_status: String::new(),
             username: "test".to_string(),
@@ -37,6 +59,16 @@ impl Default for CollectionState {
             auto_save_last_time: None,
             auto_save_interval_ms: 10000, // 默认10秒间隔，窗口长度配置
             auto_save_count: 0,
+            discard_remaining: 0,
+            discard_until_timestamp: None,
+            last_data_time: None,
+            identity_change_flushed: false,
+            save_queue_full_count: 0,
+            paused_for_db_backlog: false,
+            recent_usernames: Vec::new(),
+            recent_scenarios: Vec::new(),
+            recent_usernames_receiver: None,
+            recent_scenarios_receiver: None,
         }
     }
 }
@@ -48,6 +80,8 @@ pub struct CalibrationState {
     pub calibration_data: Vec<DataPoint>,
     pub calibration_start_time: Option<Instant>,
     pub calculated_sample_rate: Option<f64>,
+    pub buffered_audio: Vec<AudioData>, // 校准期间缓存的音频数据
+    pub retry_count: u32, // 因采样率不合理而重新校准的次数，校准完成后清零
 }
 
 impl Default for CalibrationState {
@@ -57,6 +91,8 @@ impl Default for CalibrationState {
             calibration_data: Vec::new(),
             calibration_start_time: None,
             calculated_sample_rate: None,
+            buffered_audio: Vec::new(),
+            retry_count: 0,
         }
     }
 }
@@ -67,10 +103,18 @@ pub struct ExportState {
     pub export_status: String,
     pub show_export_dialog: bool,
     pub available_sessions: Vec<String>,
-    pub sessions_with_export_status: Vec<(String, bool)>, // (session_id, is_exported)
+    pub sessions_with_metadata: Vec<SessionInfo>,
     pub selected_sessions: HashSet<String>,
     pub export_result_receiver: Option<crossbeam_channel::Receiver<ExportResult>>,
-    pub sessions_result_receiver: Option<crossbeam_channel::Receiver<Vec<(String, bool)>>>,
+    pub export_progress: Option<ExportProgress>, // 进行中导出的最新进度
+    pub export_progress_receiver: Option<crossbeam_channel::Receiver<ExportProgress>>,
+    pub failed_exports: Vec<(String, String)>, // 最近一次导出失败的session及错误，用于只重试失败的部分
+    pub sessions_result_receiver: Option<crossbeam_channel::Receiver<Vec<SessionInfo>>>,
+    // 导出大小估算
+    pub size_estimates: HashMap<String, SessionSizeEstimate>,
+    pub size_estimates_requested: HashSet<String>,
+    pub size_estimate_sender: Sender<SessionSizeEstimate>,
+    pub size_estimate_receiver: Receiver<SessionSizeEstimate>,
 }
 
 /// 历史数据显示选项
@@ -82,7 +126,15 @@ pub struct HistoryDisplayOptions {
     pub show_gx_axis: bool,
     pub show_gy_axis: bool,
     pub show_gz_axis: bool,
+    pub show_acc_magnitude: bool,   // 显示加速度矢量模长 sqrt(x²+y²+z²)，与传感器朝向无关
+    pub show_gyro_magnitude: bool,  // 显示角速度矢量模长
     pub show_audio: bool,
+    pub show_spectrogram: bool,
+    pub show_smoothed: bool,      // 在原始波形上叠加滑动平均线
+    pub smoothing_window: usize,  // 滑动平均窗口（样本数）
+    pub show_orientation: bool,   // 显示由姿态四元数分解出的欧拉角
+    pub absolute_time: bool,      // 横轴显示绝对时间（HH:MM:SS）而不是相对起点的秒数
+    pub audio_envelope: bool,     // 音频波形按像素绘制最小/最大值包络，而不是每个样本
 }
 
 impl Default for HistoryDisplayOptions {
@@ -94,7 +146,15 @@ impl Default for HistoryDisplayOptions {
             show_gx_axis: false,  // 默认不显示陀螺仪，避免界面过于拥挤
             show_gy_axis: false,
             show_gz_axis: false,
+            show_acc_magnitude: false,
+            show_gyro_magnitude: false,
             show_audio: true,
+            show_spectrogram: false,
+            show_smoothed: false,
+            smoothing_window: 15,
+            show_orientation: false,
+            absolute_time: false,
+            audio_envelope: true,
         }
     }
 }
@@ -106,6 +166,10 @@ pub struct AudioPlaybackState {
     pub is_playing: bool,
     pub is_paused: bool,
     pub sample_rate: f32,
+    pub position_samples: u64, // 播放位置，用于在波形上绘制播放光标
+    pub normalize: bool,       // 播放前将音频峰值放大到满幅
+    pub override_sample_rate: bool, // 忽略存储的采样率，按手动指定的采样率播放（用于元数据错误的录音）
+    pub sample_rate_override_hz: u32,
 }
 
 impl Default for AudioPlaybackState {
@@ -114,7 +178,38 @@ impl Default for AudioPlaybackState {
             is_available: false,
             is_playing: false,
             is_paused: false,
-            sample_rate: 16000.0,
+            sample_rate: DEFAULT_AUDIO_SAMPLE_RATE as f32,
+            position_samples: 0,
+            normalize: false,
+            override_sample_rate: false,
+            sample_rate_override_hz: DEFAULT_AUDIO_SAMPLE_RATE,
+        }
+    }
+}
+
+/// 历史session列表的排序方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionSortOrder {
+    Date,
+    Size,
+    Duration,
+    ExportStatus,
+}
+
+impl SessionSortOrder {
+    pub const ALL: [SessionSortOrder; 4] = [
+        SessionSortOrder::Date,
+        SessionSortOrder::Size,
+        SessionSortOrder::Duration,
+        SessionSortOrder::ExportStatus,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SessionSortOrder::Date => "Newest first",
+            SessionSortOrder::Size => "Largest first",
+            SessionSortOrder::Duration => "Longest first",
+            SessionSortOrder::ExportStatus => "Unexported first",
         }
     }
 }
@@ -133,22 +228,57 @@ pub struct HistoryVisualizationState {
     pub aligned_audio_data: Vec<f64>,
     pub display_options: HistoryDisplayOptions,
     pub loading_status: String,
+    pub history_load_completed: bool, // 当前显示的数据是否已加载完成（用于区分"加载中"与"无数据"）
     pub available_usernames: Vec<String>,
     pub available_scenarios: Vec<String>,
     pub selected_scenario: Option<String>,
+    pub available_node_ids: Vec<String>,
+    pub selected_node_id: Option<String>, // None表示不按节点过滤
     pub history_sessions: Vec<String>,
+    // 按时长隐藏session（翻页时跳过），避免误按空格产生的极短session干扰浏览
+    pub filter_min_duration_ms: u64,
+    pub filter_max_duration_ms: Option<u64>,
+    pub session_sort_order: SessionSortOrder,
+    pub session_summaries: HashMap<String, SessionSummary>,
+    pub session_summaries_result_receiver: Option<crossbeam_channel::Receiver<Vec<SessionSummary>>>,
     pub history_result_receiver: Option<crossbeam_channel::Receiver<(Vec<DataPoint>, Vec<f64>)>>,
     pub aligned_history_result_receiver: Option<crossbeam_channel::Receiver<(Vec<DataPoint>, Vec<f64>, i64)>>,
+    pub load_progress: Option<Arc<LoadProgress>>, // 进行中的历史数据加载，用于显示进度和取消
+    pub time_cursor_ms: Option<f64>, // 音频播放位置（相对数据起点），在所有历史波形上同步绘制
+    pub export_range_s: Option<(f64, f64)>, // 片段导出的起止时间（相对数据起点的秒数），None表示尚未选择
+    pub audio_sample_rate: u32, // 当前session音频的采样率
+    pub audio_sample_rate_receiver: Option<crossbeam_channel::Receiver<u32>>,
+    pub session_statistics: Option<SessionStatistics>, // 最近一次计算的session统计量
+    pub session_statistics_receiver: Option<crossbeam_channel::Receiver<SessionStatistics>>,
+    // 按session ID直接打开
+    pub session_lookup_input: String,
+    pub session_lookup_receiver: Option<(String, crossbeam_channel::Receiver<SessionLookup>)>,
     pub common_time_range_ms: i64,
     pub sessions_result_receiver: Option<crossbeam_channel::Receiver<Vec<String>>>,
     pub usernames_result_receiver: Option<crossbeam_channel::Receiver<Vec<String>>>,
     pub scenarios_result_receiver: Option<crossbeam_channel::Receiver<Vec<String>>>,
+    pub node_ids_result_receiver: Option<crossbeam_channel::Receiver<Vec<String>>>,
     pub panel_width: f32,
     pub show_aligned_data: bool,
     pub delete_result_receiver: Option<crossbeam_channel::Receiver<Result<(), String>>>,
+    pub export_result_receiver: Option<crossbeam_channel::Receiver<ExportResult>>,
     pub show_delete_confirmation: bool,
-    pub session_to_delete: Option<String>,
+    pub sessions_to_delete: Vec<String>, // 待确认/正在删除的session，单个删除时只有一个
+    pub multi_selected_sessions: HashSet<String>, // Ctrl+点击翻页时累积选中的session，用于批量导出/删除
+    pub reviewed_sessions: HashSet<String>, // 已人工审阅的session，持久化在数据库session_metadata表
+    pub reviewed_sessions_receiver: Option<crossbeam_channel::Receiver<Vec<String>>>,
+    pub set_reviewed_result_receiver: Option<crossbeam_channel::Receiver<Result<(), String>>>,
+    // 场景重命名
+    pub show_rename_scenario_dialog: bool,
+    pub rename_scenario_new_name: String,
+    pub rename_scenario_result_receiver: Option<crossbeam_channel::Receiver<Result<usize, String>>>,
+    pub duplicate_result_receiver: Option<crossbeam_channel::Receiver<Result<String, String>>>,
+    // 数据库完整性检查
+    pub integrity_report: Option<IntegrityReport>,
+    pub integrity_report_receiver: Option<crossbeam_channel::Receiver<Result<IntegrityReport, String>>>,
     pub audio_playback: AudioPlaybackState,
+    pub spectrogram_cache: crate::spectrogram::SpectrogramCache,
+    pub audio_waveform_cache: crate::plotter::AudioWaveformCache,
     // 缓存相关字段
     pub usernames_cache: Option<Vec<String>>,
     pub sessions_cache: std::collections::HashMap<String, Vec<String>>,
@@ -157,18 +287,43 @@ pub struct HistoryVisualizationState {
 
 impl Default for ExportState {
     fn default() -> Self {
+        let (size_estimate_sender, size_estimate_receiver) = crossbeam_channel::unbounded();
         Self {
             export_status: String::new(),
             show_export_dialog: false,
             available_sessions: Vec::new(),
-            sessions_with_export_status: Vec::new(),
+            sessions_with_metadata: Vec::new(),
             selected_sessions: HashSet::new(),
             export_result_receiver: None,
+            export_progress: None,
+            export_progress_receiver: None,
+            failed_exports: Vec::new(),
             sessions_result_receiver: None,
+            size_estimates: HashMap::new(),
+            size_estimates_requested: HashSet::new(),
+            size_estimate_sender,
+            size_estimate_receiver,
         }
     }
 }
 
+impl HistoryVisualizationState {
+    /// session时长是否在过滤范围内；摘要尚未返回的session不过滤
+    pub fn passes_duration_filter(&self, session_id: &str) -> bool {
+        let Some(summary) = self.session_summaries.get(session_id) else {
+            return true;
+        };
+        let duration_ms = summary.duration_ms.max(0) as u64;
+        duration_ms >= self.filter_min_duration_ms
+            && self.filter_max_duration_ms.is_none_or(|max| duration_ms <= max)
+    }
+
+    /// 通过时长过滤的session数量
+    pub fn visible_session_count(&self) -> usize {
+        self.history_sessions.iter().filter(|s| self.passes_duration_filter(s)).count()
+    }
+}
+
 impl Default for HistoryVisualizationState {
     fn default() -> Self {
         Self {
@@ -183,22 +338,53 @@ impl Default for HistoryVisualizationState {
             aligned_audio_data: Vec::new(),
             display_options: HistoryDisplayOptions::default(),
             loading_status: String::new(),
+            history_load_completed: false,
             available_usernames: Vec::new(),
             available_scenarios: Vec::new(),
             selected_scenario: None,
+            available_node_ids: Vec::new(),
+            selected_node_id: None,
             history_sessions: Vec::new(),
+            filter_min_duration_ms: 0,
+            filter_max_duration_ms: None,
+            session_sort_order: SessionSortOrder::Date,
+            session_summaries: HashMap::new(),
+            session_summaries_result_receiver: None,
             history_result_receiver: None,
             aligned_history_result_receiver: None,
+            load_progress: None,
+            time_cursor_ms: None,
+            export_range_s: None,
+            audio_sample_rate: DEFAULT_AUDIO_SAMPLE_RATE,
+            audio_sample_rate_receiver: None,
+            session_statistics: None,
+            session_statistics_receiver: None,
+            session_lookup_input: String::new(),
+            session_lookup_receiver: None,
             common_time_range_ms: 0,
             sessions_result_receiver: None,
             usernames_result_receiver: None,
             scenarios_result_receiver: None,
+            node_ids_result_receiver: None,
             panel_width: 300.0, // 默认侧边面板宽度
             show_aligned_data: true, // 默认显示对齐后的数据
             delete_result_receiver: None,
+            export_result_receiver: None,
             show_delete_confirmation: false,
-            session_to_delete: None,
+            sessions_to_delete: Vec::new(),
+            multi_selected_sessions: HashSet::new(),
+            reviewed_sessions: HashSet::new(),
+            reviewed_sessions_receiver: None,
+            set_reviewed_result_receiver: None,
+            show_rename_scenario_dialog: false,
+            rename_scenario_new_name: String::new(),
+            rename_scenario_result_receiver: None,
+            duplicate_result_receiver: None,
+            integrity_report: None,
+            integrity_report_receiver: None,
             audio_playback: AudioPlaybackState::default(),
+            spectrogram_cache: crate::spectrogram::SpectrogramCache::default(),
+            audio_waveform_cache: crate::plotter::AudioWaveformCache::default(),
             // 缓存相关字段
             usernames_cache: None,
             sessions_cache: std::collections::HashMap::new(),
@@ -213,13 +399,55 @@ pub struct DatabaseState {
     pub db_task_sender: Sender<DatabaseTask>,
     pub save_result_receiver: Receiver<SaveResult>,
     pub last_audio_metadata: Option<AudioData>,
+    pub status: SharedDatabaseStatus, // 数据库线程的连接状态
+    pub channel_handoff: DatabaseChannelHandoff, // 数据库线程崩溃重启后的新通道
+    pub pending_saves: PendingSaves, // 队列满时暂存、等待重新发送的保存任务（以及排在它们之后的导出任务）
+    pub size_bytes: Option<u64>, // 数据库文件大小，定期从数据库线程刷新
+    pub size_receiver: Option<Receiver<u64>>,
+    pub size_requested_at: Option<Instant>,
+}
+
+/// 数据库队列满时暂存的保存任务，按产生顺序重新发送
+#[derive(Clone, Default)]
+pub struct PendingSaves(pub VecDeque<DatabaseTask>);
+
+impl std::fmt::Debug for PendingSaves {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PendingSaves")
+            .field("len", &self.0.len())
+            .finish()
+    }
+}
+
+/// 通道统计的刷新间隔
+const CHANNEL_STATS_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 状态日志保留的最近条目数
+const STATUS_HISTORY_LIMIT: usize = 20;
+
+/// 数据库文件大小的刷新间隔
+const DATABASE_SIZE_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 状态栏显示的通道收发统计，按固定间隔从共享计数器刷新
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ChannelStats {
+    pub acc_total: u64,
+    pub acc_dropped: u64,
+    pub audio_total: u64,
+    pub audio_dropped: u64,
 }
 
 /// 数据通道状态
 #[derive(Debug)]
 pub struct DataChannels {
     pub data_receiver: Receiver<DataPoint>,
+    pub display_receiver: Receiver<DataPoint>, // 抽样后的传感器数据，仅用于绘图
+    pub ingest_decimation: usize, // MQTT线程向显示通道转发数据点的间隔，启动时确定
     pub audio_receiver: Receiver<AudioData>,
+    pub mqtt_control: Arc<MqttControl>, // MQTT连接状态与手动重连信号
+    pub mqtt_stats: Arc<Mutex<MqttStats>>, // MQTT消息接收与解析统计
+    pub drop_counters: ChannelDropCounters, // 通道满时丢弃的数据条数
+    pub audio_monitor: crate::app::handlers::AudioChannelMonitor, // 音频通道占用告警和最高占用
 }
 
 /// 文本阅读器状态
@@ -230,6 +458,9 @@ pub struct TextReaderState {
     pub current_text: String,
     pub is_enabled: bool,
     pub file_loaded: bool,
+    pub repeat_last_on_wrap: bool, // 到达末尾时保持最后一行，否则回到第一行
+    pub end_of_file_reached: bool,
+    pub file_path: Option<PathBuf>, // 当前加载的文本文件
 }
 
 impl Default for TextReaderState {
@@ -240,10 +471,48 @@ impl Default for TextReaderState {
             current_text: String::new(),
             is_enabled: false,
             file_loaded: false,
+            repeat_last_on_wrap: true,
+            end_of_file_reached: false,
+            file_path: None,
         }
     }
 }
 
+/// 设置窗口标签页
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsTab {
+    Window,
+    Mqtt,
+    Plot,
+    Calibration,
+    Channels,
+}
+
+/// 设置窗口状态
+#[derive(Debug, Clone)]
+pub struct SettingsState {
+    pub show_settings_panel: bool,
+    pub active_tab: SettingsTab,
+    pub status: String,
+}
+
+impl Default for SettingsState {
+    fn default() -> Self {
+        Self {
+            show_settings_panel: false,
+            active_tab: SettingsTab::Window,
+            status: String::new(),
+        }
+    }
+}
+
+/// 关于对话框状态
+#[derive(Debug, Clone, Default)]
+pub struct AboutState {
+    pub show_about_dialog: bool,
+    pub duckdb_version: Option<String>, // 首次打开时查询并缓存
+}
+
 /// 统一的应用状态管理
 #[derive(Debug)]
 pub struct AppState {
@@ -255,21 +524,56 @@ pub struct AppState {
     pub channels: DataChannels,
     pub waveform_plot: WaveformPlot,
     pub text_reader: TextReaderState,
+    pub settings: SettingsState,
+    pub about: AboutState,
+    pub auto_calibration_restart: bool, // 校准得到不合理的采样率时自动重新校准
+    pub pending_center_y: Option<f32>, // 仅配置了y坐标时，待获取显示器尺寸后水平居中
+    pub channel_stats: ChannelStats,
+    pub channel_stats_refreshed_at: Option<Instant>,
+    pub export_status_history: VecDeque<(Instant, String)>, // 最近的保存/导出状态，状态栏只显示最新一条会被覆盖
+    pub show_status_log: bool,
+    pub screenshot_pending: bool, // 已请求截图，等待下一帧的 Event::Screenshot
+}
+
+/// GUI持有的通道端和与后台线程共享的句柄，由main创建后整体交给应用
+pub struct GuiChannels {
+    pub data_receiver: Receiver<DataPoint>,
+    pub display_receiver: Receiver<DataPoint>,
+    pub ingest_decimation: usize,
+    pub audio_receiver: Receiver<AudioData>,
+    pub db_task_sender: Sender<DatabaseTask>,
+    pub save_result_receiver: Receiver<SaveResult>,
+    pub mqtt_control: Arc<MqttControl>,
+    pub mqtt_stats: Arc<Mutex<MqttStats>>,
+    pub drop_counters: ChannelDropCounters,
+    pub db_status: SharedDatabaseStatus,
+    pub db_channel_handoff: DatabaseChannelHandoff,
 }
 
 impl AppState {
     /// 创建新的应用状态
-    pub fn new(
-        data_receiver: Receiver<DataPoint>,
-        audio_receiver: Receiver<AudioData>,
-        db_task_sender: Sender<DatabaseTask>,
-        save_result_receiver: Receiver<SaveResult>,
-        config: &crate::config::AppConfig,
-    ) -> Self {
+    pub fn new(channels: GuiChannels, config: &crate::config::AppConfig) -> Self {
+        let GuiChannels {
+            data_receiver,
+            display_receiver,
+            ingest_decimation,
+            audio_receiver,
+            db_task_sender,
+            save_result_receiver,
+            mqtt_control,
+            mqtt_stats,
+            drop_counters,
+            db_status,
+            db_channel_handoff,
+        } = channels;
         let initial_sample_rate = config.calibration.initial_sample_rate;
 
         Self {
-            collection: CollectionState::default(),
+            collection: CollectionState {
+                username: config.session.initial_username(),
+                scenario: config.session.initial_scenario(),
+                ..CollectionState::default()
+            },
             calibration: CalibrationState::default(),
             export: ExportState::default(),
             history: HistoryVisualizationState::default(),
@@ -277,13 +581,157 @@ impl AppState {
                 db_task_sender,
                 save_result_receiver,
                 last_audio_metadata: None,
+                status: db_status,
+                channel_handoff: db_channel_handoff,
+                pending_saves: PendingSaves::default(),
+                size_bytes: None,
+                size_receiver: None,
+                size_requested_at: None,
             },
             channels: DataChannels {
                 data_receiver,
+                display_receiver,
+                ingest_decimation,
                 audio_receiver,
+                mqtt_control,
+                mqtt_stats,
+                drop_counters,
+                audio_monitor: Default::default(),
             },
-            waveform_plot: WaveformPlot::new(initial_sample_rate, &config.plot),
+            waveform_plot: WaveformPlot::new(initial_sample_rate, &config.plot, ingest_decimation),
             text_reader: TextReaderState::default(),
+            settings: SettingsState::default(),
+            about: AboutState::default(),
+            auto_calibration_restart: true,
+            pending_center_y: match (config.window.x, config.window.y) {
+                (None, Some(y)) => Some(y),
+                _ => None,
+            },
+            channel_stats: ChannelStats::default(),
+            channel_stats_refreshed_at: None,
+            export_status_history: VecDeque::with_capacity(STATUS_HISTORY_LIMIT),
+            show_status_log: false,
+            screenshot_pending: false,
+        }
+    }
+
+    /// 向数据库请求已有的用户名和场景列表，结果由 handle_recent_identity_results 接收
+    pub fn request_recent_identities(&mut self) {
+        let (usernames_sender, usernames_receiver) = crossbeam_channel::bounded(1);
+        let (scenarios_sender, scenarios_receiver) = crossbeam_channel::bounded(1);
+        let sender = &self.database.db_task_sender;
+        if sender.try_send(DatabaseTask::GetUsernames { response_sender: usernames_sender }).is_ok() {
+            self.collection.recent_usernames_receiver = Some(usernames_receiver);
+        }
+        if sender.try_send(DatabaseTask::GetScenarios { response_sender: scenarios_sender }).is_ok() {
+            self.collection.recent_scenarios_receiver = Some(scenarios_receiver);
+        }
+    }
+
+    /// 记录一条保存/导出状态，只保留最近的 STATUS_HISTORY_LIMIT 条
+    pub fn record_status(&mut self, message: &str) {
+        if self.export_status_history.len() >= STATUS_HISTORY_LIMIT {
+            self.export_status_history.pop_front();
+        }
+        self.export_status_history.push_back((Instant::now(), message.to_string()));
+    }
+
+    /// 每5秒从通道共享计数器刷新一次收发统计
+    pub fn refresh_channel_stats(&mut self) {
+        let now = Instant::now();
+        if self.channel_stats_refreshed_at.is_some_and(|t| now.duration_since(t) < CHANNEL_STATS_REFRESH_INTERVAL) {
+            return;
+        }
+        self.channel_stats_refreshed_at = Some(now);
+
+        let counters = &self.channels.drop_counters;
+        self.channel_stats = ChannelStats {
+            acc_total: counters.data_received(),
+            acc_dropped: counters.data_dropped(),
+            audio_total: counters.audio_received(),
+            audio_dropped: counters.audio_dropped(),
+        };
+    }
+
+    /// 数据库线程崩溃后由监控线程重启，改用重启时新建的通道
+    pub fn adopt_restarted_database_channels(&mut self) {
+        let Some((task_sender, result_receiver)) = self.database.channel_handoff.take() else {
+            return;
+        };
+
+        self.database.db_task_sender = task_sender;
+        self.database.save_result_receiver = result_receiver;
+        // 旧线程上等待中的请求不会再有响应，丢弃所有进行中的接收器，需要时由界面重新请求
+        self.database.size_receiver = None;
+        self.collection.recent_usernames_receiver = None;
+        self.collection.recent_scenarios_receiver = None;
+
+        let export = &mut self.export;
+        if export.export_result_receiver.take().is_some() {
+            export.export_status = "Export interrupted: database thread restarted".to_string();
+        }
+        export.export_progress_receiver = None;
+        export.export_progress = None;
+        export.sessions_result_receiver = None;
+        export.size_estimates_requested.clear();
+
+        let history = &mut self.history;
+        let delete_pending = history.delete_result_receiver.take().is_some();
+        if delete_pending {
+            // 确认删除对话框打开时sessions_to_delete仍在等待确认，只在删除已发出时清空
+            history.sessions_to_delete.clear();
+        }
+        let interrupted = [
+            history.history_result_receiver.take().is_some(),
+            history.aligned_history_result_receiver.take().is_some(),
+            history.export_result_receiver.take().is_some(),
+            delete_pending,
+        ];
+        if interrupted.contains(&true) {
+            history.loading_status = "Interrupted: database thread restarted".to_string();
+        }
+        history.load_progress = None;
+        history.session_summaries_result_receiver = None;
+        history.audio_sample_rate_receiver = None;
+        history.session_statistics_receiver = None;
+        history.session_lookup_receiver = None;
+        history.sessions_result_receiver = None;
+        history.usernames_result_receiver = None;
+        history.scenarios_result_receiver = None;
+        history.node_ids_result_receiver = None;
+        history.reviewed_sessions_receiver = None;
+        history.set_reviewed_result_receiver = None;
+        history.rename_scenario_result_receiver = None;
+        history.duplicate_result_receiver = None;
+        history.integrity_report_receiver = None;
+
+        self.collection.save_status = "Database thread restarted after a failure".to_string();
+        log::warn!("数据库线程已重启，GUI已切换到新的任务通道");
+    }
+
+    /// 接收数据库大小的查询结果，并每30秒重新请求一次
+    pub fn refresh_database_size(&mut self) {
+        let database = &mut self.database;
+        if let Some(receiver) = &database.size_receiver {
+            match receiver.try_recv() {
+                Ok(size) => {
+                    database.size_bytes = Some(size);
+                    database.size_receiver = None;
+                }
+                Err(crossbeam_channel::TryRecvError::Disconnected) => database.size_receiver = None,
+                Err(crossbeam_channel::TryRecvError::Empty) => return,
+            }
+        }
+
+        let now = Instant::now();
+        if database.size_requested_at.is_some_and(|t| now.duration_since(t) < DATABASE_SIZE_REFRESH_INTERVAL) {
+            return;
+        }
+        database.size_requested_at = Some(now);
+
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        if database.db_task_sender.try_send(DatabaseTask::GetDatabaseSize { response_sender: sender }).is_ok() {
+            database.size_receiver = Some(receiver);
         }
     }
 
@@ -302,6 +750,22 @@ impl AppState {
         }
     }
 
+    /// 实时音频电平：取音频缓冲区末尾最多512个样本计算RMS并换算为dBFS，缓冲区为空时返回None
+    /// 静音时RMS为0，按-100 dBFS下限返回，避免出现负无穷
+    pub fn get_live_audio_rms_db(&self) -> Option<f64> {
+        const LEVEL_WINDOW_SAMPLES: usize = 512;
+
+        let (sum_squares, count) = self.waveform_plot
+            .recent_audio_samples(LEVEL_WINDOW_SAMPLES)
+            .fold((0.0, 0usize), |(sum, count), sample| (sum + sample * sample, count + 1));
+        if count == 0 {
+            return None;
+        }
+
+        let rms = (sum_squares / count as f64).sqrt();
+        Some((20.0 * rms.log10()).max(-100.0))
+    }
+
     /// 检查是否有数据需要保存
     pub fn has_data_to_save(&self) -> bool {
         !self.waveform_plot.get_current_accelerometer_data().is_empty() ||
@@ -313,6 +777,7 @@ impl AppState {
         self.calibration.calibration_data.clear();
         self.calibration.calibration_start_time = None;
         self.calibration.calculated_sample_rate = None;
+        self.calibration.buffered_audio.clear();
         self.calibration.is_calibrating = true;
     }
 
@@ -320,10 +785,26 @@ impl AppState {
     pub fn complete_calibration(&mut self, sample_rate: f64, config: &crate::config::PlotConfig) {
         self.calibration.is_calibrating = false;
         self.calibration.calculated_sample_rate = Some(sample_rate);
+        self.calibration.retry_count = 0;
         self.collection.is_collecting = true;
+        self.collection.collection_started_at = Some(Instant::now());
+        self.collection.last_data_time = Some(Instant::now());
 
         // 使用计算出的采样率和配置重新创建 WaveformPlot
-        self.waveform_plot = WaveformPlot::new(sample_rate as usize, config);
+        self.waveform_plot = WaveformPlot::new(sample_rate as usize, config, self.channels.ingest_decimation);
+
+        // 需要丢弃起始样本时，校准期间缓存的音频早于被丢弃的数据，一并丢弃
+        if self.collection.discard_remaining > 0 {
+            self.calibration.buffered_audio.clear();
+        }
+
+        // 将校准期间缓存的音频交给新的波形缓冲区，使其进入第一个保存窗口
+        for audio_data in self.calibration.buffered_audio.drain(..) {
+            if let Some(samples) = crate::app::handlers::data_collection::decode_pcm16_samples(&audio_data) {
+                self.waveform_plot.add_audio_samples(&samples, audio_data.timestamp, audio_data.sample_rate);
+            }
+            self.database.last_audio_metadata = Some(crate::app::handlers::data_collection::mono_audio_metadata(&audio_data));
+        }
 
         // 清空校准数据
         self.calibration.calibration_data.clear();
@@ -334,12 +815,15 @@ impl AppState {
     pub fn stop_collection(&mut self) {
         self.collection.is_collecting = false;
         self.collection.is_paused = false;
+        self.collection.collection_started_at = None;
     }
 
     /// 开始采集
     pub fn start_collection(&mut self) {
         self.collection.is_collecting = true;
         self.collection.is_paused = false;
+        self.collection.collection_started_at = Some(Instant::now());
+        self.collection.last_data_time = Some(Instant::now());
     }
 
     /// 暂停采集
@@ -353,6 +837,7 @@ impl AppState {
     pub fn resume_collection(&mut self) {
         if self.collection.is_collecting {
             self.collection.is_paused = false;
+            self.collection.last_data_time = Some(Instant::now());
         }
     }
 
@@ -361,14 +846,145 @@ impl AppState {
         self.collection.is_collecting && !self.collection.is_paused
     }
 
+    /// 立即导出指定session（不经过导出对话框）
+    pub fn export_session_immediately(&mut self, session_id: &str, options: crate::config::ExportConfig) -> Result<(), String> {
+        let (response_sender, response_receiver) = crossbeam_channel::bounded(1);
+        let task = DatabaseTask::Export {
+            export_type: ExportType::SelectedSessions(vec![session_id.to_string()]),
+            options,
+            response_sender,
+        };
+
+        // 还有暂存的保存任务时排在它们之后，保证导出包含这些窗口
+        if !self.database.pending_saves.0.is_empty() {
+            self.database.pending_saves.0.push_back(task);
+            self.export.export_status = format!("Export of session {} queued after pending saves", session_id);
+            self.export.export_result_receiver = Some(response_receiver);
+            return Ok(());
+        }
+
+        match self.database.db_task_sender.try_send(task) {
+            Ok(()) => {
+                self.export.export_status = format!("Exporting session {}...", session_id);
+                self.export.export_result_receiver = Some(response_receiver);
+                Ok(())
+            }
+            Err(e) => {
+                let message = format!("Failed to start export: {}", e);
+                self.export.export_status = message.clone();
+                Err(message)
+            }
+        }
+    }
+
+    /// 立即导出历史面板中当前查看的session，结果显示在历史面板的状态栏
+    pub fn export_selected_history_session_immediately(&mut self, options: crate::config::ExportConfig) -> Result<(), String> {
+        let Some(session_id) = self.history.selected_session.clone() else {
+            return Err("No history session selected".to_string());
+        };
+
+        self.export_history_sessions_immediately(vec![session_id], options)
+    }
+
+    /// 立即导出历史面板中多选的session，结果显示在历史面板的状态栏
+    pub fn export_multi_selected_history_sessions_immediately(&mut self, options: crate::config::ExportConfig) -> Result<(), String> {
+        if self.history.multi_selected_sessions.is_empty() {
+            return Err("No history sessions selected".to_string());
+        }
+
+        let mut session_ids: Vec<String> = self.history.multi_selected_sessions.iter().cloned().collect();
+        session_ids.sort();
+        self.export_history_sessions_immediately(session_ids, options)
+    }
+
+    /// 导出历史面板中当前session的一段时间范围（相对数据起点的秒数），结果显示在历史面板的状态栏
+    pub fn export_history_range_immediately(&mut self, start_s: f64, end_s: f64, options: crate::config::ExportConfig) -> Result<(), String> {
+        let Some(session_id) = self.history.selected_session.clone() else {
+            return Err("No history session selected".to_string());
+        };
+
+        let start_ms = (start_s * 1000.0).round() as i64;
+        let end_ms = (end_s * 1000.0).round() as i64;
+        if end_ms <= start_ms {
+            return Err("Range end must be after range start".to_string());
+        }
+
+        let (response_sender, response_receiver) = crossbeam_channel::bounded(1);
+        let task = DatabaseTask::Export {
+            export_type: ExportType::TimeRange { session_id: session_id.clone(), start_ms, end_ms },
+            options,
+            response_sender,
+        };
+
+        match self.database.db_task_sender.try_send(task) {
+            Ok(()) => {
+                self.history.loading_status = format!("Exporting {:.2}s - {:.2}s of session {}...", start_s, end_s, session_id);
+                self.history.export_result_receiver = Some(response_receiver);
+                Ok(())
+            }
+            Err(e) => {
+                let message = format!("Failed to start export: {}", e);
+                self.history.loading_status = message.clone();
+                Err(message)
+            }
+        }
+    }
+
+    fn export_history_sessions_immediately(&mut self, session_ids: Vec<String>, options: crate::config::ExportConfig) -> Result<(), String> {
+        let status = match session_ids.as_slice() {
+            [session_id] => format!("Exporting session {}...", session_id),
+            _ => format!("Exporting {} sessions...", session_ids.len()),
+        };
+
+        let (response_sender, response_receiver) = crossbeam_channel::bounded(1);
+        let task = DatabaseTask::Export {
+            export_type: ExportType::SelectedSessions(session_ids),
+            options,
+            response_sender,
+        };
+
+        match self.database.db_task_sender.try_send(task) {
+            Ok(()) => {
+                self.history.loading_status = status;
+                self.history.export_result_receiver = Some(response_receiver);
+                Ok(())
+            }
+            Err(e) => {
+                let message = format!("Failed to start export: {}", e);
+                self.history.loading_status = message.clone();
+                Err(message)
+            }
+        }
+    }
+
+    /// 请求数据库线程流式计算指定session的各轴统计量，结果在事件循环中接收
+    pub fn collect_statistics_for_session(&mut self, session_id: &str) -> Result<(), String> {
+        let (response_sender, response_receiver) = crossbeam_channel::bounded(1);
+        let task = DatabaseTask::ComputeSessionStatistics {
+            session_id: session_id.to_string(),
+            response_sender,
+        };
+
+        match self.database.db_task_sender.try_send(task) {
+            Ok(()) => {
+                self.history.session_statistics = None;
+                self.history.session_statistics_receiver = Some(response_receiver);
+                Ok(())
+            }
+            Err(e) => Err(format!("Failed to request session statistics: {}", e)),
+        }
+    }
+
     /// 加载文本文件
-    pub fn load_text_file(&mut self, file_path: &str) -> Result<(), String> {
+    pub fn load_text_file<P: AsRef<Path>>(&mut self, file_path: P) -> Result<(), String> {
         use std::fs;
-        match fs::read_to_string(file_path) {
+        match fs::read_to_string(file_path.as_ref()) {
             Ok(content) => {
+                self.text_reader.file_path = Some(file_path.as_ref().to_path_buf());
                 self.text_reader.lines = content.lines().map(|s| s.to_string()).collect();
                 self.text_reader.current_line_index = 0;
                 self.text_reader.file_loaded = true;
+                self.text_reader.end_of_file_reached = false;
                 if !self.text_reader.lines.is_empty() {
                     self.text_reader.current_text = self.text_reader.lines[0].clone();
                 }
@@ -378,6 +994,21 @@ impl AppState {
         }
     }
 
+    /// 通过系统文件选择对话框选择并加载文本文件，用户取消时返回Ok(None)
+    pub fn load_text_file_from_dialog(&mut self) -> Result<Option<PathBuf>, String> {
+        let mut dialog = rfd::FileDialog::new().add_filter("Text", &["txt"]);
+        if let Some(directory) = self.text_reader.file_path.as_deref().and_then(Path::parent) {
+            dialog = dialog.set_directory(directory);
+        }
+
+        let Some(file_path) = dialog.pick_file() else {
+            return Ok(None);
+        };
+
+        self.load_text_file(&file_path)?;
+        Ok(Some(file_path))
+    }
+
     /// 切换到下一行文本
     pub fn next_text_line(&mut self) {
         if !self.text_reader.file_loaded || self.text_reader.lines.is_empty() {
@@ -387,9 +1018,28 @@ impl AppState {
         if self.text_reader.current_line_index + 1 < self.text_reader.lines.len() {
             self.text_reader.current_line_index += 1;
             self.text_reader.current_text = self.text_reader.lines[self.text_reader.current_line_index].clone();
+            if self.text_reader.current_line_index + 1 == self.text_reader.lines.len() {
+                self.text_reader.end_of_file_reached = true;
+            }
+        } else if self.text_reader.repeat_last_on_wrap {
+            // 保持最后一行可见
+            self.text_reader.end_of_file_reached = true;
+        } else {
+            self.restart_text_reader();
         }
     }
 
+    /// 回到文本第一行
+    pub fn restart_text_reader(&mut self) {
+        if !self.text_reader.file_loaded || self.text_reader.lines.is_empty() {
+            return;
+        }
+
+        self.text_reader.current_line_index = 0;
+        self.text_reader.current_text = self.text_reader.lines[0].clone();
+        self.text_reader.end_of_file_reached = false;
+    }
+
     /// 切换到上一行文本
     pub fn previous_text_line(&mut self) {
         if !self.text_reader.file_loaded || self.text_reader.lines.is_empty() {
@@ -399,6 +1049,7 @@ impl AppState {
         if self.text_reader.current_line_index > 0 {
             self.text_reader.current_line_index -= 1;
             self.text_reader.current_text = self.text_reader.lines[self.text_reader.current_line_index].clone();
+            self.text_reader.end_of_file_reached = false;
         }
     }
 
@@ -413,8 +1064,14 @@ impl AppState {
     /// 获取当前session信息
     pub fn get_current_session_info(&self) -> String {
         if let Some(ref session) = self.history.selected_session {
-            if let Some(index) = self.history.history_sessions.iter().position(|s| s == session) {
-                format!("{}/{}", index + 1, self.history.history_sessions.len())
+            let history = &self.history;
+            if history.history_sessions.contains(session) {
+                // 序号只计通过时长过滤的session，当前session被过滤时不显示序号
+                let visible = history.visible_session_count();
+                match history.history_sessions.iter().filter(|s| history.passes_duration_filter(s)).position(|s| s == session) {
+                    Some(index) => format!("{}/{}", index + 1, visible),
+                    None => format!("-/{}", visible),
+                }
             } else {
                 "1/1".to_string()
             }
@@ -423,14 +1080,43 @@ impl AppState {
         }
     }
 
-    /// 切换到上一个session
+    /// 按当前排序方式重新排列历史session列表，保持当前选中的session不变
+    pub fn sort_history_sessions(&mut self) {
+        let summaries = &self.history.session_summaries;
+        let sessions = &mut self.history.history_sessions;
+
+        // session_id带有时间戳，按ID倒序即为最新优先，也作为其他排序的次级顺序
+        sessions.sort_by(|a, b| b.cmp(a));
+        match self.history.session_sort_order {
+            SessionSortOrder::Date => {}
+            SessionSortOrder::Size => {
+                sessions.sort_by_key(|id| std::cmp::Reverse(summaries.get(id).map_or(0, |s| s.acc_rows)));
+            }
+            SessionSortOrder::Duration => {
+                sessions.sort_by_key(|id| std::cmp::Reverse(summaries.get(id).map_or(0, |s| s.duration_ms)));
+            }
+            SessionSortOrder::ExportStatus => {
+                sessions.sort_by_key(|id| summaries.get(id).is_some_and(|s| s.is_exported));
+            }
+        }
+
+        if let Some(selected) = &self.history.selected_session {
+            if let Some(index) = sessions.iter().position(|s| s == selected) {
+                self.history.current_session_index = index;
+            }
+        }
+    }
+
+    /// 切换到上一个session，跳过不满足时长过滤的session
     pub fn previous_session(&mut self) -> Option<String> {
         if let Some(ref current_session) = self.history.selected_session {
             if let Some(current_index) = self.history.history_sessions.iter().position(|s| s == current_session) {
-                if current_index > 0 {
-                    let previous_session = self.history.history_sessions[current_index - 1].clone();
+                let previous_index = (0..current_index).rev()
+                    .find(|&index| self.history.passes_duration_filter(&self.history.history_sessions[index]));
+                if let Some(previous_index) = previous_index {
+                    let previous_session = self.history.history_sessions[previous_index].clone();
                     self.history.selected_session = Some(previous_session.clone());
-                    self.history.current_session_index = current_index - 1;
+                    self.history.current_session_index = previous_index;
                     return Some(previous_session);
                 }
             }
@@ -438,14 +1124,16 @@ impl AppState {
         None
     }
 
-    /// 切换到下一个session
+    /// 切换到下一个session，跳过不满足时长过滤的session
     pub fn next_session(&mut self) -> Option<String> {
         if let Some(ref current_session) = self.history.selected_session {
             if let Some(current_index) = self.history.history_sessions.iter().position(|s| s == current_session) {
-                if current_index + 1 < self.history.history_sessions.len() {
-                    let next_session = self.history.history_sessions[current_index + 1].clone();
+                let next_index = (current_index + 1..self.history.history_sessions.len())
+                    .find(|&index| self.history.passes_duration_filter(&self.history.history_sessions[index]));
+                if let Some(next_index) = next_index {
+                    let next_session = self.history.history_sessions[next_index].clone();
                     self.history.selected_session = Some(next_session.clone());
-                    self.history.current_session_index = current_index + 1;
+                    self.history.current_session_index = next_index;
                     return Some(next_session);
                 }
             }
@@ -0,0 +1,66 @@
+use eframe::egui;
+use crate::app::app_core::SensorDataApp;
+
+pub fn render_about_dialog(app: &mut SensorDataApp, ctx: &egui::Context) {
+    if !app.state.about.show_about_dialog {
+        return;
+    }
+
+    // DuckDB版本需要打开内存数据库查询，只在首次打开时执行
+    let duckdb_version = app.state.about.duckdb_version
+        .get_or_insert_with(crate::database::duckdb_version)
+        .clone();
+
+    let mut open = true;
+    egui::Window::new("❓ About SenseHub")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .default_width(360.0)
+        .show(ctx, |ui| {
+            ui.heading(format!("SenseHub v{}", env!("CARGO_PKG_VERSION")));
+            ui.add_space(6.0);
+
+            egui::Grid::new("about_grid")
+                .num_columns(2)
+                .spacing([20.0, 4.0])
+                .show(ui, |ui| {
+                    ui.label("Version:");
+                    ui.label(env!("CARGO_PKG_VERSION"));
+                    ui.end_row();
+
+                    ui.label("Build date:");
+                    ui.label(env!("SENSEHUB_BUILD_DATE"));
+                    ui.end_row();
+
+                    ui.label("OS:");
+                    ui.label(format!("{} ({})", std::env::consts::OS, std::env::consts::ARCH));
+                    ui.end_row();
+
+                    ui.label("Rust:");
+                    ui.label(env!("SENSEHUB_RUSTC_VERSION"));
+                    ui.end_row();
+
+                    ui.label("DuckDB:");
+                    ui.label(&duckdb_version);
+                    ui.end_row();
+                });
+
+            ui.add_space(6.0);
+            if ui.button("📋 Copy").on_hover_text("Copy version and system info").clicked() {
+                ctx.copy_text(format!(
+                    "SenseHub {}\nBuild: {}\nOS: {} ({})\n{}\nDuckDB {}",
+                    env!("CARGO_PKG_VERSION"),
+                    env!("SENSEHUB_BUILD_DATE"),
+                    std::env::consts::OS,
+                    std::env::consts::ARCH,
+                    env!("SENSEHUB_RUSTC_VERSION"),
+                    duckdb_version,
+                ));
+            }
+        });
+
+    if !open {
+        app.state.about.show_about_dialog = false;
+    }
+}
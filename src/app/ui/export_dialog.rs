@@ -1,13 +1,14 @@
 use eframe::egui;
 use crate::app::app_core::SensorDataApp;
 use crate::app::handlers::ExportHandler;
+use crate::utils::{format_bytes, format_duration_ms};
 
 pub fn render_export_dialog(app: &mut SensorDataApp, ctx: &egui::Context) {
     if app.state.export.show_export_dialog {
         egui::Window::new("Export Database Data")
             .collapsible(false)
             .resizable(true)
-            .default_width(500.0)
+            .default_width(700.0)
             .show(ctx, |ui| {
                 ui.label("Export session data from database to CSV files (one file per session)");
                 ui.add_space(10.0);
@@ -21,9 +22,17 @@ pub fn render_export_dialog(app: &mut SensorDataApp, ctx: &egui::Context) {
                 
                 render_session_list(app, ui);
                 
+                ui.add_space(10.0);
+
+                render_csv_format_options(app, ui);
+
                 ui.add_space(10.0);
                 
                 render_export_buttons(app, ui);
+
+                render_export_progress(app, ui);
+
+                render_export_failures(app, ui);
                 
                 ui.add_space(5.0);
                 ui.label("Note: Each session will be exported as a separate CSV file, filename format: session_id.csv");
@@ -31,57 +40,142 @@ pub fn render_export_dialog(app: &mut SensorDataApp, ctx: &egui::Context) {
     }
 }
 
+fn render_csv_format_options(app: &mut SensorDataApp, ui: &mut egui::Ui) {
+    let export_config = &mut app.config.get_config_mut().export;
+
+    ui.horizontal(|ui| {
+        ui.label("CSV delimiter:");
+        let delimiter_name = |delimiter: char| match delimiter {
+            ',' => "Comma (,)".to_string(),
+            ';' => "Semicolon (;)".to_string(),
+            '\t' => "Tab (\\t)".to_string(),
+            other => format!("'{}'", other),
+        };
+        egui::ComboBox::from_id_salt("export_csv_delimiter")
+            .selected_text(delimiter_name(export_config.csv_delimiter))
+            .show_ui(ui, |ui| {
+                for delimiter in [',', ';', '\t'] {
+                    ui.selectable_value(&mut export_config.csv_delimiter, delimiter, delimiter_name(delimiter));
+                }
+            });
+
+        ui.checkbox(&mut export_config.csv_quote_strings, "Quote header strings");
+        ui.checkbox(&mut export_config.csv_metadata_header, "Metadata comments")
+            .on_hover_text("Start each CSV with '#' comment lines describing the session (some parsers reject comment lines)");
+        ui.checkbox(&mut export_config.auto_export_on_exit, "Export new sessions on exit")
+            .on_hover_text("When the app closes normally, export every session that has not been exported yet");
+    });
+}
+
 fn render_session_list(app: &mut SensorDataApp, ui: &mut egui::Ui) {
-    if app.state.export.sessions_with_export_status.is_empty() {
+    if app.state.export.sessions_with_metadata.is_empty() {
         ui.label("No session data found");
     } else {
-        let total_sessions = app.state.export.sessions_with_export_status.len();
-        let exported_count = app.state.export.sessions_with_export_status.iter()
-            .filter(|(_, is_exported)| *is_exported).count();
+        let total_sessions = app.state.export.sessions_with_metadata.len();
+        let exported_count = app.state.export.sessions_with_metadata.iter()
+            .filter(|session| session.is_exported).count();
         let unexported_count = total_sessions - exported_count;
         
         ui.label(format!("Found {} sessions ({} exported, {} unexported):", 
                         total_sessions, exported_count, unexported_count));
+
+        ui.horizontal(|ui| {
+            let export = &mut app.state.export;
+            if ui.button("✅ Select All").clicked() {
+                export.selected_sessions.extend(export.sessions_with_metadata.iter().map(|session| session.session_id.clone()));
+            }
+            if ui.button("⬜ Deselect All").clicked() {
+                export.selected_sessions.clear();
+            }
+            if ui.add_enabled(unexported_count > 0, egui::Button::new("Select Unexported Only")).clicked() {
+                export.selected_sessions = export.sessions_with_metadata.iter()
+                    .filter(|session| !session.is_exported)
+                    .map(|session| session.session_id.clone())
+                    .collect();
+            }
+            ui.label(format!("{} selected", export.selected_sessions.len()));
+        });
         ui.add_space(5.0);
         
         // Session selection list
         egui::ScrollArea::vertical()
             .max_height(200.0)
             .show(ui, |ui| {
-                for (session_id, is_exported) in &app.state.export.sessions_with_export_status.clone() {
-                    ui.horizontal(|ui| {
-                        let mut selected = app.state.export.selected_sessions.contains(session_id);
-                        if ui.checkbox(&mut selected, "").changed() {
-                            if selected {
-                                app.state.export.selected_sessions.insert(session_id.clone());
+                egui::Grid::new("export_session_grid")
+                    .num_columns(8)
+                    .striped(true)
+                    .spacing([12.0, 4.0])
+                    .show(ui, |ui| {
+                        ui.label("");
+                        ui.strong("Session");
+                        ui.strong("User");
+                        ui.strong("Scenario");
+                        ui.strong("Samples");
+                        ui.strong("Duration");
+                        ui.strong("Status");
+                        ui.strong("Size");
+                        ui.end_row();
+
+                        for session in &app.state.export.sessions_with_metadata.clone() {
+                            let session_id = &session.session_id;
+                            let mut selected = app.state.export.selected_sessions.contains(session_id);
+                            if ui.checkbox(&mut selected, "").changed() {
+                                if selected {
+                                    app.state.export.selected_sessions.insert(session_id.clone());
+                                } else {
+                                    app.state.export.selected_sessions.remove(session_id);
+                                }
+                            }
+                            ui.label(session_id);
+                            ui.label(&session.username);
+                            ui.label(&session.scenario);
+                            ui.label(session.acc_count.to_string());
+                            ui.label(format_duration_ms(session.duration_ms));
+
+                            // Show export status with better visual indicators
+                            if session.is_exported {
+                                ui.colored_label(egui::Color32::GRAY, "✓ Exported");
                             } else {
-                                app.state.export.selected_sessions.remove(session_id);
+                                ui.colored_label(egui::Color32::GREEN, "● New");
                             }
-                        }
-                        ui.label(session_id);
-                        
-                        // Show export status with better visual indicators
-                        if *is_exported {
-                            ui.colored_label(egui::Color32::GRAY, "✓ Exported");
-                        } else {
-                            ui.colored_label(egui::Color32::GREEN, "● New");
+
+                            // Estimated CSV size
+                            match app.state.export.size_estimates.get(session_id) {
+                                Some(estimate) => {
+                                    ui.label(format!("~{}", format_bytes(estimate.estimated_csv_bytes)))
+                                        .on_hover_text(format!("{} acc rows, {} audio samples ({} stored)",
+                                                               estimate.acc_rows, estimate.audio_samples, format_bytes(estimate.audio_bytes)));
+                                }
+                                None => {
+                                    ExportHandler::request_size_estimate(app, session_id);
+                                    ui.colored_label(egui::Color32::GRAY, "…");
+                                }
+                            }
+                            ui.end_row();
                         }
                     });
-                }
             });
     }
 }
 
 fn render_export_buttons(app: &mut SensorDataApp, ui: &mut egui::Ui) {
+    let exporting = app.state.export.export_result_receiver.is_some();
     ui.horizontal(|ui| {
-        if ui.button("✅ Export Selected Sessions").clicked() {
+        // 逐session导出时保持对话框打开以显示进度
+        if ui.add_enabled(!exporting, egui::Button::new("✅ Export Selected Sessions")).clicked() {
             ExportHandler::export_selected_sessions(app);
+        }
+        
+        if ui.add_enabled(!exporting, egui::Button::new("📊 Export Comparison"))
+            .on_hover_text("Export selected sessions side by side in one CSV")
+            .clicked()
+        {
+            ExportHandler::export_comparison(app);
             app.state.export.show_export_dialog = false;
         }
         
-        if ui.button("📤 Export All New Sessions").clicked() {
+        if ui.add_enabled(!exporting, egui::Button::new("📤 Export All New Sessions")).clicked() {
             ExportHandler::export_new_sessions_only(app);
-            app.state.export.show_export_dialog = false;
         }
         
         if ui.button("❌ Cancel").clicked() {
@@ -89,3 +183,70 @@ fn render_export_buttons(app: &mut SensorDataApp, ui: &mut egui::Ui) {
         }
     });
 }
+
+/// 上一次导出中失败的session及原因，可只重试这些session
+fn render_export_failures(app: &mut SensorDataApp, ui: &mut egui::Ui) {
+    if app.state.export.failed_exports.is_empty() {
+        return;
+    }
+
+    ui.add_space(5.0);
+    let exporting = app.state.export.export_result_receiver.is_some();
+    ui.horizontal(|ui| {
+        ui.colored_label(egui::Color32::from_rgb(200, 0, 0),
+                         format!("⚠ {} sessions failed to export", app.state.export.failed_exports.len()));
+        if ui.add_enabled(!exporting, egui::Button::new("🔁 Retry failed")).clicked() {
+            ExportHandler::retry_failed_exports(app);
+        }
+    });
+
+    egui::CollapsingHeader::new("Failed sessions")
+        .id_salt("export_failures")
+        .show(ui, |ui| {
+            egui::ScrollArea::vertical()
+                .id_salt("export_failures_scroll")
+                .max_height(120.0)
+                .show(ui, |ui| {
+                    for (session_id, error) in &app.state.export.failed_exports {
+                        ui.horizontal(|ui| {
+                            ui.monospace(session_id);
+                            ui.label(error);
+                        });
+                    }
+                });
+        });
+}
+
+/// 导出进行中时显示进度条，悬停显示已写文件数和写入速度
+fn render_export_progress(app: &mut SensorDataApp, ui: &mut egui::Ui) {
+    if app.state.export.export_progress_receiver.is_none() {
+        return;
+    }
+
+    ui.add_space(5.0);
+    match &app.state.export.export_progress {
+        Some(progress) => {
+            ui.add(
+                egui::ProgressBar::new(progress.fraction())
+                    .show_percentage()
+                    .desired_width(300.0),
+            )
+            .on_hover_text(format!(
+                "{}/{} ({:.2} MB/s, {} written)",
+                progress.files_done,
+                progress.files_total,
+                progress.bytes_per_sec / (1024.0 * 1024.0),
+                format_bytes(progress.bytes_written)
+            ));
+        }
+        None => {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Starting export...");
+            });
+        }
+    }
+
+    // 进度由数据库线程推送，需要持续重绘
+    ui.ctx().request_repaint_after(std::time::Duration::from_millis(100));
+}
@@ -1,6 +1,9 @@
 use eframe::egui;
 use egui::Color32;
 use crate::app::app_core::SensorDataApp;
+use crate::config::SpectrogramColormap;
+use crate::app::state::SessionSortOrder;
+use crate::types::{LoadProgress, SessionStatistics};
 use log::warn;
 
 pub fn render_panel_controls(app: &mut SensorDataApp, ui: &mut egui::Ui) {
@@ -10,6 +13,14 @@ pub fn render_panel_controls(app: &mut SensorDataApp, ui: &mut egui::Ui) {
             refresh_history_sessions(app);
         }
 
+        let checking = app.state.history.integrity_report_receiver.is_some();
+        if ui.add_enabled(!checking, egui::Button::new("🩺"))
+            .on_hover_text("Check database: scan all sessions for missing data, broken audio blocks and bad timestamps")
+            .clicked()
+        {
+            check_database_integrity(app);
+        }
+
         if ui.button("❌").clicked() {
             app.state.history.show_history_panel = false;
         }
@@ -28,9 +39,155 @@ pub fn render_panel_controls(app: &mut SensorDataApp, ui: &mut egui::Ui) {
     if !app.state.history.loading_status.is_empty() {
         ui.colored_label(Color32::BLUE, &app.state.history.loading_status);
     }
+
+    render_load_progress(app, ui);
+
+    render_session_statistics(app, ui);
+}
+
+/// 显示当前session的各轴统计量（均值、标准差、最小/最大值）
+fn render_session_statistics(app: &mut SensorDataApp, ui: &mut egui::Ui) {
+    if app.state.history.session_statistics_receiver.is_some() {
+        ui.horizontal(|ui| {
+            ui.spinner();
+            ui.label("Computing statistics...");
+        });
+        ui.ctx().request_repaint_after(std::time::Duration::from_millis(100));
+        return;
+    }
+
+    let Some(statistics) = &app.state.history.session_statistics else {
+        return;
+    };
+    if app.state.history.selected_session.as_deref() != Some(statistics.session_id.as_str()) {
+        return;
+    }
+
+    ui.add_space(5.0);
+    if statistics.row_count() == 0 {
+        ui.label("No sensor data in this session");
+        return;
+    }
+
+    ui.label(format!("Statistics ({} rows):", statistics.row_count()));
+    egui::Grid::new("history_session_statistics")
+        .num_columns(5)
+        .striped(true)
+        .show(ui, |ui| {
+            for header in ["Axis", "Mean", "Std", "Min", "Max"] {
+                ui.strong(header);
+            }
+            ui.end_row();
+
+            for (label, axis) in SessionStatistics::AXIS_LABELS.iter().zip(&statistics.axes) {
+                ui.label(*label);
+                ui.monospace(format!("{:.4}", axis.mean));
+                ui.monospace(format!("{:.4}", axis.std_dev()));
+                ui.monospace(format!("{:.4}", axis.min));
+                ui.monospace(format!("{:.4}", axis.max));
+                ui.end_row();
+            }
+        });
+}
+
+fn render_load_progress(app: &mut SensorDataApp, ui: &mut egui::Ui) {
+    let Some(progress) = app.state.history.load_progress.clone() else {
+        return;
+    };
+
+    ui.horizontal(|ui| {
+        ui.spinner();
+
+        let loaded = progress.rows_loaded();
+        let total = progress.total_rows();
+        if total > 0 {
+            ui.add(
+                egui::ProgressBar::new(loaded as f32 / total as f32)
+                    .desired_width(140.0)
+                    .text(format!("{} / {} rows", loaded, total)),
+            );
+        } else {
+            ui.label("Counting rows...");
+        }
+
+        if ui.button("✖ Cancel load").clicked() {
+            cancel_history_load(app);
+        }
+    });
+
+    // 进度由数据库线程更新，需要持续重绘
+    ui.ctx().request_repaint_after(std::time::Duration::from_millis(100));
+}
+
+/// 取消进行中的历史数据加载：通知数据库线程停止读取并丢弃待接收的结果
+pub fn cancel_history_load(app: &mut SensorDataApp) {
+    if let Some(progress) = app.state.history.load_progress.take() {
+        progress.cancel();
+    }
+    app.state.history.history_result_receiver = None;
+    app.state.history.aligned_history_result_receiver = None;
+    app.state.history.loading_status = "History load cancelled".to_string();
+}
+
+/// 为新的加载请求创建进度句柄，同时取消之前未完成的加载（被取消的任务不会再返回结果）
+fn start_load_progress(app: &mut SensorDataApp) -> std::sync::Arc<LoadProgress> {
+    if let Some(previous) = app.state.history.load_progress.take() {
+        previous.cancel();
+        app.state.history.history_result_receiver = None;
+        app.state.history.aligned_history_result_receiver = None;
+    }
+    let progress = LoadProgress::new();
+    app.state.history.load_progress = Some(progress.clone());
+    progress
 }
 
 pub fn render_session_selector(app: &mut SensorDataApp, ui: &mut egui::Ui) {
+    ui.horizontal(|ui| {
+        ui.label("Go to:");
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut app.state.history.session_lookup_input)
+                .hint_text("session id")
+                .desired_width(180.0),
+        );
+        let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+        let session_id = app.state.history.session_lookup_input.trim().to_string();
+        let can_open = !session_id.is_empty() && app.state.history.session_lookup_receiver.is_none();
+        if (ui.add_enabled(can_open, egui::Button::new("Open")).clicked() || submitted) && can_open {
+            open_session_by_id(app, &session_id);
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("Node:");
+
+        let selected_node_text = app.state.history.selected_node_id
+            .clone()
+            .unwrap_or_else(|| "All nodes".to_string());
+
+        let previous_node = app.state.history.selected_node_id.clone();
+        egui::ComboBox::from_id_salt("node_selector")
+            .selected_text(selected_node_text)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut app.state.history.selected_node_id, None, "All nodes");
+                for node_id in &app.state.history.available_node_ids {
+                    ui.selectable_value(
+                        &mut app.state.history.selected_node_id,
+                        Some(node_id.clone()),
+                        node_id
+                    );
+                }
+            });
+
+        if app.state.history.selected_node_id != previous_node {
+            app.state.history.selected_session = None;
+            app.state.history.history_sessions.clear();
+            if let Some(username) = app.state.history.selected_username.clone() {
+                load_sessions_for_username(app, &username);
+            }
+        }
+    });
+
     ui.horizontal(|ui| {
         ui.label("User:");
 
@@ -86,11 +243,36 @@ pub fn render_session_selector(app: &mut SensorDataApp, ui: &mut egui::Ui) {
                         }
                     }
                 }
+
+                ui.separator();
+                let can_rename = app.state.history.selected_username.is_some()
+                    && app.state.history.selected_scenario.is_some();
+                if ui.add_enabled(can_rename, egui::Button::new("✏ Rename scenario...")).clicked() {
+                    app.state.history.rename_scenario_new_name = app.state.history.selected_scenario.clone().unwrap_or_default();
+                    app.state.history.show_rename_scenario_dialog = true;
+                }
             });
     });
 
     ui.add_space(5.0);
 
+    ui.horizontal(|ui| {
+        ui.label("Sort:");
+        let previous_order = app.state.history.session_sort_order;
+        egui::ComboBox::from_id_salt("session_sort_selector")
+            .selected_text(previous_order.label())
+            .show_ui(ui, |ui| {
+                for order in SessionSortOrder::ALL {
+                    ui.selectable_value(&mut app.state.history.session_sort_order, order, order.label());
+                }
+            });
+        if app.state.history.session_sort_order != previous_order {
+            app.state.sort_history_sessions();
+        }
+    });
+
+    ui.add_space(5.0);
+
     if app.state.history.selected_username.is_some() {
         let username = app.state.history.selected_username.clone().unwrap();
 
@@ -101,8 +283,13 @@ pub fn render_session_selector(app: &mut SensorDataApp, ui: &mut egui::Ui) {
                 ui.label(format!("Loading sessions for {}...", username));
             } else {
                 ui.horizontal(|ui| {
-                    if ui.button("◀").on_hover_text("Previous session").clicked() {
+                    // 按住Ctrl翻页时把经过的session加入多选集合，普通翻页清空多选
+                    let ctrl_held = ui.input(|i| i.modifiers.ctrl);
+
+                    if ui.button("◀").on_hover_text("Previous session (K / PageUp), Ctrl+click to add to selection").clicked() {
+                        let previous = app.state.history.selected_session.clone();
                         if let Some(session) = app.state.previous_session() {
+                            update_multi_selection(app, ctrl_held, previous, &session);
                             load_both_data_types(app, &session);
                         }
                     }
@@ -112,28 +299,236 @@ pub fn render_session_selector(app: &mut SensorDataApp, ui: &mut egui::Ui) {
                         .as_ref()
                         .map(|s| s.as_str())
                         .unwrap_or("None");
-                    ui.label(format!("{} ({})", current_session, session_info));
+                    // 已审阅的session用弱化颜色显示；标签响应点击以支持右键菜单
+                    let mut label_text = egui::RichText::new(format!("{} ({})", current_session, session_info));
+                    if app.state.history.reviewed_sessions.contains(current_session) {
+                        label_text = label_text.weak();
+                    }
+                    let label = ui.add(egui::Label::new(label_text).sense(egui::Sense::click()));
+                    // 右键菜单：复制当前session
+                    let mut duplicate_requested = false;
+                    if app.state.history.selected_session.is_some() {
+                        let duplicating = app.state.history.duplicate_result_receiver.is_some();
+                        label.context_menu(|ui| {
+                            if ui.add_enabled(!duplicating, egui::Button::new("📋 Duplicate")).clicked() {
+                                duplicate_requested = true;
+                                ui.close();
+                            }
+                        });
+                    }
+                    if let Some(summary) = app.state.history.session_summaries.get(current_session) {
+                        label.on_hover_text(format!(
+                            "{} rows · {:.1}s · {:.1} KB audio · {}",
+                            summary.acc_rows,
+                            summary.duration_ms as f64 / 1000.0,
+                            summary.audio_bytes as f64 / 1024.0,
+                            if summary.is_exported { "exported" } else { "not exported" }
+                        ));
+                    }
+                    if duplicate_requested {
+                        if let Some(session_id) = app.state.history.selected_session.clone() {
+                            duplicate_session(app, &session_id);
+                        }
+                    }
 
-                    if ui.button("▶").on_hover_text("Next session").clicked() {
+                    if ui.button("▶").on_hover_text("Next session (J / PageDown), Ctrl+click to add to selection").clicked() {
+                        let previous = app.state.history.selected_session.clone();
                         if let Some(session) = app.state.next_session() {
+                            update_multi_selection(app, ctrl_held, previous, &session);
                             load_both_data_types(app, &session);
                         }
                     }
 
+                    if app.state.history.selected_session.is_some() {
+                        let exporting = app.state.history.export_result_receiver.is_some();
+                        if ui.add_enabled(!exporting, egui::Button::new("📤 Export This"))
+                            .on_hover_text("Export this session to CSV without opening the export dialog")
+                            .clicked()
+                        {
+                            let options = app.config.get_config().export.clone();
+                            if let Err(e) = app.state.export_selected_history_session_immediately(options) {
+                                warn!("Failed to export history session: {}", e);
+                            }
+                        }
+                    }
+
+                    if let Some(session_id) = app.state.history.selected_session.clone() {
+                        let computing = app.state.history.session_statistics_receiver.is_some();
+                        if ui.add_enabled(!computing, egui::Button::new("📊 Stats"))
+                            .on_hover_text("Compute per-axis mean and standard deviation for this session")
+                            .clicked()
+                        {
+                            if let Err(e) = app.state.collect_statistics_for_session(&session_id) {
+                                warn!("Failed to compute session statistics: {}", e);
+                            }
+                        }
+                    }
+
+                    if let Some(session_id) = app.state.history.selected_session.clone() {
+                        let mut reviewed = app.state.history.reviewed_sessions.contains(&session_id);
+                        if ui.checkbox(&mut reviewed, "Reviewed")
+                            .on_hover_text("Mark this session as reviewed (saved in the database)")
+                            .changed()
+                        {
+                            set_session_reviewed(app, &session_id, reviewed);
+                        }
+                    }
+
                     if let Some(selected_session) = &app.state.history.selected_session {
                         if ui.button("🗑").on_hover_text("删除此session").clicked() {
-                            app.state.history.session_to_delete = Some(selected_session.clone());
+                            app.state.history.sessions_to_delete = vec![selected_session.clone()];
                             app.state.history.show_delete_confirmation = true;
                         }
                     }
                 });
             }
         });
+
+        render_multi_selection_actions(app, ui);
     } else {
         ui.label("Please select a user first");
     }
 }
 
+/// 请求数据库线程扫描所有session的数据问题，报告在app_events中接收
+fn check_database_integrity(app: &mut SensorDataApp) {
+    use crate::types::DatabaseTask;
+
+    let (sender, receiver) = crossbeam_channel::unbounded();
+    let task = DatabaseTask::CheckIntegrity { response_sender: sender };
+
+    if let Ok(()) = app.state.database.db_task_sender.try_send(task) {
+        app.state.history.integrity_report_receiver = Some(receiver);
+        app.state.history.loading_status = "Checking database...".to_string();
+    } else {
+        app.state.history.loading_status = "Unable to send database check request".to_string();
+    }
+}
+
+/// 在数据库中把session复制到新的session ID下，结果在app_events中处理
+fn duplicate_session(app: &mut SensorDataApp, session_id: &str) {
+    use crate::types::DatabaseTask;
+
+    let (sender, receiver) = crossbeam_channel::unbounded();
+    let task = DatabaseTask::DuplicateSession {
+        session_id: session_id.to_string(),
+        response_sender: sender,
+    };
+
+    if let Ok(()) = app.state.database.db_task_sender.try_send(task) {
+        app.state.history.duplicate_result_receiver = Some(receiver);
+        app.state.history.loading_status = format!("Duplicating session {}...", session_id);
+    } else {
+        app.state.history.loading_status = "Unable to send duplicate session request".to_string();
+    }
+}
+
+/// 立即更新本地审阅标记并写入数据库，失败时在app_events中回滚为数据库中的状态
+fn set_session_reviewed(app: &mut SensorDataApp, session_id: &str, reviewed: bool) {
+    use crate::types::DatabaseTask;
+
+    if reviewed {
+        app.state.history.reviewed_sessions.insert(session_id.to_string());
+    } else {
+        app.state.history.reviewed_sessions.remove(session_id);
+    }
+
+    let (sender, receiver) = crossbeam_channel::unbounded();
+    let task = DatabaseTask::SetReviewed {
+        session_id: session_id.to_string(),
+        reviewed,
+        response_sender: sender,
+    };
+
+    if let Ok(()) = app.state.database.db_task_sender.try_send(task) {
+        app.state.history.set_reviewed_result_receiver = Some(receiver);
+    } else {
+        warn!("Unable to send reviewed flag update for session {}", session_id);
+    }
+}
+
+/// Ctrl+翻页时把翻页前后的session都加入多选集合，普通翻页清空多选
+fn update_multi_selection(app: &mut SensorDataApp, ctrl_held: bool, previous: Option<String>, session: &str) {
+    let selection = &mut app.state.history.multi_selected_sessions;
+    if ctrl_held {
+        selection.extend(previous);
+        selection.insert(session.to_string());
+    } else {
+        selection.clear();
+    }
+}
+
+/// 多选了session时显示批量导出/删除按钮
+fn render_multi_selection_actions(app: &mut SensorDataApp, ui: &mut egui::Ui) {
+    let selected_count = app.state.history.multi_selected_sessions.len();
+    if selected_count == 0 {
+        return;
+    }
+
+    ui.horizontal(|ui| {
+        let mut selected: Vec<&String> = app.state.history.multi_selected_sessions.iter().collect();
+        selected.sort();
+        let selected_list = selected.iter().map(|s| s.as_str()).collect::<Vec<_>>().join("\n");
+        ui.label(format!("{} sessions selected", selected_count))
+            .on_hover_text(selected_list);
+
+        let exporting = app.state.history.export_result_receiver.is_some();
+        if ui.add_enabled(!exporting, egui::Button::new("📤 Export Selected"))
+            .on_hover_text("Export all selected sessions to CSV")
+            .clicked()
+        {
+            let options = app.config.get_config().export.clone();
+            if let Err(e) = app.state.export_multi_selected_history_sessions_immediately(options) {
+                warn!("Failed to export selected history sessions: {}", e);
+            }
+        }
+
+        let deleting = app.state.history.delete_result_receiver.is_some();
+        if ui.add_enabled(!deleting, egui::Button::new("🗑 Delete Selected")).clicked() {
+            let mut session_ids: Vec<String> = app.state.history.multi_selected_sessions.iter().cloned().collect();
+            session_ids.sort();
+            app.state.history.sessions_to_delete = session_ids;
+            app.state.history.show_delete_confirmation = true;
+        }
+
+        if ui.button("✖ Clear").on_hover_text("Clear the multi-selection").clicked() {
+            app.state.history.multi_selected_sessions.clear();
+        }
+    });
+}
+
+/// session时长过滤：输入以秒为单位，翻页时跳过范围外的session
+fn render_duration_filter(app: &mut SensorDataApp, ui: &mut egui::Ui) {
+    let history = &mut app.state.history;
+    ui.horizontal(|ui| {
+        ui.label("Duration:");
+
+        let mut min_seconds = history.filter_min_duration_ms as f64 / 1000.0;
+        if ui.add(egui::DragValue::new(&mut min_seconds).range(0.0..=86_400.0).speed(0.1).prefix("min ").suffix(" s"))
+            .on_hover_text("Hide sessions shorter than this, e.g. accidental one-second saves")
+            .changed()
+        {
+            history.filter_min_duration_ms = (min_seconds * 1000.0).round() as u64;
+        }
+
+        let mut has_max = history.filter_max_duration_ms.is_some();
+        if ui.checkbox(&mut has_max, "max").changed() {
+            history.filter_max_duration_ms = has_max.then_some(history.filter_min_duration_ms.max(60_000));
+        }
+        if let Some(max_ms) = history.filter_max_duration_ms.as_mut() {
+            let mut max_seconds = *max_ms as f64 / 1000.0;
+            if ui.add(egui::DragValue::new(&mut max_seconds).range(0.0..=86_400.0).speed(0.1).suffix(" s")).changed() {
+                *max_ms = (max_seconds * 1000.0).round() as u64;
+            }
+        }
+
+        let hidden = history.history_sessions.len() - history.visible_session_count();
+        if hidden > 0 {
+            ui.colored_label(Color32::GRAY, format!("{} hidden", hidden));
+        }
+    });
+}
+
 pub fn render_display_options(app: &mut SensorDataApp, ui: &mut egui::Ui) {
     ui.label("Display Options:");
     ui.horizontal(|ui| {
@@ -141,6 +536,8 @@ pub fn render_display_options(app: &mut SensorDataApp, ui: &mut egui::Ui) {
         ui.checkbox(&mut app.state.history.display_options.show_x_axis, "X-Axis");
         ui.checkbox(&mut app.state.history.display_options.show_y_axis, "Y-Axis");
         ui.checkbox(&mut app.state.history.display_options.show_z_axis, "Z-Axis");
+        ui.checkbox(&mut app.state.history.display_options.show_acc_magnitude, "|ACC|")
+            .on_hover_text("Acceleration magnitude sqrt(x² + y² + z²), independent of sensor orientation");
     });
 
     ui.horizontal(|ui| {
@@ -148,10 +545,43 @@ pub fn render_display_options(app: &mut SensorDataApp, ui: &mut egui::Ui) {
         ui.checkbox(&mut app.state.history.display_options.show_gx_axis, "GX-Axis");
         ui.checkbox(&mut app.state.history.display_options.show_gy_axis, "GY-Axis");
         ui.checkbox(&mut app.state.history.display_options.show_gz_axis, "GZ-Axis");
+        ui.checkbox(&mut app.state.history.display_options.show_gyro_magnitude, "|GYRO|")
+            .on_hover_text("Angular rate magnitude sqrt(gx² + gy² + gz²)");
+        ui.checkbox(&mut app.state.history.display_options.show_orientation, "Orientation")
+            .on_hover_text("Roll/pitch/yaw decomposed from the device quaternion, if recorded");
     });
 
+    ui.horizontal(|ui| {
+        let options = &mut app.state.history.display_options;
+        ui.checkbox(&mut options.show_smoothed, "Smooth")
+            .on_hover_text("Overlay a moving average on the IMU plots");
+        if options.show_smoothed {
+            ui.add(egui::DragValue::new(&mut options.smoothing_window).range(2..=1000).prefix("window "));
+        }
+        ui.checkbox(&mut options.absolute_time, "Absolute time")
+            .on_hover_text("Label the time axis with wall-clock HH:MM:SS instead of seconds since the session start");
+    });
+
+    render_duration_filter(app, ui);
+
     ui.horizontal(|ui| {
         ui.checkbox(&mut app.state.history.display_options.show_audio, "Audio");
+        if app.state.history.display_options.show_audio {
+            ui.checkbox(&mut app.state.history.display_options.audio_envelope, "Envelope")
+                .on_hover_text("Draw the min/max of each pixel column instead of every sample; much faster for long sessions");
+        }
+        ui.checkbox(&mut app.state.history.display_options.show_spectrogram, "Spectrogram");
+
+        if app.state.history.display_options.show_spectrogram {
+            let colormap = &mut app.config.get_config_mut().plot.spectrogram_colormap;
+            egui::ComboBox::from_id_salt("spectrogram_colormap_selector")
+                .selected_text(format!("{:?}", colormap))
+                .show_ui(ui, |ui| {
+                    for option in [SpectrogramColormap::Viridis, SpectrogramColormap::Inferno, SpectrogramColormap::Grayscale] {
+                        ui.selectable_value(colormap, option, format!("{:?}", option));
+                    }
+                });
+        }
     });
 
     ui.add_space(5.0);
@@ -183,8 +613,19 @@ pub fn render_delete_confirmation_dialog(app: &mut SensorDataApp, ctx: &egui::Co
         .resizable(false)
         .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
         .show(ctx, |ui| {
-            if let Some(session_id) = app.state.history.session_to_delete.clone() {
-                ui.label(format!("确定要删除session '{}'吗？", session_id));
+            let session_ids = app.state.history.sessions_to_delete.clone();
+            if !session_ids.is_empty() {
+                match session_ids.as_slice() {
+                    [session_id] => {
+                        ui.label(format!("确定要删除session '{}'吗？", session_id));
+                    }
+                    _ => {
+                        ui.label(format!("确定要删除选中的 {} 个session吗？", session_ids.len()));
+                        for session_id in &session_ids {
+                            ui.monospace(session_id);
+                        }
+                    }
+                }
                 ui.add_space(10.0);
                 ui.colored_label(egui::Color32::from_rgb(200, 100, 100), "⚠ 此操作不可撤销！");
                 ui.add_space(10.0);
@@ -192,13 +633,13 @@ pub fn render_delete_confirmation_dialog(app: &mut SensorDataApp, ctx: &egui::Co
                 ui.horizontal(|ui| {
                     if ui.button("❌ 取消").clicked() {
                         app.state.history.show_delete_confirmation = false;
-                        app.state.history.session_to_delete = None;
+                        app.state.history.sessions_to_delete.clear();
                     }
 
                     ui.add_space(20.0);
 
                     if ui.button("🗑 确认删除").clicked() {
-                        delete_selected_session(app, &session_id);
+                        delete_sessions(app, session_ids.clone());
                         app.state.history.show_delete_confirmation = false;
                     }
                 });
@@ -206,6 +647,105 @@ pub fn render_delete_confirmation_dialog(app: &mut SensorDataApp, ctx: &egui::Co
         });
 }
 
+/// 显示完整性检查报告，可把有问题的session交给删除确认对话框
+pub fn render_integrity_report_dialog(app: &mut SensorDataApp, ctx: &egui::Context) {
+    let Some(report) = &app.state.history.integrity_report else {
+        return;
+    };
+
+    let affected_sessions = report.affected_sessions();
+    let mut open = true;
+    let mut delete_requested = false;
+    egui::Window::new("🩺 Database Check")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(true)
+        .default_width(480.0)
+        .show(ctx, |ui| {
+            ui.label(format!("Checked {} sessions", report.sessions_checked));
+            if report.issues.is_empty() {
+                ui.colored_label(egui::Color32::from_rgb(0, 150, 0), "✅ No problems found");
+                return;
+            }
+
+            ui.colored_label(
+                egui::Color32::from_rgb(200, 100, 0),
+                format!("⚠ {} issues in {} sessions", report.issues.len(), affected_sessions.len()),
+            );
+            ui.add_space(5.0);
+
+            egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                egui::Grid::new("integrity_issues_grid")
+                    .num_columns(3)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for issue in &report.issues {
+                            ui.monospace(&issue.session_id);
+                            ui.label(issue.kind.label());
+                            ui.label(&issue.detail);
+                            ui.end_row();
+                        }
+                    });
+            });
+
+            ui.add_space(10.0);
+            if ui.button(format!("🗑 Delete {} affected sessions...", affected_sessions.len())).clicked() {
+                delete_requested = true;
+            }
+        });
+
+    if delete_requested {
+        app.state.history.sessions_to_delete = affected_sessions;
+        app.state.history.show_delete_confirmation = true;
+        app.state.history.integrity_report = None;
+    } else if !open {
+        app.state.history.integrity_report = None;
+    }
+}
+
+pub fn render_rename_scenario_dialog(app: &mut SensorDataApp, ctx: &egui::Context) {
+    if !app.state.history.show_rename_scenario_dialog {
+        return;
+    }
+
+    let (Some(username), Some(old_name)) = (
+        app.state.history.selected_username.clone(),
+        app.state.history.selected_scenario.clone(),
+    ) else {
+        app.state.history.show_rename_scenario_dialog = false;
+        return;
+    };
+
+    egui::Window::new("重命名场景")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.label(format!("将用户 '{}' 的场景 '{}' 重命名为：", username, old_name));
+            ui.add_space(5.0);
+            ui.text_edit_singleline(&mut app.state.history.rename_scenario_new_name);
+            ui.add_space(5.0);
+            ui.colored_label(egui::Color32::from_rgb(200, 100, 100), "⚠ 该场景下的所有历史数据都会被更新");
+            ui.add_space(10.0);
+
+            let new_name = app.state.history.rename_scenario_new_name.trim().to_string();
+            let is_valid = !new_name.is_empty() && new_name != old_name;
+
+            ui.horizontal(|ui| {
+                if ui.button("❌ 取消").clicked() {
+                    app.state.history.show_rename_scenario_dialog = false;
+                }
+
+                ui.add_space(20.0);
+
+                if ui.add_enabled(is_valid, egui::Button::new("✏ 确认重命名")).clicked() {
+                    rename_scenario(app, &username, &old_name, &new_name);
+                    app.state.history.show_rename_scenario_dialog = false;
+                }
+            });
+        });
+}
+
 pub fn render_audio_playback_controls(app: &mut SensorDataApp, ui: &mut egui::Ui) {
     ui.horizontal(|ui| {
         ui.label("🎵 Audio Playback:");
@@ -224,6 +764,22 @@ pub fn render_audio_playback_controls(app: &mut SensorDataApp, ui: &mut egui::Ui
             app.stop_history_audio();
         }
 
+        ui.checkbox(&mut app.state.history.audio_playback.normalize, "Normalize")
+            .on_hover_text("Scale the track so its peak reaches full scale (applies from the next play)");
+
+        let stored_sample_rate = app.state.history.audio_sample_rate;
+        let playback = &mut app.state.history.audio_playback;
+        if ui.checkbox(&mut playback.override_sample_rate, "Override rate")
+            .on_hover_text(format!("Play at a manual sample rate instead of the stored {} Hz (applies from the next play)", stored_sample_rate))
+            .changed()
+            && playback.override_sample_rate
+        {
+            playback.sample_rate_override_hz = stored_sample_rate;
+        }
+        if playback.override_sample_rate {
+            ui.add(egui::DragValue::new(&mut playback.sample_rate_override_hz).range(1000..=192_000).speed(100).suffix(" Hz"));
+        }
+
         if app.state.history.audio_playback.is_available {
             ui.separator();
             if app.state.history.audio_playback.is_playing {
@@ -265,6 +821,24 @@ pub fn refresh_history_sessions(app: &mut SensorDataApp) {
     } else {
         warn!("Unable to send scenarios query request");
     }
+
+    let (node_ids_sender, node_ids_receiver) = crossbeam_channel::unbounded();
+    let node_ids_task = DatabaseTask::GetNodeIds { response_sender: node_ids_sender };
+
+    if let Ok(()) = app.state.database.db_task_sender.try_send(node_ids_task) {
+        app.state.history.node_ids_result_receiver = Some(node_ids_receiver);
+    } else {
+        warn!("Unable to send node ids query request");
+    }
+
+    let (reviewed_sender, reviewed_receiver) = crossbeam_channel::unbounded();
+    let reviewed_task = DatabaseTask::GetReviewed { response_sender: reviewed_sender };
+
+    if let Ok(()) = app.state.database.db_task_sender.try_send(reviewed_task) {
+        app.state.history.reviewed_sessions_receiver = Some(reviewed_receiver);
+    } else {
+        warn!("Unable to send reviewed sessions query request");
+    }
 }
 
 fn load_sessions_for_username(app: &mut SensorDataApp, username: &str) {
@@ -275,6 +849,24 @@ fn load_sessions_for_username(app: &mut SensorDataApp, username: &str) {
         return;
     }
 
+    // 选择了节点时按节点列出session，不再按用户和场景过滤
+    if let Some(node_id) = app.state.history.selected_node_id.clone() {
+        app.state.history.loading_status = format!("Loading sessions for node: {}", node_id);
+
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let task = DatabaseTask::GetSessionsByNodeId {
+            node_id,
+            response_sender: sender
+        };
+
+        if let Ok(()) = app.state.database.db_task_sender.try_send(task) {
+            app.state.history.sessions_result_receiver = Some(receiver);
+        } else {
+            app.state.history.loading_status = "Unable to send sessions query request".to_string();
+        }
+        return;
+    }
+
     let scenario = app.state.history.selected_scenario
         .as_ref()
         .map(|s| s.as_str())
@@ -296,6 +888,64 @@ fn load_sessions_for_username(app: &mut SensorDataApp, username: &str) {
     }
 }
 
+/// 请求当前session列表的统计信息（行数、时长、导出状态），返回后按所选方式排序
+pub fn request_session_summaries(app: &mut SensorDataApp) {
+    use crate::types::DatabaseTask;
+
+    if app.state.history.history_sessions.is_empty() {
+        return;
+    }
+
+    let (sender, receiver) = crossbeam_channel::unbounded();
+    let task = DatabaseTask::GetSessionSummaries {
+        session_ids: app.state.history.history_sessions.clone(),
+        response_sender: sender
+    };
+
+    if let Ok(()) = app.state.database.db_task_sender.try_send(task) {
+        app.state.history.session_summaries_result_receiver = Some(receiver);
+    } else {
+        warn!("Unable to send session summaries query request");
+    }
+}
+
+/// 按session ID直接打开：先查询其所属用户和场景，结果返回后填充选择器并加载数据
+pub fn open_session_by_id(app: &mut SensorDataApp, session_id: &str) {
+    use crate::types::DatabaseTask;
+
+    app.state.history.loading_status = format!("Looking up session: {}", session_id);
+
+    let (sender, receiver) = crossbeam_channel::unbounded();
+    let task = DatabaseTask::ResolveSession {
+        session_id: session_id.to_string(),
+        response_sender: sender
+    };
+
+    if let Ok(()) = app.state.database.db_task_sender.try_send(task) {
+        app.state.history.session_lookup_receiver = Some((session_id.to_string(), receiver));
+    } else {
+        app.state.history.loading_status = "Unable to send session lookup request".to_string();
+    }
+}
+
+/// session查询返回后，切换用户/场景选择器到该session并加载数据
+pub fn select_resolved_session(app: &mut SensorDataApp, session_id: &str, username: String, scenario: String) {
+    let history = &mut app.state.history;
+    if !history.available_usernames.contains(&username) {
+        history.available_usernames.push(username.clone());
+    }
+    history.selected_username = Some(username.clone());
+    history.selected_scenario = Some(scenario);
+    history.selected_node_id = None;
+    history.selected_session = Some(session_id.to_string());
+    history.history_sessions.clear();
+
+    // 填充该用户的session和场景列表；已选中session，因此不会再自动选择第一个
+    load_sessions_for_username(app, &username);
+    load_scenarios_for_username(app, &username);
+    load_both_data_types(app, session_id);
+}
+
 fn load_scenarios_for_username(app: &mut SensorDataApp, username: &str) {
     use crate::types::DatabaseTask;
 
@@ -323,19 +973,37 @@ fn load_both_data_types(app: &mut SensorDataApp, session_id: &str) {
     use crate::types::DatabaseTask;
 
     app.state.history.loading_status = format!("Loading both original and aligned data: {}", session_id);
+    app.state.history.history_load_completed = false;
+    app.state.history.export_range_s = None;
+
+    let progress = start_load_progress(app);
 
     let (original_sender, original_receiver) = crossbeam_channel::unbounded();
     let original_task = DatabaseTask::LoadHistoryData {
         session_id: session_id.to_string(),
+        progress: progress.clone(),
         response_sender: original_sender,
     };
 
     let (aligned_sender, aligned_receiver) = crossbeam_channel::unbounded();
     let aligned_task = DatabaseTask::LoadAlignedHistoryData {
         session_id: session_id.to_string(),
+        progress,
         response_sender: aligned_sender,
     };
 
+    // 音频采样率随session变化，与数据一起查询
+    let (rate_sender, rate_receiver) = crossbeam_channel::unbounded();
+    let rate_task = DatabaseTask::GetAudioSampleRate {
+        session_id: session_id.to_string(),
+        response_sender: rate_sender,
+    };
+    if app.state.database.db_task_sender.try_send(rate_task).is_ok() {
+        app.state.history.audio_sample_rate_receiver = Some(rate_receiver);
+    } else {
+        warn!("Unable to send audio sample rate query request");
+    }
+
     let original_sent = app.state.database.db_task_sender.try_send(original_task).is_ok();
     let aligned_sent = app.state.database.db_task_sender.try_send(aligned_task).is_ok();
 
@@ -344,6 +1012,7 @@ fn load_both_data_types(app: &mut SensorDataApp, session_id: &str) {
         app.state.history.aligned_history_result_receiver = Some(aligned_receiver);
     } else {
         app.state.history.loading_status = "Unable to send data loading requests".to_string();
+        app.state.history.load_progress = None;
     }
 }
 
@@ -351,6 +1020,8 @@ fn switch_to_aligned_data(app: &mut SensorDataApp) {
     if !app.state.history.aligned_history_data.is_empty() || !app.state.history.aligned_audio_data.is_empty() {
         app.state.history.loaded_history_data = app.state.history.aligned_history_data.clone();
         app.state.history.loaded_audio_data = app.state.history.aligned_audio_data.clone();
+        app.state.history.audio_waveform_cache.invalidate();
+        app.state.history.history_load_completed = true;
         app.state.history.loading_status = format!(
             "Showing aligned data: {} acc points, {} audio samples",
             app.state.history.loaded_history_data.len(),
@@ -368,6 +1039,8 @@ fn switch_to_original_data(app: &mut SensorDataApp) {
     if !app.state.history.original_history_data.is_empty() || !app.state.history.original_audio_data.is_empty() {
         app.state.history.loaded_history_data = app.state.history.original_history_data.clone();
         app.state.history.loaded_audio_data = app.state.history.original_audio_data.clone();
+        app.state.history.audio_waveform_cache.invalidate();
+        app.state.history.history_load_completed = true;
         app.state.history.loading_status = format!(
             "Showing original data: {} acc points, {} audio samples",
             app.state.history.loaded_history_data.len(),
@@ -387,10 +1060,12 @@ fn load_session_data(app: &mut SensorDataApp, session_id: &str) {
     }
 
     app.state.history.loading_status = format!("Loading aligned session data: {}", session_id);
+    app.state.history.history_load_completed = false;
 
     let (sender, receiver) = crossbeam_channel::unbounded();
     let task = DatabaseTask::LoadAlignedHistoryData {
         session_id: session_id.to_string(),
+        progress: start_load_progress(app),
         response_sender: sender,
     };
 
@@ -398,6 +1073,7 @@ fn load_session_data(app: &mut SensorDataApp, session_id: &str) {
         app.state.history.aligned_history_result_receiver = Some(receiver);
     } else {
         app.state.history.loading_status = "Unable to send aligned data loading request".to_string();
+        app.state.history.load_progress = None;
     }
 }
 
@@ -410,10 +1086,12 @@ fn load_original_data(app: &mut SensorDataApp, session_id: &str) {
     }
 
     app.state.history.loading_status = format!("Loading original session data: {}", session_id);
+    app.state.history.history_load_completed = false;
 
     let (sender, receiver) = crossbeam_channel::unbounded();
     let task = DatabaseTask::LoadHistoryData {
         session_id: session_id.to_string(),
+        progress: start_load_progress(app),
         response_sender: sender,
     };
 
@@ -421,23 +1099,36 @@ fn load_original_data(app: &mut SensorDataApp, session_id: &str) {
         app.state.history.history_result_receiver = Some(receiver);
     } else {
         app.state.history.loading_status = "Unable to send original data loading request".to_string();
+        app.state.history.load_progress = None;
     }
 }
 
-fn delete_selected_session(app: &mut SensorDataApp, session_id: &str) {
+fn delete_sessions(app: &mut SensorDataApp, mut session_ids: Vec<String>) {
     use crate::types::DatabaseTask;
 
+    let description = match session_ids.as_slice() {
+        [session_id] => format!("session: {}", session_id),
+        _ => format!("{} 个session", session_ids.len()),
+    };
+
     if app.state.history.delete_result_receiver.is_some() {
-        app.state.history.loading_status = format!("已经在删除session: {}", session_id);
+        app.state.history.loading_status = format!("已经在删除{}", description);
         return;
     }
 
-    app.state.history.loading_status = format!("正在删除session: {}", session_id);
+    app.state.history.loading_status = format!("正在删除{}", description);
 
     let (sender, receiver) = crossbeam_channel::unbounded();
-    let task = DatabaseTask::DeleteSession {
-        session_id: session_id.to_string(),
-        response_sender: sender,
+    let task = if session_ids.len() == 1 {
+        DatabaseTask::DeleteSession {
+            session_id: session_ids.remove(0),
+            response_sender: sender,
+        }
+    } else {
+        DatabaseTask::BatchDelete {
+            session_ids,
+            response_sender: sender,
+        }
     };
 
     if let Ok(()) = app.state.database.db_task_sender.try_send(task) {
@@ -447,6 +1138,31 @@ fn delete_selected_session(app: &mut SensorDataApp, session_id: &str) {
     }
 }
 
+fn rename_scenario(app: &mut SensorDataApp, username: &str, old_name: &str, new_name: &str) {
+    use crate::types::DatabaseTask;
+
+    if app.state.history.rename_scenario_result_receiver.is_some() {
+        app.state.history.loading_status = format!("已经在重命名场景: {}", old_name);
+        return;
+    }
+
+    app.state.history.loading_status = format!("正在重命名场景: {} -> {}", old_name, new_name);
+
+    let (sender, receiver) = crossbeam_channel::unbounded();
+    let task = DatabaseTask::RenameScenario {
+        old_name: old_name.to_string(),
+        new_name: new_name.to_string(),
+        username: username.to_string(),
+        response_sender: sender,
+    };
+
+    if let Ok(()) = app.state.database.db_task_sender.try_send(task) {
+        app.state.history.rename_scenario_result_receiver = Some(receiver);
+    } else {
+        app.state.history.loading_status = "无法发送重命名请求".to_string();
+    }
+}
+
 // Public wrapper functions for external use
 pub fn load_both_data_types_from_main(app: &mut SensorDataApp, session_id: &str) {
     load_both_data_types(app, session_id);
@@ -454,4 +1170,8 @@ pub fn load_both_data_types_from_main(app: &mut SensorDataApp, session_id: &str)
 
 pub fn load_sessions_for_username_from_main(app: &mut SensorDataApp, username: &str) {
     load_sessions_for_username(app, username);
-}
\ No newline at end of file
+}
+
+pub fn load_scenarios_for_username_from_main(app: &mut SensorDataApp, username: &str) {
+    load_scenarios_for_username(app, username);
+}
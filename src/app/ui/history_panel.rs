@@ -1,8 +1,11 @@
 use eframe::egui;
-use egui_plot::{Line, Plot, PlotPoints};
-use egui::Color32;
+use egui_plot::{Line, Plot, PlotPoints, VLine};
+use egui::{Color32, Stroke};
 use crate::app::app_core::SensorDataApp;
-use crate::types::DataPoint;
+use crate::types::{DataPoint, quaternion_to_euler};
+use crate::plotter::{moving_average, tick_spacer, AudioWaveformPoints};
+use crate::config::{YPaddingConfig, YRangeMode};
+use crate::utils::format_timestamp;
 use super::history_controls::*;
 
 /// 格式化数字为固定宽度的 y 轴标签
@@ -48,13 +51,15 @@ pub fn render_history_panel(app: &mut SensorDataApp, ctx: &egui::Context) {
                 render_history_visualization(app, ui);
             } else {
                 ui.centered_and_justified(|ui| {
-                    ui.colored_label(Color32::GRAY, "Please select a session to view history data");
+                    ui.colored_label(Color32::GRAY, "No session selected");
                 });
             }
         });
 
     // 渲染删除确认对话框
     render_delete_confirmation_dialog(app, ctx);
+    render_rename_scenario_dialog(app, ctx);
+    render_integrity_report_dialog(app, ctx);
 }
 
 
@@ -63,7 +68,13 @@ pub fn render_history_panel(app: &mut SensorDataApp, ctx: &egui::Context) {
 fn render_history_visualization(app: &mut SensorDataApp, ui: &mut egui::Ui) {
     if app.state.history.loaded_history_data.is_empty() {
         ui.centered_and_justified(|ui| {
-            ui.colored_label(Color32::GRAY, "Loading history data...");
+            if app.state.history.history_load_completed {
+                ui.colored_label(Color32::from_rgb(200, 120, 0), "Session has no data");
+            } else if app.state.history.load_progress.is_some() {
+                ui.colored_label(Color32::GRAY, "⏳ Loading history data...");
+            } else {
+                ui.colored_label(Color32::GRAY, "History data not loaded");
+            }
         });
         return;
     }
@@ -100,6 +111,9 @@ fn render_history_visualization(app: &mut SensorDataApp, ui: &mut egui::Ui) {
         });
     }
 
+    ui.separator();
+    render_export_range_controls(app, ui);
+
     // 音频播放控制区域（在滚动区域外面）
     if app.state.history.display_options.show_audio && !app.state.history.loaded_audio_data.is_empty() {
         ui.separator();
@@ -108,43 +122,178 @@ fn render_history_visualization(app: &mut SensorDataApp, ui: &mut egui::Ui) {
 
     ui.add_space(5.0);
 
+    let plot_config = &app.config.get_config().plot;
+    let ticks = (plot_config.tick_count_x, plot_config.tick_count_y);
+    let y_ranges = plot_config.y_range.clone();
+    let line_widths = plot_config.line_width.clone();
+    let colormap = plot_config.spectrogram_colormap;
+    let ctx = ui.ctx().clone();
+
+    let display_options = &app.state.history.display_options;
+    let smoothing_window = display_options.show_smoothed.then_some(display_options.smoothing_window);
+    let time_cursor_ms = app.state.history.time_cursor_ms;
+    let y_padding = plot_config.y_padding;
+    let absolute_time = display_options.absolute_time;
+    let export_range_s = app.state.history.export_range_s;
+    let acc_style = HistoryPlotStyle { ticks, y_range_mode: y_ranges.accelerometer, smoothing_window, time_cursor_ms, y_padding, absolute_time, export_range_s };
+    let gyro_style = HistoryPlotStyle { ticks, y_range_mode: y_ranges.gyroscope, smoothing_window, time_cursor_ms, y_padding, absolute_time, export_range_s };
+    let audio_style = HistoryPlotStyle { ticks, y_range_mode: y_ranges.audio, smoothing_window: None, time_cursor_ms, y_padding, absolute_time, export_range_s };
+    let orientation_style = HistoryPlotStyle { ticks, y_range_mode: YRangeMode::Auto, smoothing_window, time_cursor_ms, y_padding, absolute_time, export_range_s };
+    // 音频只有样本没有时间戳，与其他波形一样以session起点为横轴零点
+    let session_start_ms = app.state.history.loaded_history_data.first().map_or(0, |dp| dp.timestamp);
+
+    // 播放时加快刷新，使各波形上的光标平滑移动
+    if app.state.history.audio_playback.is_playing {
+        ui.ctx().request_repaint_after(std::time::Duration::from_millis(50));
+    }
+
+    // 在任一波形上拖动即选择片段导出的时间范围
+    let mut range_selection = None;
     egui::ScrollArea::vertical()
         .max_height(ui.available_height() - 100.0)
         .show(ui, |ui| {
             // Render accelerometer data
             if app.state.history.display_options.show_x_axis {
-                render_history_axis(ui, "ACC X-Axis History", &app.state.history.loaded_history_data, |dp| dp.x, Color32::RED);
+                render_history_axis(ui, "ACC X-Axis History", &app.state.history.loaded_history_data, |dp| dp.x, Stroke::new(line_widths.accelerometer, Color32::RED), acc_style, &mut range_selection);
             }
 
             if app.state.history.display_options.show_y_axis {
-                render_history_axis(ui, "ACC Y-Axis History", &app.state.history.loaded_history_data, |dp| dp.y, Color32::GREEN);
+                render_history_axis(ui, "ACC Y-Axis History", &app.state.history.loaded_history_data, |dp| dp.y, Stroke::new(line_widths.accelerometer, Color32::GREEN), acc_style, &mut range_selection);
             }
 
             if app.state.history.display_options.show_z_axis {
-                render_history_axis(ui, "ACC Z-Axis History", &app.state.history.loaded_history_data, |dp| dp.z, Color32::BLUE);
+                render_history_axis(ui, "ACC Z-Axis History", &app.state.history.loaded_history_data, |dp| dp.z, Stroke::new(line_widths.accelerometer, Color32::BLUE), acc_style, &mut range_selection);
+            }
+
+            if app.state.history.display_options.show_acc_magnitude {
+                render_history_axis(ui, "ACC Magnitude History", &app.state.history.loaded_history_data, |dp| (dp.x * dp.x + dp.y * dp.y + dp.z * dp.z).sqrt(), Stroke::new(line_widths.accelerometer, Color32::from_rgb(180, 180, 180)), acc_style, &mut range_selection);
             }
 
             // Render gyroscope data
             if app.state.history.display_options.show_gx_axis {
-                render_history_axis(ui, "GYRO X-Axis History", &app.state.history.loaded_history_data, |dp| dp.gx, Color32::from_rgb(255, 165, 0));
+                render_history_axis(ui, "GYRO X-Axis History", &app.state.history.loaded_history_data, |dp| dp.gx, Stroke::new(line_widths.gyroscope, Color32::from_rgb(255, 165, 0)), gyro_style, &mut range_selection);
             }
 
             if app.state.history.display_options.show_gy_axis {
-                render_history_axis(ui, "GYRO Y-Axis History", &app.state.history.loaded_history_data, |dp| dp.gy, Color32::from_rgb(255, 20, 147));
+                render_history_axis(ui, "GYRO Y-Axis History", &app.state.history.loaded_history_data, |dp| dp.gy, Stroke::new(line_widths.gyroscope, Color32::from_rgb(255, 20, 147)), gyro_style, &mut range_selection);
             }
 
             if app.state.history.display_options.show_gz_axis {
-                render_history_axis(ui, "GYRO Z-Axis History", &app.state.history.loaded_history_data, |dp| dp.gz, Color32::from_rgb(0, 255, 255));
+                render_history_axis(ui, "GYRO Z-Axis History", &app.state.history.loaded_history_data, |dp| dp.gz, Stroke::new(line_widths.gyroscope, Color32::from_rgb(0, 255, 255)), gyro_style, &mut range_selection);
+            }
+
+            if app.state.history.display_options.show_gyro_magnitude {
+                render_history_axis(ui, "GYRO Magnitude History", &app.state.history.loaded_history_data, |dp| (dp.gx * dp.gx + dp.gy * dp.gy + dp.gz * dp.gz).sqrt(), Stroke::new(line_widths.gyroscope, Color32::from_rgb(200, 160, 255)), gyro_style, &mut range_selection);
+            }
+
+            // Render orientation (Euler angles decomposed from quaternion)
+            if app.state.history.display_options.show_orientation {
+                // 只绘制带有姿态数据的点，旧设备或旧数据没有四元数
+                let oriented: Vec<DataPoint> = app.state.history.loaded_history_data
+                    .iter()
+                    .filter(|dp| dp.orientation.is_some())
+                    .cloned()
+                    .collect();
+                if oriented.is_empty() {
+                    if !app.state.history.loaded_history_data.is_empty() {
+                        ui.colored_label(Color32::GRAY, "No orientation data recorded for this session");
+                    }
+                } else {
+                    let euler = |dp: &DataPoint| dp.orientation.map_or((0.0, 0.0, 0.0), quaternion_to_euler);
+                    render_history_axis(ui, "Roll (°) History", &oriented, |dp| euler(dp).0, Stroke::new(line_widths.gyroscope, Color32::from_rgb(220, 120, 60)), orientation_style, &mut range_selection);
+                    render_history_axis(ui, "Pitch (°) History", &oriented, |dp| euler(dp).1, Stroke::new(line_widths.gyroscope, Color32::from_rgb(60, 180, 120)), orientation_style, &mut range_selection);
+                    render_history_axis(ui, "Yaw (°) History", &oriented, |dp| euler(dp).2, Stroke::new(line_widths.gyroscope, Color32::from_rgb(90, 120, 220)), orientation_style, &mut range_selection);
+                }
             }
 
             // Render audio data (without controls)
             if app.state.history.display_options.show_audio && !app.state.history.loaded_audio_data.is_empty() {
-                render_history_audio_waveform(ui, "Audio History", &app.state.history.loaded_audio_data, Color32::PURPLE, &app.state.history.audio_playback);
+                // 包络模式下每个像素一桶，绘制点数与音频长度无关；完整样本仍保留用于播放和导出
+                let buckets = app.state.history.display_options.audio_envelope.then(|| ui.available_width().max(1.0) as usize);
+                let history = &mut app.state.history;
+                let waveform = history.audio_waveform_cache.waveform(&history.loaded_audio_data, history.audio_sample_rate as f64, buckets);
+                render_history_audio_waveform(ui, "Audio History", waveform, session_start_ms, Stroke::new(line_widths.audio, Color32::PURPLE), audio_style, &mut range_selection);
+            }
+
+            // Render audio spectrogram
+            if app.state.history.display_options.show_spectrogram && !app.state.history.loaded_audio_data.is_empty() {
+                let history = &mut app.state.history;
+                match history.spectrogram_cache.texture(&ctx, &history.loaded_audio_data, colormap) {
+                    Some(texture) => {
+                        ui.label("Audio Spectrogram");
+                        ui.add(egui::Image::new(texture).fit_to_exact_size(egui::vec2(ui.available_width(), 120.0)));
+                    }
+                    None => {
+                        ui.colored_label(Color32::GRAY, "Audio too short for spectrogram");
+                    }
+                }
             }
         });
+
+    if let Some((start_s, end_s)) = range_selection {
+        app.state.history.export_range_s = Some((start_s.max(0.0), end_s.max(0.0)));
+    }
+}
+
+/// 片段导出：起止时间为相对数据起点的秒数，可直接输入或在波形上拖动选择
+fn render_export_range_controls(app: &mut SensorDataApp, ui: &mut egui::Ui) {
+    let history = &app.state.history;
+    let acc_duration_s = match (history.loaded_history_data.first(), history.loaded_history_data.last()) {
+        (Some(first), Some(last)) => (last.timestamp - first.timestamp) as f64 / 1000.0,
+        _ => 0.0,
+    };
+    let audio_duration_s = history.loaded_audio_data.len() as f64 / history.audio_sample_rate.max(1) as f64;
+    let duration_s = acc_duration_s.max(audio_duration_s);
+    let (mut start_s, mut end_s) = history.export_range_s.unwrap_or((0.0, duration_s));
+    let exporting = history.export_result_receiver.is_some();
+
+    ui.horizontal(|ui| {
+        ui.label("Range:");
+        let start_changed = ui.add(egui::DragValue::new(&mut start_s).range(0.0..=duration_s).speed(0.01).max_decimals(3).suffix(" s")).changed();
+        ui.label("-");
+        let end_changed = ui.add(egui::DragValue::new(&mut end_s).range(0.0..=duration_s).speed(0.01).max_decimals(3).suffix(" s")).changed();
+        if start_changed || end_changed {
+            app.state.history.export_range_s = Some((start_s, end_s));
+        }
+
+        if ui.add_enabled(!exporting && end_s > start_s, egui::Button::new("✂ Export Range"))
+            .on_hover_text("Export only this time range to CSV, plus a WAV file of its audio. Drag on a plot to select the range")
+            .clicked()
+        {
+            let options = app.config.get_config().export.clone();
+            if let Err(e) = app.state.export_history_range_immediately(start_s, end_s, options) {
+                log::warn!("Failed to export history range: {}", e);
+            }
+        }
+
+        if app.state.history.export_range_s.is_some() && ui.small_button("Reset").on_hover_text("Select the whole session").clicked() {
+            app.state.history.export_range_s = None;
+        }
+    });
 }
 
-fn render_history_axis<F>(ui: &mut egui::Ui, title: &str, data: &[DataPoint], value_extractor: F, color: Color32)
+/// 历史波形的公共绘制参数
+#[derive(Clone, Copy)]
+struct HistoryPlotStyle {
+    ticks: (usize, usize),
+    y_range_mode: YRangeMode,
+    smoothing_window: Option<usize>, // 滑动平均叠加线的窗口，None表示不绘制
+    time_cursor_ms: Option<f64>,     // 音频播放光标位置，None表示不绘制
+    y_padding: YPaddingConfig,
+    absolute_time: bool,             // 横轴标签显示绝对时间
+    export_range_s: Option<(f64, f64)>, // 片段导出范围，None表示不绘制
+}
+
+/// 横轴标签：数据始终按相对起点的秒数绘制（播放光标也依赖这一点），只在显示时换算成绝对时间
+fn format_time_axis_label(elapsed_seconds: f64, start_timestamp_ms: i64, absolute_time: bool) -> String {
+    if absolute_time {
+        format_timestamp(start_timestamp_ms + (elapsed_seconds * 1000.0).round() as i64)
+    } else {
+        format!("{:.2}s", elapsed_seconds)
+    }
+}
+
+fn render_history_axis<F>(ui: &mut egui::Ui, title: &str, data: &[DataPoint], value_extractor: F, stroke: Stroke, style: HistoryPlotStyle, range_selection: &mut Option<(f64, f64)>)
 where
     F: Fn(&DataPoint) -> f64,
 {
@@ -161,81 +310,113 @@ where
         |(min, max), &val| (min.min(val), max.max(val))
     );
 
-    let range = (y_max - y_min).max(0.1);
-    let y_min_padded = y_min - range * 0.05;
-    let y_max_padded = y_max + range * 0.05;
+    // 历史数据是静态的，按整段数据计算的自动范围本身就是稳定的（锁定模式等同于自动）
+    let (y_min_padded, y_max_padded) = style.y_range_mode.fixed_bounds()
+        .unwrap_or_else(|| style.y_padding.apply(y_min, y_max));
 
+    let start_timestamp_ms = data.first().unwrap().timestamp;
     Plot::new(title)
         .height(75.0)
-        .x_axis_formatter(|v, _| format!("{:.2}s", v.value))
+        .x_axis_formatter(move |v, _| format_time_axis_label(v.value, start_timestamp_ms, style.absolute_time))
         .y_axis_formatter(|v, _| format_fixed_width_y_label(v.value))
+        .x_grid_spacer(tick_spacer(style.ticks.0))
+        .y_grid_spacer(tick_spacer(style.ticks.1))
         .allow_drag(true)
         .allow_zoom(true)
         .show(ui, |plot_ui| {
-            let points: Vec<[f64; 2]> = data
+            let times: Vec<f64> = data
                 .iter()
-                .map(|dp| {
-                    let time_offset = (dp.timestamp as f64 / 1000.0) - start_time;
-                    [time_offset, value_extractor(dp)]
-                })
+                .map(|dp| (dp.timestamp as f64 / 1000.0) - start_time)
                 .collect();
+            let points: Vec<[f64; 2]> = times.iter().zip(&values).map(|(&t, &v)| [t, v]).collect();
 
             plot_ui.set_plot_bounds(egui_plot::PlotBounds::from_min_max(
                 [0.0, y_min_padded],
                 [(data.last().unwrap().timestamp as f64 / 1000.0) - start_time, y_max_padded],
             ));
 
-            plot_ui.line(Line::new(title, PlotPoints::from(points)).color(color).width(0.75));
+            plot_ui.line(Line::new(title, PlotPoints::from(points)).stroke(stroke));
+
+            // 滑动平均叠加线，颜色加深、线宽加粗以区别于原始数据
+            if let Some(window) = style.smoothing_window {
+                let smoothed: Vec<[f64; 2]> = times.iter()
+                    .zip(moving_average(&values, window))
+                    .map(|(&t, v)| [t, v])
+                    .collect();
+                let smooth_stroke = Stroke::new(stroke.width * 2.0, stroke.color.gamma_multiply(0.6).to_opaque());
+                plot_ui.line(Line::new(format!("{} (smoothed)", title), PlotPoints::from(smoothed)).stroke(smooth_stroke));
+            }
+
+            draw_export_range(plot_ui, style.export_range_s);
+            draw_time_cursor(plot_ui, style.time_cursor_ms);
+            if let Some(selection) = drag_selection(plot_ui) {
+                *range_selection = Some(selection);
+            }
         });
 }
 
 
 // 音频波形显示（不带控制按钮）
-fn render_history_audio_waveform(ui: &mut egui::Ui, title: &str, audio_data: &[f64], color: Color32, _playback_state: &crate::app::state::AudioPlaybackState) {
-    if audio_data.is_empty() {
+fn render_history_audio_waveform(ui: &mut egui::Ui, title: &str, waveform: &AudioWaveformPoints, start_timestamp_ms: i64, stroke: Stroke, style: HistoryPlotStyle, range_selection: &mut Option<(f64, f64)>) {
+    if waveform.points.is_empty() {
         return;
     }
 
-    let (y_min, y_max) = audio_data.iter().fold(
-        (f64::INFINITY, f64::NEG_INFINITY),
-        |(min, max), &val| (min.min(val), max.max(val))
-    );
+    let (y_min, y_max) = waveform.y_range;
 
-    let range = (y_max - y_min).max(0.1);
-    let y_min_padded = y_min - range * 0.05;
-    let y_max_padded = y_max + range * 0.05;
+    // 历史数据是静态的，按整段数据计算的自动范围本身就是稳定的（锁定模式等同于自动）
+    let (y_min_padded, y_max_padded) = style.y_range_mode.fixed_bounds()
+        .unwrap_or_else(|| style.y_padding.apply(y_min, y_max));
 
     Plot::new(title)
         .height(100.0)
-        .x_axis_formatter(|v, _| format!("{:.2}s", v.value))
+        .x_axis_formatter(move |v, _| format_time_axis_label(v.value, start_timestamp_ms, style.absolute_time))
         .y_axis_formatter(|v, _| format_fixed_width_y_label(v.value))
+        .x_grid_spacer(tick_spacer(style.ticks.0))
+        .y_grid_spacer(tick_spacer(style.ticks.1))
         .allow_drag(true)
         .allow_zoom(true)
         .show(ui, |plot_ui| {
-            // 假设16kHz采样率
-            let sample_rate = 16000.0;
-            let points: Vec<[f64; 2]> = audio_data
-                .iter()
-                .enumerate()
-                .map(|(i, &value)| {
-                    let time = i as f64 / sample_rate;
-                    [time, value]
-                })
-                .collect();
-
-            let duration = audio_data.len() as f64 / sample_rate;
             plot_ui.set_plot_bounds(egui_plot::PlotBounds::from_min_max(
                 [0.0, y_min_padded],
-                [duration, y_max_padded],
+                [waveform.duration, y_max_padded],
             ));
 
-            plot_ui.line(Line::new(title, PlotPoints::from(points)).color(color).width(1.0));
+            plot_ui.line(Line::new(title, PlotPoints::from(waveform.points.clone())).stroke(stroke));
 
+            draw_export_range(plot_ui, style.export_range_s);
+            draw_time_cursor(plot_ui, style.time_cursor_ms);
+            if let Some(selection) = drag_selection(plot_ui) {
+                *range_selection = Some(selection);
+            }
         });
 }
 
+/// 在波形上绘制播放光标，各波形的横轴都是相对数据起点的秒数
+fn draw_time_cursor(plot_ui: &mut egui_plot::PlotUi, time_cursor_ms: Option<f64>) {
+    if let Some(cursor_ms) = time_cursor_ms {
+        plot_ui.vline(VLine::new("Playback", cursor_ms / 1000.0).color(Color32::RED).width(1.5));
+    }
+}
 
+/// 用两条竖线标出片段导出的起止时间
+fn draw_export_range(plot_ui: &mut egui_plot::PlotUi, export_range_s: Option<(f64, f64)>) {
+    if let Some((start_s, end_s)) = export_range_s {
+        for x in [start_s, end_s] {
+            plot_ui.vline(VLine::new("Export range", x).color(Color32::from_rgb(255, 200, 0)).width(1.0));
+        }
+    }
+}
 
+/// 主键拖动时返回拖动起点到当前指针之间的时间范围（秒），历史波形的视图每帧固定，拖动不会平移
+fn drag_selection(plot_ui: &egui_plot::PlotUi) -> Option<(f64, f64)> {
+    let response = plot_ui.response();
+    if !response.dragged_by(egui::PointerButton::Primary) {
+        return None;
+    }
 
-
-
+    let origin = response.ctx.input(|i| i.pointer.press_origin())?;
+    let current = plot_ui.pointer_coordinate()?.x;
+    let anchor = plot_ui.plot_from_screen(origin).x;
+    Some((anchor.min(current), anchor.max(current)))
+}
@@ -1,5 +1,11 @@
 use eframe::egui;
 use crate::app::app_core::SensorDataApp;
+use crate::app::state::TextReaderState;
+use crate::config::{AudioDisplayMode, TextReaderConfig};
+use log::{info, warn};
+
+/// 文本阅读器中当前行上下各显示的上下文行数
+const TEXT_READER_CONTEXT_LINES: usize = 2;
 
 pub fn render_main_panel(app: &mut SensorDataApp, ctx: &egui::Context) {
     egui::CentralPanel::default().show(ctx, |ui| {
@@ -25,31 +31,76 @@ pub fn render_main_panel(app: &mut SensorDataApp, ctx: &egui::Context) {
             } else {
                 ui.label(functions.join(" + "));
             }
+            ui.colored_label(egui::Color32::from_rgb(0, 150, 0), "Ctrl+S");
+            ui.label("Save + export");
             
             ui.separator();
             
             // 用户名输入框
             ui.label("Username:");
-            ui.add(egui::TextEdit::singleline(&mut app.state.collection.username)
+            // 修改前的用户名/场景，采集中修改时先按原身份保存当前窗口
+            let previous_username = app.state.collection.username.clone();
+            let previous_scenario = app.state.collection.scenario.clone();
+            let mut username_text = previous_username.clone();
+            let username_response = ui.add(egui::TextEdit::singleline(&mut username_text)
                 .desired_width(100.0)
                 .hint_text("Enter username"));
+            if username_response.changed() {
+                app.flush_window_before_identity_change(previous_username.clone(), previous_scenario.clone());
+                app.state.collection.username = username_text;
+            }
+            if let Some(username) = recent_values_menu(ui, &app.state.collection.recent_usernames, &previous_username) {
+                app.flush_window_before_identity_change(previous_username.clone(), previous_scenario.clone());
+                app.state.collection.username = username;
+                app.state.collection.identity_change_flushed = false;
+            }
             
-            // 场景输入框
+            // 场景输入框，清空时回退到配置的默认场景
             ui.label("Scenario:");
+            let default_scenario = app.config.get_config().session.default_scenario.clone();
             let mut scenario_text = app.state.collection.scenario.clone();
             if scenario_text.is_empty() {
-                scenario_text = "standard".to_string();
+                scenario_text = default_scenario.clone();
                 app.state.collection.scenario = scenario_text.clone();
             }
-            if ui.add(egui::TextEdit::singleline(&mut scenario_text)
+            let scenario_response = ui.add(egui::TextEdit::singleline(&mut scenario_text)
                 .desired_width(100.0)
-                .hint_text("standard")).changed() {
+                .hint_text(&default_scenario));
+            if scenario_response.changed() {
+                app.flush_window_before_identity_change(previous_username.clone(), previous_scenario.clone());
                 app.state.collection.scenario = if scenario_text.is_empty() {
-                    "standard".to_string()
+                    default_scenario
                 } else {
                     scenario_text
                 };
             }
+            if let Some(scenario) = recent_values_menu(ui, &app.state.collection.recent_scenarios, &previous_scenario) {
+                app.flush_window_before_identity_change(previous_username, previous_scenario);
+                app.state.collection.scenario = scenario;
+                app.state.collection.identity_change_flushed = false;
+                app.apply_scenario_config();
+            }
+            // 场景配置在编辑结束（失去焦点或回车）时才应用，输入过程中的中间值不触发
+            if scenario_response.lost_focus() {
+                app.apply_scenario_config();
+            }
+            if username_response.lost_focus() || scenario_response.lost_focus() {
+                app.state.collection.identity_change_flushed = false;
+            }
+            
+            ui.separator();
+            
+            // 当前session ID，只读显示，可复制到剪贴板用于外部记录
+            ui.label("Session:");
+            let session_id = app.state.collection.current_session_id.clone();
+            ui.add(egui::TextEdit::singleline(&mut session_id.as_str())
+                .desired_width(140.0));
+            if ui.button("📋 Copy")
+                .on_hover_text("Click to copy session ID to clipboard")
+                .clicked()
+            {
+                ui.ctx().copy_text(session_id);
+            }
             
             ui.separator();
             
@@ -58,6 +109,13 @@ pub fn render_main_panel(app: &mut SensorDataApp, ctx: &egui::Context) {
             if ui.checkbox(&mut app.state.text_reader.is_enabled, "Enable").changed() {
                 // 当启用/禁用文本阅读器时的处理
             }
+
+            let current_file = app.state.text_reader.file_path.as_ref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_else(|| "No file loaded".to_string());
+            if ui.button("📂 Load Text File").on_hover_text(current_file).clicked() {
+                load_text_file_from_dialog(app);
+            }
             
             ui.separator();
             
@@ -68,6 +126,17 @@ pub fn render_main_panel(app: &mut SensorDataApp, ctx: &egui::Context) {
                 // 更新配置
                 app.config.get_config_mut().plot.show_gyroscope = show_gyroscope;
             }
+
+            let audio_mode = &mut app.config.get_config_mut().plot.audio_display_mode;
+            egui::ComboBox::from_id_salt("audio_display_mode_selector")
+                .selected_text(audio_mode.label())
+                .show_ui(ui, |ui| {
+                    for mode in AudioDisplayMode::ALL {
+                        ui.selectable_value(audio_mode, mode, mode.label());
+                    }
+                })
+                .response
+                .on_hover_text("How the live audio is drawn; RMS envelope and spectrogram are much faster for long windows");
         });
         ui.add_space(10.0);
 
@@ -80,20 +149,19 @@ pub fn render_main_panel(app: &mut SensorDataApp, ctx: &egui::Context) {
                     ui.label(format!("Line: {}", app.state.get_text_info()));
                     ui.separator();
                     ui.label("Controls: SPACE=Next, ←=Previous, →=Next");
+
+                    if app.state.text_reader.end_of_file_reached {
+                        ui.separator();
+                        ui.colored_label(egui::Color32::from_rgb(200, 120, 0), "📖 End of file");
+                        if ui.button("↺ Restart").clicked() {
+                            app.state.restart_text_reader();
+                        }
+                    }
                 });
                 
                 ui.add_space(5.0);
                 
-                // 显示当前文本，使用较大的字体
-                ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
-                    ui.add(
-                        egui::Label::new(
-                            egui::RichText::new(&app.state.text_reader.current_text)
-                                .size(24.0)
-                                .color(egui::Color32::BLACK)
-                        ).wrap()
-                    );
-                });
+                render_text_reader_lines(ui, &app.state.text_reader, &app.config.get_config().text_reader);
             });
             ui.add_space(10.0);
         }
@@ -101,3 +169,90 @@ pub fn render_main_panel(app: &mut SensorDataApp, ctx: &egui::Context) {
         app.state.waveform_plot.ui(ui, &app.config.get_config().plot);
     });
 }
+
+/// 输入框旁的下拉按钮，列出数据库中已有的值，返回新选择的值
+fn recent_values_menu(ui: &mut egui::Ui, values: &[String], current: &str) -> Option<String> {
+    let mut selected = None;
+    ui.add_enabled_ui(!values.is_empty(), |ui| {
+        ui.menu_button("▾", |ui| {
+            egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                for value in values {
+                    if ui.selectable_label(value == current, value).clicked() {
+                        if value != current {
+                            selected = Some(value.clone());
+                        }
+                        ui.close();
+                    }
+                }
+            });
+        })
+        .response
+        .on_hover_text("Pick a value already used in the database");
+    });
+    selected
+}
+
+/// 选择文本文件并记住路径，下次启动时自动加载
+fn load_text_file_from_dialog(app: &mut SensorDataApp) {
+    match app.state.load_text_file_from_dialog() {
+        Ok(Some(file_path)) => {
+            info!("Text file {} loaded", file_path.display());
+            app.state.text_reader.is_enabled = true;
+            app.config.get_config_mut().text_reader.last_file_path = Some(file_path);
+            if let Err(e) = app.config.save() {
+                warn!("Failed to remember text file path: {}", e);
+            }
+        }
+        Ok(None) => {}
+        Err(e) => warn!("Failed to load text file: {}", e),
+    }
+}
+
+/// 以当前行为中心显示前后几行，当前行用大字号高亮，上下文行变暗
+fn render_text_reader_lines(ui: &mut egui::Ui, reader: &TextReaderState, style: &TextReaderConfig) {
+    if reader.lines.is_empty() {
+        return;
+    }
+
+    let current = reader.current_line_index.min(reader.lines.len() - 1);
+    let first = current.saturating_sub(TEXT_READER_CONTEXT_LINES);
+    let last = (current + TEXT_READER_CONTEXT_LINES).min(reader.lines.len() - 1);
+
+    // 上下文行与当前行的字号比例保持默认的16:24；未配置颜色时随主题取强调文字色，高亮底色也按深浅主题区分
+    let context_size = style.font_size * 2.0 / 3.0;
+    let text_color = style.text_color
+        .map(|[r, g, b]| egui::Color32::from_rgb(r, g, b))
+        .unwrap_or_else(|| ui.visuals().strong_text_color());
+    let highlight_color = if ui.visuals().dark_mode {
+        egui::Color32::from_rgb(90, 75, 20)
+    } else {
+        egui::Color32::from_rgb(255, 243, 180)
+    };
+
+    ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
+        // 开头和结尾处用空行占位，保持当前行在面板中的位置不变
+        for _ in 0..(TEXT_READER_CONTEXT_LINES - (current - first)) {
+            ui.label(egui::RichText::new(" ").size(context_size));
+        }
+
+        for index in first..=last {
+            let line = &reader.lines[index];
+            let text = if index == current {
+                egui::RichText::new(line)
+                    .size(style.font_size)
+                    .strong()
+                    .color(text_color)
+                    .background_color(highlight_color)
+            } else {
+                egui::RichText::new(line)
+                    .size(context_size)
+                    .color(egui::Color32::GRAY)
+            };
+            ui.add(egui::Label::new(text).wrap());
+        }
+
+        for _ in 0..(TEXT_READER_CONTEXT_LINES - (last - current)) {
+            ui.label(egui::RichText::new(" ").size(context_size));
+        }
+    });
+}
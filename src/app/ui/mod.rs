@@ -3,8 +3,12 @@ pub mod export_dialog;
 pub mod main_panel;
 pub mod history_panel;
 pub mod history_controls;
+pub mod settings_panel;
+pub mod about_dialog;
 
 pub use status_bar::{render_status_bar, render_bottom_status_bar};
 pub use export_dialog::render_export_dialog;
 pub use main_panel::render_main_panel;
 pub use history_panel::render_history_panel;
+pub use settings_panel::render_settings_panel;
+pub use about_dialog::render_about_dialog;
@@ -0,0 +1,604 @@
+use eframe::egui;
+use crate::app::app_core::SensorDataApp;
+use crate::app::state::{DataChannels, SettingsTab};
+use crate::app::handlers::calibration::{MIN_PLAUSIBLE_SAMPLE_RATE, MAX_PLAUSIBLE_SAMPLE_RATE};
+use crate::config::{AppConfig, AudioStorageFormat, AxisUnit, DbQueueFullPolicy, OverflowStrategy, ReconnectStrategy, TimestampSource, TopicKind, TopicRoute, YRangeMode, DEFAULT_CONFIG_PATH};
+
+pub fn render_settings_panel(app: &mut SensorDataApp, ctx: &egui::Context) {
+    if !app.state.settings.show_settings_panel {
+        return;
+    }
+
+    let mut open = true;
+    egui::Window::new("⚙ Settings")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(true)
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            // 标签页选择
+            ui.horizontal(|ui| {
+                let tab = &mut app.state.settings.active_tab;
+                ui.selectable_value(tab, SettingsTab::Window, "Window");
+                ui.selectable_value(tab, SettingsTab::Mqtt, "MQTT");
+                ui.selectable_value(tab, SettingsTab::Plot, "Plot");
+                ui.selectable_value(tab, SettingsTab::Calibration, "Calibration");
+                ui.selectable_value(tab, SettingsTab::Channels, "Channels");
+            });
+            ui.separator();
+
+            let active_tab = app.state.settings.active_tab;
+            let config = app.config.get_config_mut();
+            egui::Grid::new("settings_grid")
+                .num_columns(2)
+                .spacing([20.0, 6.0])
+                .show(ui, |ui| {
+                    match active_tab {
+                        SettingsTab::Window => render_window_settings(ui, config),
+                        SettingsTab::Mqtt => render_mqtt_settings(ui, config),
+                        SettingsTab::Plot => {
+                            render_plot_settings(ui, config, &mut app.state.collection.auto_save_interval_ms)
+                        }
+                        SettingsTab::Calibration => {
+                            render_calibration_settings(ui, config, &mut app.state.auto_calibration_restart)
+                        }
+                        SettingsTab::Channels => render_channel_settings(ui, config, &app.state.channels),
+                    }
+                });
+
+            ui.add_space(6.0);
+            ui.colored_label(egui::Color32::GRAY, "⟳ = takes effect after restart");
+
+            // 配置文件加载失败时不允许保存，避免默认配置覆盖用户的文件
+            let load_error = app.config.load_error().map(str::to_string);
+            if let Some(error) = &load_error {
+                ui.colored_label(
+                    egui::Color32::from_rgb(200, 0, 0),
+                    format!("⚠ Config file could not be loaded, running with defaults: {}", error),
+                );
+            }
+
+            // 实时校验当前配置，不合法时禁止保存
+            let validation = app.config.get_config().validate();
+            if let Err(e) = &validation {
+                ui.colored_label(egui::Color32::from_rgb(200, 0, 0), format!("⚠ {}", e));
+            }
+
+            ui.add_space(10.0);
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                let save_button = ui.add_enabled(validation.is_ok() && load_error.is_none(), egui::Button::new("💾 Save Config"))
+                    .on_disabled_hover_text("Fix the configuration errors above first");
+                if save_button.clicked() {
+                    app.state.settings.status = match app.config.save_to_file(DEFAULT_CONFIG_PATH) {
+                        Ok(()) => format!("Config saved to {}", DEFAULT_CONFIG_PATH),
+                        Err(e) => format!("Failed to save config: {}", e),
+                    };
+                }
+
+                if !app.state.settings.status.is_empty() {
+                    ui.colored_label(egui::Color32::from_rgb(0, 100, 200), &app.state.settings.status);
+                }
+            });
+        });
+
+    if !open {
+        app.state.settings.show_settings_panel = false;
+    }
+}
+
+fn render_window_settings(ui: &mut egui::Ui, config: &mut AppConfig) {
+    let window = &mut config.window;
+
+    restart_required_label(ui, "Title:");
+    ui.text_edit_singleline(&mut window.title);
+    ui.end_row();
+
+    restart_required_label(ui, "Width:");
+    ui.add(egui::DragValue::new(&mut window.width).range(200.0..=7680.0).suffix(" px"));
+    ui.end_row();
+
+    restart_required_label(ui, "Height:");
+    ui.add(egui::DragValue::new(&mut window.height).range(200.0..=4320.0).suffix(" px"));
+    ui.end_row();
+
+    render_optional_position(ui, "X:", &mut window.x);
+    render_optional_position(ui, "Y:", &mut window.y);
+
+    restart_required_label(ui, "Resizable:");
+    ui.checkbox(&mut window.resizable, "");
+    ui.end_row();
+
+    restart_required_label(ui, "VSync:");
+    ui.checkbox(&mut window.vsync, "");
+    ui.end_row();
+
+    restart_required_label(ui, "Hardware acceleration:");
+    ui.checkbox(&mut window.hardware_acceleration, "");
+    ui.end_row();
+
+    let text_reader = &mut config.text_reader;
+
+    ui.label("Reader font size:");
+    ui.add(egui::DragValue::new(&mut text_reader.font_size).range(8.0..=96.0).suffix(" pt"));
+    ui.end_row();
+
+    ui.label("Reader text color:");
+    ui.horizontal(|ui| {
+        let mut custom_color = text_reader.text_color.is_some();
+        if ui.checkbox(&mut custom_color, "Custom").changed() {
+            text_reader.text_color = custom_color.then_some([0, 0, 0]);
+        }
+        if let Some(color) = text_reader.text_color.as_mut() {
+            ui.color_edit_button_srgb(color);
+        } else {
+            ui.label("Follows theme");
+        }
+    });
+    ui.end_row();
+
+    let session = &mut config.session;
+
+    restart_required_label(ui, "Default username:");
+    ui.text_edit_singleline(&mut session.default_username);
+    ui.end_row();
+
+    restart_required_label(ui, "Default scenario:");
+    ui.text_edit_singleline(&mut session.default_scenario);
+    ui.end_row();
+
+    let audio = &mut config.audio;
+
+    ui.label("Playback output rate:");
+    ui.horizontal(|ui| {
+        let mut fixed_rate = audio.preferred_output_rate.is_some();
+        if ui.checkbox(&mut fixed_rate, "Fixed").changed() {
+            audio.preferred_output_rate = fixed_rate.then_some(44100);
+        }
+        if let Some(rate) = audio.preferred_output_rate.as_mut() {
+            ui.add(egui::DragValue::new(rate).range(8000..=192000).suffix(" Hz"));
+        } else {
+            ui.label("Device native");
+        }
+    });
+    ui.end_row();
+}
+
+/// 需要重启才能生效的设置项标签
+fn restart_required_label(ui: &mut egui::Ui, label: &str) {
+    ui.label(format!("{} ⟳", label)).on_hover_text("Takes effect after restart");
+}
+
+fn render_optional_position(ui: &mut egui::Ui, label: &str, value: &mut Option<f32>) {
+    restart_required_label(ui, label);
+    ui.horizontal(|ui| {
+        let mut is_set = value.is_some();
+        if ui.checkbox(&mut is_set, "Fixed").changed() {
+            *value = if is_set { Some(0.0) } else { None };
+        }
+        if let Some(position) = value {
+            ui.add(egui::DragValue::new(position).suffix(" px"));
+        } else {
+            ui.label("Centered");
+        }
+    });
+    ui.end_row();
+}
+
+fn render_mqtt_settings(ui: &mut egui::Ui, config: &mut AppConfig) {
+    let mqtt = &mut config.mqtt;
+
+    restart_required_label(ui, "Broker:");
+    ui.text_edit_singleline(&mut mqtt.broker);
+    ui.end_row();
+
+    restart_required_label(ui, "Port:");
+    ui.add(egui::DragValue::new(&mut mqtt.port));
+    ui.end_row();
+
+    restart_required_label(ui, "Client ID:");
+    ui.text_edit_singleline(&mut mqtt.client_id);
+    ui.end_row();
+
+    restart_required_label(ui, "Topics:");
+    ui.vertical(|ui| {
+        let mut removed = None;
+        for (index, route) in mqtt.routes.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut route.topic).desired_width(160.0));
+                egui::ComboBox::from_id_salt(("settings_mqtt_route_kind", index))
+                    .selected_text(route.kind.label())
+                    .show_ui(ui, |ui| {
+                        for kind in TopicKind::ALL {
+                            ui.selectable_value(&mut route.kind, kind, kind.label());
+                        }
+                    });
+                if ui.small_button("🗑").on_hover_text("Remove this topic").clicked() {
+                    removed = Some(index);
+                }
+            });
+        }
+        if let Some(index) = removed {
+            mqtt.routes.remove(index);
+        }
+        if ui.small_button("➕ Add topic").clicked() {
+            mqtt.routes.push(TopicRoute::new("", TopicKind::Accelerometer));
+        }
+    });
+    ui.end_row();
+
+    restart_required_label(ui, "QoS:");
+    egui::ComboBox::from_id_salt("settings_mqtt_qos")
+        .selected_text(mqtt.qos.to_string())
+        .show_ui(ui, |ui| {
+            for qos in 0..=2u8 {
+                ui.selectable_value(&mut mqtt.qos, qos, qos.to_string());
+            }
+        });
+    ui.end_row();
+
+    restart_required_label(ui, "Keep alive:");
+    ui.add(egui::DragValue::new(&mut mqtt.keep_alive).range(5..=3600).suffix(" s"));
+    ui.end_row();
+
+    restart_required_label(ui, "Ingest decimation:");
+    ui.add(egui::DragValue::new(&mut mqtt.ingest_decimation).range(1..=100))
+        .on_hover_text("Forward every Nth sensor point to the live display; every point is still saved");
+    ui.end_row();
+
+    restart_required_label(ui, "Alignment timestamps:");
+    egui::ComboBox::from_id_salt("settings_mqtt_timestamp_source")
+        .selected_text(mqtt.timestamp_source.label())
+        .show_ui(ui, |ui| {
+            for source in TimestampSource::ALL {
+                ui.selectable_value(&mut mqtt.timestamp_source, source, source.label());
+            }
+        })
+        .response
+        .on_hover_text("Both timestamps are stored; this selects which one aligns audio and accelerometer data");
+    ui.end_row();
+
+    restart_required_label(ui, "Reconnect strategy:");
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_salt("settings_mqtt_reconnect_strategy")
+            .selected_text(mqtt.reconnect_strategy.label())
+            .show_ui(ui, |ui| {
+                for strategy in ReconnectStrategy::ALL {
+                    let selected = std::mem::discriminant(&mqtt.reconnect_strategy) == std::mem::discriminant(&strategy);
+                    if ui.selectable_label(selected, strategy.label()).clicked() && !selected {
+                        mqtt.reconnect_strategy = strategy;
+                    }
+                }
+            })
+            .response
+            .on_hover_text(format!("Delay between automatic reconnect attempts; after {} failures SenseHub waits for a manual reconnect", crate::mqtt::MQTT_MAX_RETRIES));
+        if let ReconnectStrategy::Fixed(secs) = &mut mqtt.reconnect_strategy {
+            ui.add(egui::DragValue::new(secs).range(1..=3600).suffix(" s"));
+        }
+    });
+    ui.end_row();
+
+    ui.label("Auto-stop after silence:");
+    ui.add(egui::DragValue::new(&mut mqtt.auto_stop_after_silence_secs).range(0..=86400).suffix(" s"))
+        .on_hover_text("0 disables auto-stop");
+    ui.end_row();
+}
+
+fn render_plot_settings(ui: &mut egui::Ui, config: &mut AppConfig, auto_save_interval_ms: &mut u64) {
+    let plot = &mut config.plot;
+
+    ui.label("Window duration:");
+    ui.add(egui::DragValue::new(&mut plot.window_duration_seconds).range(0.5..=120.0).speed(0.1).suffix(" s"));
+    ui.end_row();
+
+    ui.label("Save window duration:");
+    ui.add(egui::DragValue::new(&mut plot.save_window_duration_seconds).range(0.5..=600.0).speed(0.1).suffix(" s"));
+    ui.end_row();
+
+    ui.label("Auto-save interval:");
+    ui.add(egui::DragValue::new(auto_save_interval_ms).range(500..=600_000).speed(100.0).suffix(" ms"))
+        .on_hover_text("Applies to the current session only; defaults to the save window duration");
+    ui.end_row();
+
+    ui.label("Save on user change:");
+    ui.checkbox(&mut config.database.save_on_identity_change, "Save the current window under the old user/scenario before switching");
+    ui.end_row();
+
+    restart_required_label(ui, "Audio storage:");
+    egui::ComboBox::from_id_salt("settings_audio_storage_format")
+        .selected_text(config.database.audio_storage_format.label())
+        .show_ui(ui, |ui| {
+            for format in AudioStorageFormat::ALL {
+                ui.selectable_value(&mut config.database.audio_storage_format, format, format.label());
+            }
+        });
+    ui.end_row();
+
+    ui.label("Plot height:");
+    ui.add(egui::DragValue::new(&mut plot.plot_height).range(50.0..=600.0).suffix(" px"));
+    ui.end_row();
+
+    ui.label("Display decimation:");
+    ui.add(egui::DragValue::new(&mut plot.display_decimation).range(1..=100))
+        .on_hover_text("Draw only every Nth sample of the live waveform; every sample is still saved");
+    ui.end_row();
+
+    ui.label("X ticks:");
+    ui.add(egui::DragValue::new(&mut plot.tick_count_x).range(2..=20));
+    ui.end_row();
+
+    ui.label("Y ticks:");
+    ui.add(egui::DragValue::new(&mut plot.tick_count_y).range(2..=20));
+    ui.end_row();
+
+    ui.label("Show axes:");
+    ui.checkbox(&mut plot.show_axes, "");
+    ui.end_row();
+
+    ui.label("Show grid:");
+    ui.checkbox(&mut plot.show_grid, "");
+    ui.end_row();
+
+    ui.label("Plot background:");
+    ui.horizontal(|ui| {
+        let mut custom_background = plot.background_color.is_some();
+        if ui.checkbox(&mut custom_background, "Custom").changed() {
+            plot.background_color = custom_background.then_some([20, 20, 20]);
+        }
+        if let Some(color) = plot.background_color.as_mut() {
+            ui.color_edit_button_srgb(color);
+        }
+    });
+    ui.end_row();
+
+    ui.label("Allow drag:");
+    ui.checkbox(&mut plot.allow_drag, "");
+    ui.end_row();
+
+    ui.label("Allow zoom:");
+    ui.checkbox(&mut plot.allow_zoom, "");
+    ui.end_row();
+
+    ui.label("Show gyroscope:");
+    ui.checkbox(&mut plot.show_gyroscope, "");
+    ui.end_row();
+
+    ui.label("Show temperature:");
+    ui.checkbox(&mut plot.show_temperature, "Only when the IMU reports a die temperature");
+    ui.end_row();
+
+    render_y_range_mode(ui, "ACC y-range:", "settings_y_range_acc", &mut plot.y_range.accelerometer, (-20.0, 20.0));
+    render_y_range_mode(ui, "GYRO y-range:", "settings_y_range_gyro", &mut plot.y_range.gyroscope, (-10.0, 10.0));
+    render_y_range_mode(ui, "Audio y-range:", "settings_y_range_audio", &mut plot.y_range.audio, (-1.0, 1.0));
+
+    for (label, width) in [
+        ("ACC line width:", &mut plot.line_width.accelerometer),
+        ("GYRO line width:", &mut plot.line_width.gyroscope),
+        ("Audio line width:", &mut plot.line_width.audio),
+    ] {
+        ui.label(label);
+        ui.add(egui::DragValue::new(width).range(0.25..=5.0).speed(0.05));
+        ui.end_row();
+    }
+
+    ui.label("Y-range padding:");
+    ui.add(egui::DragValue::new(&mut plot.y_padding.fraction).range(0.0..=1.0).speed(0.005).suffix(" × range"))
+        .on_hover_text("Space added above and below the data in auto/locked y-range modes");
+    ui.end_row();
+
+    ui.label("Minimum y-range:");
+    ui.add(egui::DragValue::new(&mut plot.y_padding.min_range).range(0.0001..=1000.0).speed(0.01))
+        .on_hover_text("Smallest span used when framing a near-constant signal");
+    ui.end_row();
+
+    ui.label("Show peaks:");
+    ui.checkbox(&mut plot.show_peaks, "");
+    ui.end_row();
+
+    render_axis_unit(ui, "ACC unit:", "settings_acc_unit", &mut plot.acc_unit, &mut plot.acc_scale);
+    render_axis_unit(ui, "GYRO unit:", "settings_gyro_unit", &mut plot.gyro_unit, &mut plot.gyro_scale);
+
+    ui.label("Peak threshold:");
+    ui.add_enabled(plot.show_peaks, egui::DragValue::new(&mut plot.peak_threshold).range(-1000.0..=1000.0).speed(0.1))
+        .on_hover_text("Local maxima below this value are not marked");
+    ui.end_row();
+
+    let colors = &mut plot.colors;
+    for (label, color) in [
+        ("ACC X color:", &mut colors.x_axis),
+        ("ACC Y color:", &mut colors.y_axis),
+        ("ACC Z color:", &mut colors.z_axis),
+        ("GYRO X color:", &mut colors.gyro_x),
+        ("GYRO Y color:", &mut colors.gyro_y),
+        ("GYRO Z color:", &mut colors.gyro_z),
+        ("Audio color:", &mut colors.audio),
+    ] {
+        ui.label(label);
+        ui.color_edit_button_srgb(color);
+        ui.end_row();
+    }
+}
+
+fn render_axis_unit(ui: &mut egui::Ui, label: &str, id: &str, unit: &mut AxisUnit, scale: &mut Option<f64>) {
+    ui.label(label);
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_salt(id)
+            .selected_text(unit.label())
+            .show_ui(ui, |ui| {
+                for option in AxisUnit::ALL {
+                    ui.selectable_value(unit, option, option.label());
+                }
+            });
+
+        // 传感器上报原始LSB时，用换算系数把刻度标签转换到所选单位
+        let mut use_scale = scale.is_some();
+        if ui.checkbox(&mut use_scale, "Scale").on_hover_text("Multiply raw LSB values by this factor in the axis labels").changed() {
+            *scale = use_scale.then_some(1.0);
+        }
+        if let Some(factor) = scale.as_mut() {
+            ui.add(egui::DragValue::new(factor).speed(0.0001).max_decimals(6));
+        }
+    });
+    ui.end_row();
+}
+
+fn render_y_range_mode(ui: &mut egui::Ui, label: &str, id: &str, mode: &mut YRangeMode, default_fixed: (f64, f64)) {
+    ui.label(label);
+    ui.horizontal(|ui| {
+        let mode_name = match mode {
+            YRangeMode::Auto => "Auto",
+            YRangeMode::Locked => "Locked",
+            YRangeMode::Fixed { .. } => "Fixed",
+        };
+
+        egui::ComboBox::from_id_salt(id)
+            .selected_text(mode_name)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(mode, YRangeMode::Auto, "Auto");
+                ui.selectable_value(mode, YRangeMode::Locked, "Locked")
+                    .on_hover_text("Auto-range that only expands, never contracts");
+                if ui.selectable_label(matches!(mode, YRangeMode::Fixed { .. }), "Fixed").clicked()
+                    && mode.fixed_bounds().is_none()
+                {
+                    *mode = YRangeMode::Fixed { min: default_fixed.0, max: default_fixed.1 };
+                }
+            });
+
+        if let YRangeMode::Fixed { min, max } = mode {
+            ui.add(egui::DragValue::new(min).speed(0.1).prefix("min "));
+            ui.add(egui::DragValue::new(max).speed(0.1).prefix("max "));
+        }
+    });
+    ui.end_row();
+}
+
+fn render_calibration_settings(ui: &mut egui::Ui, config: &mut AppConfig, auto_restart: &mut bool) {
+    let calibration = &mut config.calibration;
+
+    ui.label("Duration:");
+    ui.add(egui::DragValue::new(&mut calibration.duration_seconds).range(3.0..=60.0).speed(0.1).suffix(" s"));
+    ui.end_row();
+
+    ui.label("Minimum samples:");
+    ui.add(egui::DragValue::new(&mut calibration.min_samples).range(2..=100000));
+    ui.end_row();
+
+    ui.label("Initial sample rate:");
+    ui.add(egui::DragValue::new(&mut calibration.initial_sample_rate).range(1..=10000).suffix(" Hz"));
+    ui.end_row();
+
+    ui.label("Auto start:");
+    ui.checkbox(&mut calibration.auto_start, "");
+    ui.end_row();
+
+    ui.label("Buffer audio:");
+    ui.checkbox(&mut calibration.buffer_audio, "Keep audio received during calibration");
+    ui.end_row();
+
+    ui.label("Discard initial samples:");
+    ui.add(egui::DragValue::new(&mut calibration.discard_initial_samples).range(0..=10000));
+    ui.end_row();
+
+    ui.label("Auto restart:");
+    ui.checkbox(auto_restart, format!("Recalibrate when the rate is outside {}-{} Hz", MIN_PLAUSIBLE_SAMPLE_RATE, MAX_PLAUSIBLE_SAMPLE_RATE));
+    ui.end_row();
+
+    ui.label("Max retries:");
+    ui.add_enabled(*auto_restart, egui::DragValue::new(&mut calibration.max_retries).range(0..=20))
+        .on_hover_text("Falls back to the initial sample rate once the retries are used up");
+    ui.end_row();
+
+    let offsets = &mut calibration.axis_offsets;
+
+    ui.label("Accel offsets:")
+        .on_hover_text("Subtracted from the live plots only; saved data stays raw");
+    ui.horizontal(|ui| {
+        for (name, value) in [("X", &mut offsets.x), ("Y", &mut offsets.y), ("Z", &mut offsets.z)] {
+            ui.label(name);
+            ui.add(egui::DragValue::new(value).speed(0.001).max_decimals(4));
+        }
+    });
+    ui.end_row();
+
+    ui.label("Gyro offsets:")
+        .on_hover_text("Subtracted from the live plots only; saved data stays raw");
+    ui.horizontal(|ui| {
+        for (name, value) in [("X", &mut offsets.gx), ("Y", &mut offsets.gy), ("Z", &mut offsets.gz)] {
+            ui.label(name);
+            ui.add(egui::DragValue::new(value).speed(0.001).max_decimals(4));
+        }
+        if ui.add_enabled(!offsets.is_zero(), egui::Button::new("Clear")).clicked() {
+            *offsets = crate::config::AxisOffsets::default();
+        }
+    });
+    ui.end_row();
+}
+
+fn render_channel_settings(ui: &mut egui::Ui, config: &mut AppConfig, live_channels: &DataChannels) {
+    let channels = &mut config.channels;
+
+    // 当前通道占用和丢弃统计，用于按传感器的突发特性调整容量
+    ui.label("Data queue:");
+    render_queue_usage(ui, live_channels.data_receiver.len(), live_channels.data_receiver.capacity(), live_channels.drop_counters.data_dropped());
+    ui.end_row();
+
+    ui.label("Audio queue:");
+    render_queue_usage(ui, live_channels.audio_receiver.len(), live_channels.audio_receiver.capacity(), live_channels.drop_counters.audio_dropped());
+    ui.end_row();
+
+    restart_required_label(ui, "Data channel capacity:");
+    ui.add(egui::DragValue::new(&mut channels.data_channel_capacity).range(1..=1_000_000));
+    ui.end_row();
+
+    restart_required_label(ui, "Audio channel capacity:");
+    ui.add(egui::DragValue::new(&mut channels.audio_channel_capacity).range(1..=10_000_000));
+    ui.end_row();
+
+    restart_required_label(ui, "DB task channel capacity:");
+    ui.add(egui::DragValue::new(&mut channels.db_task_channel_capacity).range(1..=100_000));
+    ui.end_row();
+
+    restart_required_label(ui, "Save result channel capacity:");
+    ui.add(egui::DragValue::new(&mut channels.save_result_channel_capacity).range(1..=100_000));
+    ui.end_row();
+
+    restart_required_label(ui, "Overflow strategy:");
+    egui::ComboBox::from_id_salt("settings_overflow_strategy")
+        .selected_text(format!("{:?}", channels.overflow_strategy))
+        .show_ui(ui, |ui| {
+            for strategy in [OverflowStrategy::DropOldest, OverflowStrategy::DropNewest, OverflowStrategy::Block] {
+                ui.selectable_value(&mut channels.overflow_strategy, strategy, format!("{:?}", strategy));
+            }
+        });
+    ui.end_row();
+
+    ui.label("DB queue full:");
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_salt("settings_db_queue_full_policy")
+            .selected_text(channels.db_queue_full_policy.label())
+            .show_ui(ui, |ui| {
+                for policy in DbQueueFullPolicy::ALL {
+                    ui.selectable_value(&mut channels.db_queue_full_policy, policy, policy.label());
+                }
+            })
+            .response
+            .on_hover_text("What a save does when the database task queue is full");
+        if channels.db_queue_full_policy == DbQueueFullPolicy::Block {
+            ui.add(egui::DragValue::new(&mut channels.db_queue_block_timeout_ms).range(1..=DbQueueFullPolicy::MAX_BLOCK_TIMEOUT_MS).suffix(" ms"));
+        }
+    });
+    ui.end_row();
+}
+
+fn render_queue_usage(ui: &mut egui::Ui, len: usize, capacity: Option<usize>, dropped: u64) {
+    let usage = match capacity {
+        Some(capacity) => format!("{} / {}", len, capacity),
+        None => len.to_string(),
+    };
+    if dropped > 0 {
+        ui.colored_label(egui::Color32::from_rgb(200, 120, 0), format!("{} ({} dropped)", usage, dropped));
+    } else {
+        ui.label(usage);
+    }
+}
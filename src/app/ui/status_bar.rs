@@ -23,6 +23,9 @@ pub fn render_status_bar(app: &mut SensorDataApp, ctx: &egui::Context) {
 
                 ui.colored_label(status_color, status_text);
 
+                render_recording_indicator(app, ui);
+                render_audio_level_meter(app, ui);
+
                 // 添加暂停/恢复按钮
                 if app.state.collection.is_collecting && !app.state.calibration.is_calibrating {
                     ui.separator();
@@ -42,6 +45,35 @@ pub fn render_status_bar(app: &mut SensorDataApp, ctx: &egui::Context) {
                     }
                 }
 
+                // 校准期间允许跳过校准，按已知采样率立即开始采集
+                if app.state.calibration.is_calibrating {
+                    ui.separator();
+
+                    let sample_rate = app.config.get_config().calibration.initial_sample_rate;
+                    if ui.button("⏩ Quick Start")
+                        .on_hover_text(format!("Skip calibration and start collecting at {} Hz (Settings → initial sample rate)", sample_rate))
+                        .clicked()
+                    {
+                        crate::app::handlers::CalibrationHandler::quick_start(app);
+                    }
+                }
+
+                ui.separator();
+
+                // MQTT连接状态，断开时提供手动重连按钮
+                let mqtt_control = app.state.channels.mqtt_control.clone();
+                let mqtt_stats_text = mqtt_stats_tooltip(app);
+                if mqtt_control.is_connected() {
+                    ui.colored_label(egui::Color32::from_rgb(0, 150, 0), "MQTT: Connected")
+                        .on_hover_text(&mqtt_stats_text);
+                } else {
+                    ui.colored_label(egui::Color32::from_rgb(150, 0, 0), "MQTT: Disconnected")
+                        .on_hover_text(&mqtt_stats_text);
+                    if ui.button("🔄 Reconnect MQTT").on_hover_text("Skip the backoff wait and reconnect now").clicked() {
+                        mqtt_control.request_reconnect();
+                    }
+                }
+
                 ui.separator();
 
                 // 状态显示
@@ -57,13 +89,34 @@ pub fn render_status_bar(app: &mut SensorDataApp, ctx: &egui::Context) {
                 }
 
                 ui.separator();
-                ui.label(format!("Window: {:.1}s", app.config.get_config().plot.window_duration_seconds));
+                let plot_config = &app.config.get_config().plot;
+                if plot_config.save_window_duration_seconds != plot_config.window_duration_seconds {
+                    ui.label(format!("Window: {:.1}s (Save: {:.1}s)",
+                        plot_config.window_duration_seconds, plot_config.save_window_duration_seconds));
+                } else {
+                    ui.label(format!("Window: {:.1}s", plot_config.window_duration_seconds));
+                }
 
                 ui.separator();
 
 
                 // 在最右边添加导出按钮和历史面板按钮
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button("❓ About").clicked() {
+                        app.state.about.show_about_dialog = !app.state.about.show_about_dialog;
+                    }
+
+                    if ui.button("⚙ Settings").clicked() {
+                        app.state.settings.show_settings_panel = !app.state.settings.show_settings_panel;
+                    }
+
+                    if ui.button("📷 Screenshot")
+                        .on_hover_text("Save the whole window as a PNG in the data directory (F12)")
+                        .clicked()
+                    {
+                        app.request_screenshot(ctx);
+                    }
+
                     if ui.button("📤 Export Database").clicked() {
                         app.state.export.show_export_dialog = true;
                     }
@@ -112,7 +165,27 @@ pub fn render_status_bar(app: &mut SensorDataApp, ctx: &egui::Context) {
         });
 }
 
-fn render_status_details(app: &SensorDataApp, ui: &mut egui::Ui) {
+/// MQTT指示器的提示文字：消息数、解析错误率和最后一条消息的时间
+fn mqtt_stats_tooltip(app: &SensorDataApp) -> String {
+    let Ok(stats) = app.state.channels.mqtt_stats.lock().map(|stats| stats.clone()) else {
+        return "MQTT statistics unavailable".to_string();
+    };
+
+    let Some(error_rate) = stats.error_rate_percent() else {
+        return "No MQTT messages received yet".to_string();
+    };
+
+    format!(
+        "Messages received: {}\nParsed OK: {}\nParse errors: {} ({:.2}%)\nLast message: {}",
+        stats.messages_received,
+        stats.parse_ok,
+        stats.parse_error,
+        error_rate,
+        crate::utils::format_timestamp(stats.last_message_ms),
+    )
+}
+
+fn render_status_details(app: &mut SensorDataApp, ui: &mut egui::Ui) {
     if app.state.calibration.is_calibrating {
         if let Some(start_time) = app.state.calibration.calibration_start_time {
             let elapsed = start_time.elapsed().as_secs_f64();
@@ -130,6 +203,18 @@ fn render_status_details(app: &SensorDataApp, ui: &mut egui::Ui) {
         }
     } else if app.state.collection.is_collecting {
         ui.label("data collecting...");
+
+        // 校准期间采样率尚未确定，只在采集中用最近一段（传感器应保持静止）的数据记录零偏
+        let capture = ui.add_enabled(
+            !app.state.collection.is_paused,
+            egui::Button::new("🎯 Capture zero offset"),
+        ).on_hover_text(format!(
+            "Keep the sensor still. Uses the mean of each axis over the last {:.0}s as the offset subtracted from the live plots; saved data stays raw and the gravity axis is left alone",
+            ZERO_OFFSET_CAPTURE_SECONDS
+        ));
+        if capture.clicked() {
+            capture_zero_offset(app);
+        }
         
         // 显示自动保存状态
         if app.state.collection.auto_save_enabled {
@@ -149,6 +234,78 @@ fn render_status_details(app: &SensorDataApp, ui: &mut egui::Ui) {
     }
 }
 
+/// 记录零偏时取最近多少秒的数据
+const ZERO_OFFSET_CAPTURE_SECONDS: f64 = 1.0;
+
+/// 记录最近一段数据的各轴均值为零偏，只把零偏写回配置文件
+fn capture_zero_offset(app: &mut SensorDataApp) {
+    let samples = app.state.waveform_plot.recent_accelerometer_data(ZERO_OFFSET_CAPTURE_SECONDS);
+    let Some(offsets) = crate::config::AxisOffsets::from_still_samples(&samples) else {
+        app.state.collection.save_status = "No recent data to capture a zero offset from".to_string();
+        return;
+    };
+
+    app.config.get_config_mut().calibration.axis_offsets = offsets;
+    log::info!("已记录零偏（{} 个样本）: {:?}", samples.len(), offsets);
+    app.state.collection.save_status = match app.config.save_axis_offsets() {
+        Ok(()) => format!("Zero offset captured from {} samples", samples.len()),
+        Err(e) => format!("Zero offset captured but config could not be saved: {}", e),
+    };
+}
+
+/// 采集中显示录制指示灯（每秒闪烁一次，暂停时变灰常亮）和自开始采集以来的计时
+fn render_recording_indicator(app: &SensorDataApp, ui: &mut egui::Ui) {
+    let Some(started_at) = app.state.collection.collection_started_at else {
+        return;
+    };
+    if !app.state.collection.is_collecting {
+        return;
+    }
+
+    let elapsed = started_at.elapsed();
+    let dot_color = if app.state.collection.is_paused {
+        egui::Color32::GRAY
+    } else if elapsed.subsec_millis() < 500 {
+        egui::Color32::from_rgb(220, 0, 0)
+    } else {
+        egui::Color32::TRANSPARENT
+    };
+
+    ui.separator();
+    ui.label(egui::RichText::new("●").color(dot_color).size(16.0));
+    ui.label(egui::RichText::new(crate::utils::format_elapsed_clock(elapsed)).monospace().strong())
+        .on_hover_text("Time since collection started");
+
+    // 在下一次闪烁切换时重绘
+    let until_toggle = 500 - elapsed.subsec_millis() % 500;
+    ui.ctx().request_repaint_after(std::time::Duration::from_millis(until_toggle as u64));
+}
+
+/// 采集中显示实时音频电平（dBFS），电平条覆盖-60到0 dBFS，接近满幅时变红
+fn render_audio_level_meter(app: &SensorDataApp, ui: &mut egui::Ui) {
+    if !app.state.collection.is_collecting || app.state.collection.is_paused {
+        return;
+    }
+    let Some(level_db) = app.state.get_live_audio_rms_db() else {
+        return;
+    };
+
+    let fill = ((level_db + 60.0) / 60.0).clamp(0.0, 1.0) as f32;
+    let color = if level_db > -3.0 {
+        egui::Color32::from_rgb(220, 0, 0)
+    } else if level_db > -12.0 {
+        egui::Color32::from_rgb(220, 180, 0)
+    } else {
+        egui::Color32::from_rgb(0, 160, 0)
+    };
+
+    ui.separator();
+    ui.label("🎙");
+    ui.add(egui::ProgressBar::new(fill).desired_width(80.0).fill(color))
+        .on_hover_text("Live audio RMS level of the last 512 samples");
+    ui.label(egui::RichText::new(format!("{:>6.1} dBFS", level_db)).monospace());
+}
+
 pub fn render_bottom_status_bar(app: &mut SensorDataApp, ctx: &egui::Context) {
     egui::TopBottomPanel::bottom("bottom_status_bar")
         .min_height(25.0)
@@ -162,8 +319,58 @@ pub fn render_bottom_status_bar(app: &mut SensorDataApp, ctx: &egui::Context) {
                 }
                 
                 // 数据库连接状态
-                ui.label("DB: DuckDB");
+                render_database_status(app, ui);
                 ui.separator();
+
+                // 配置文件无效时以默认配置运行，且不会保存
+                if let Some(error) = app.config.load_error() {
+                    let label = ui.add(egui::Label::new(
+                        egui::RichText::new("⚠ Config not loaded").color(egui::Color32::from_rgb(200, 0, 0)),
+                    ).sense(egui::Sense::click()));
+                    if label
+                        .on_hover_text(format!("{}\nRunning with default settings; changes will not be saved until the file is fixed. Click to open Settings", error))
+                        .clicked()
+                    {
+                        app.state.settings.show_settings_panel = true;
+                    }
+                    ui.separator();
+                }
+
+                // 数据库队列满的持续提示，点击清除
+                let queue_full_count = app.state.collection.save_queue_full_count;
+                if queue_full_count > 0 {
+                    let pending = app.state.database.pending_saves.0.len();
+                    let message = if pending > 0 {
+                        format!("⚠ DB queue full {}×, {} saves pending", queue_full_count, pending)
+                    } else {
+                        format!("⚠ DB queue full {}×", queue_full_count)
+                    };
+                    if ui.add(egui::Label::new(egui::RichText::new(message).color(egui::Color32::from_rgb(200, 0, 0))).sense(egui::Sense::click()))
+                        .on_hover_text("Saves could not be queued because the database is falling behind. Choose a queue-full policy or increase the DB task channel capacity in Settings → Channels. Click to dismiss")
+                        .clicked()
+                    {
+                        app.state.collection.save_queue_full_count = 0;
+                    }
+                    ui.separator();
+                }
+
+                // 通道收发统计（每5秒刷新），通道饱和丢弃数据时高亮提示
+                let stats = app.state.channel_stats;
+                let message = format!("ACC: {} rx / {} drop, Audio: {} rx / {} drop",
+                                      stats.acc_total, stats.acc_dropped, stats.audio_total, stats.audio_dropped);
+                if stats.acc_dropped > 0 || stats.audio_dropped > 0 {
+                    ui.colored_label(egui::Color32::from_rgb(200, 120, 0), format!("⚠ {}", message))
+                        .on_hover_text("Channel full; consider increasing the channel capacity in Settings → Channels");
+                } else {
+                    ui.label(message);
+                }
+                ui.separator();
+
+                if let Some(message) = app.state.channels.audio_monitor.status_message() {
+                    ui.colored_label(egui::Color32::from_rgb(200, 0, 0), message)
+                        .on_hover_text("The UI is not draining audio fast enough; consider increasing the audio channel capacity in Settings → Channels");
+                    ui.separator();
+                }
                 
                 
                 // 文本阅读器状态
@@ -174,6 +381,13 @@ pub fn render_bottom_status_bar(app: &mut SensorDataApp, ctx: &egui::Context) {
                 
                 // 右侧：导出状态
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button("📋 View Log")
+                        .on_hover_text("Recent save and export results")
+                        .clicked()
+                    {
+                        app.state.show_status_log = !app.state.show_status_log;
+                    }
+
                     if !app.state.export.export_status.is_empty() {
                         ui.colored_label(egui::Color32::from_rgb(0, 150, 100), &app.state.export.export_status);
                     }
@@ -182,4 +396,96 @@ pub fn render_bottom_status_bar(app: &mut SensorDataApp, ctx: &egui::Context) {
             });
             ui.add_space(3.0);
         });
+
+    render_status_log(app, ctx);
+}
+
+/// 最近的保存/导出状态日志，最新的在最上面
+fn render_status_log(app: &mut SensorDataApp, ctx: &egui::Context) {
+    if !app.state.show_status_log {
+        return;
+    }
+
+    let mut open = true;
+    egui::Window::new("📋 Status Log")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(true)
+        .default_width(420.0)
+        .anchor(egui::Align2::RIGHT_BOTTOM, [-10.0, -35.0])
+        .show(ctx, |ui| {
+            if app.state.export_status_history.is_empty() {
+                ui.label("No save or export results yet");
+                return;
+            }
+
+            egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                let now = chrono::Local::now();
+                for (recorded_at, message) in app.state.export_status_history.iter().rev() {
+                    let time = chrono::Duration::from_std(recorded_at.elapsed())
+                        .map(|elapsed| (now - elapsed).format("%H:%M:%S").to_string())
+                        .unwrap_or_default();
+                    ui.horizontal(|ui| {
+                        ui.label(egui::RichText::new(time).monospace().weak());
+                        ui.label(message);
+                    });
+                }
+            });
+
+            ui.separator();
+            if ui.button("🗑 Clear").clicked() {
+                app.state.export_status_history.clear();
+            }
+        });
+
+    if !open {
+        app.state.show_status_log = false;
+    }
+}
+
+fn render_database_status(app: &mut SensorDataApp, ui: &mut egui::Ui) {
+    use crate::database::{DatabaseOpenError, DatabaseStatus};
+
+    match app.state.database.status.get() {
+        DatabaseStatus::Opening => {
+            ui.label("DB: Opening...");
+        }
+        DatabaseStatus::Ready { path } => {
+            let label = match app.state.database.size_bytes {
+                Some(size) => format!("DB: {}", crate::utils::format_bytes(size)),
+                None => "DB: DuckDB".to_string(),
+            };
+            ui.add(egui::Label::new(label).sense(egui::Sense::click()))
+                .on_hover_text(path)
+                .context_menu(|ui| {
+                    if ui.button("🔧 Rebuild table statistics")
+                        .on_hover_text("Run ANALYZE on the data tables, e.g. after deleting many sessions")
+                        .clicked()
+                    {
+                        if let Err(e) = app.state.database.db_task_sender.try_send(crate::types::DatabaseTask::Reindex) {
+                            app.state.collection.save_status = format!("Failed to request reindex: {}", e);
+                        }
+                        ui.close();
+                    }
+                });
+        }
+        DatabaseStatus::Failed(error) => {
+            let summary = match &error {
+                DatabaseOpenError::Locked(_) => "DB: Locked by another instance",
+                DatabaseOpenError::Incompatible(_) => "DB: Corrupt or incompatible file",
+                DatabaseOpenError::Other(_) => "DB: Failed to open",
+            };
+            ui.colored_label(egui::Color32::from_rgb(200, 0, 0), format!("⚠ {}", summary))
+                .on_hover_text(error.to_string());
+
+            if ui.button("🆕 Use new database file")
+                .on_hover_text("Open a fresh timestamped database file under data/")
+                .clicked()
+            {
+                if let Err(e) = app.state.database.db_task_sender.try_send(crate::types::DatabaseTask::OpenFallbackDatabase) {
+                    app.state.collection.save_status = format!("Failed to request new database: {}", e);
+                }
+            }
+        }
+    }
 }
@@ -2,6 +2,7 @@ use rodio::{OutputStreamBuilder, Sink, Source};
 use std::sync::{Arc, Mutex, mpsc};
 use std::thread::{self, JoinHandle};
 use std::time::Duration;
+use crate::types::DEFAULT_AUDIO_SAMPLE_RATE;
 
 /// 音频播放器状态
 #[derive(Debug, Clone, PartialEq)]
@@ -15,6 +16,7 @@ pub enum PlaybackState {
 #[derive(Debug, Clone)]
 pub enum AudioCommand {
     LoadAudio { data: Vec<f32>, sample_rate: f32 },
+    Resample { target_rate: f32 }, // 将已加载的音频线性重采样到目标采样率后再播放
     Play,
     Pause,
     Stop,
@@ -26,6 +28,8 @@ pub enum AudioCommand {
 pub struct AudioStatus {
     pub state: PlaybackState,
     pub is_available: bool,
+    pub position_samples: u64, // 当前播放位置（样本数），每50ms更新一次
+    pub output_sample_rate: u32, // 输出设备的采样率，音频流打开前为0
 }
 
 /// 自定义音频源，用于播放f32样本数据
@@ -77,12 +81,33 @@ impl Source for F32Source {
     }
 }
 
+/// 线性插值重采样
+fn resample_linear(data: &[f32], src_rate: f32, target_rate: f32) -> Vec<f32> {
+    if data.is_empty() || src_rate <= 0.0 || target_rate <= 0.0 {
+        return data.to_vec();
+    }
+
+    let ratio = target_rate / src_rate;
+    let new_len = (data.len() as f32 * ratio) as usize;
+    (0..new_len)
+        .map(|i| {
+            let position = i as f32 / ratio;
+            let index = position as usize;
+            let fraction = position - index as f32;
+            let current = data[index.min(data.len() - 1)];
+            let next = data[(index + 1).min(data.len() - 1)];
+            current + (next - current) * fraction
+        })
+        .collect()
+}
+
 /// 音频播放器
 pub struct AudioPlayer {
     command_sender: mpsc::Sender<AudioCommand>,
     status_receiver: Arc<Mutex<mpsc::Receiver<AudioStatus>>>,
     worker_handle: Option<JoinHandle<()>>,
     current_status: Arc<Mutex<AudioStatus>>,
+    preferred_output_rate: Option<u32>, // 回放采样率，未设置时使用输出设备的采样率
 }
 
 impl AudioPlayer {
@@ -94,6 +119,8 @@ impl AudioPlayer {
         let initial_status = AudioStatus {
             state: PlaybackState::Stopped,
             is_available: false,
+            position_samples: 0,
+            output_sample_rate: 0,
         };
 
         let current_status = Arc::new(Mutex::new(initial_status.clone()));
@@ -111,16 +138,32 @@ impl AudioPlayer {
             status_receiver: Arc::new(Mutex::new(status_receiver)),
             worker_handle: Some(worker_handle),
             current_status,
+            preferred_output_rate: None,
         })
     }
 
+    /// 设置回放采样率，None表示使用输出设备的采样率
+    pub fn set_preferred_output_rate(&mut self, rate: Option<u32>) {
+        self.preferred_output_rate = rate;
+    }
+
     /// 加载音频数据（从f64音频样本，16kHz采样率）
-    pub fn load_audio_data(&mut self, data: &[f64], original_sample_rate: f32) {
-        let audio_data: Vec<f32> = data.iter().map(|&x| x as f32).collect();
+    /// normalize为true时按整段音频的峰值放大到满幅，便于听清较轻的录音
+    pub fn load_audio_data(&mut self, data: &[f64], original_sample_rate: f32, normalize: bool) {
+        let peak = data.iter().fold(0.0f64, |peak, &x| peak.max(x.abs()));
+        let gain = if normalize && peak > f64::EPSILON { 1.0 / peak } else { 1.0 };
+        let audio_data: Vec<f32> = data.iter().map(|&x| (x * gain) as f32).collect();
         let _ = self.command_sender.send(AudioCommand::LoadAudio { 
             data: audio_data, 
             sample_rate: original_sample_rate 
         });
+
+        // 显式重采样到设备原生采样率，避免依赖rodio内部重采样产生失真
+        let device_rate = self.current_status.lock().unwrap().output_sample_rate;
+        let target_rate = self.preferred_output_rate.unwrap_or(device_rate);
+        if target_rate > 0 && target_rate as f32 != original_sample_rate {
+            let _ = self.command_sender.send(AudioCommand::Resample { target_rate: target_rate as f32 });
+        }
     }
 
     /// 开始播放
@@ -155,6 +198,11 @@ impl AudioPlayer {
         }
     }
 
+    /// 获取当前播放位置（样本数）
+    pub fn get_position_samples(&self) -> u64 {
+        self.current_status.lock().unwrap().position_samples
+    }
+
     /// 检查播放器是否可用
     pub fn is_available(&self) -> bool {
         self.current_status.lock().unwrap().is_available
@@ -182,18 +230,25 @@ fn audio_worker_thread(
     // 在 rodio 0.21 中，使用简单的方法创建音频输出流
     let _stream = OutputStreamBuilder::open_default_stream()
         .map_err(|e| format!("Failed to open default audio stream: {}", e))?;
+    let output_sample_rate = _stream.config().sample_rate();
 
     // 音频数据状态
     let audio_data = Arc::new(Mutex::new(Vec::<f32>::new()));
-    let sample_rate = Arc::new(Mutex::new(16000.0f32));
+    let sample_rate = Arc::new(Mutex::new(DEFAULT_AUDIO_SAMPLE_RATE as f32));
+    // 实际送入sink的采样率，重采样后与原始采样率不同；播放位置仍按原始采样率换算
+    let playback_rate = Arc::new(Mutex::new(DEFAULT_AUDIO_SAMPLE_RATE as f32));
     let sink = Arc::new(Mutex::new(Option::<Sink>::None));
     let playback_state = Arc::new(Mutex::new(PlaybackState::Stopped));
 
     // 发送初始状态
-    let _ = status_sender.send(AudioStatus {
+    let initial_status = AudioStatus {
         state: PlaybackState::Stopped,
         is_available: false,
-    });
+        position_samples: 0,
+        output_sample_rate,
+    };
+    *current_status.lock().unwrap() = initial_status.clone();
+    let _ = status_sender.send(initial_status);
 
     println!("Debug: Audio worker thread started with rodio 0.21");
 
@@ -215,18 +270,32 @@ fn audio_worker_thread(
                 // 存储音频数据
                 *audio_data.lock().unwrap() = data.clone();
                 *sample_rate.lock().unwrap() = sr;
+                *playback_rate.lock().unwrap() = sr;
 
                 // 更新状态
                 let status = AudioStatus {
                     state: PlaybackState::Stopped,
                     is_available: true,
+                    position_samples: 0,
+                    output_sample_rate,
                 };
                 *current_status.lock().unwrap() = status.clone();
                 let _ = status_sender.send(status);
             },
+            Ok(AudioCommand::Resample { target_rate }) => {
+                let current_rate = *playback_rate.lock().unwrap();
+                if target_rate <= 0.0 || target_rate == current_rate {
+                    continue;
+                }
+
+                let mut data = audio_data.lock().unwrap();
+                *data = resample_linear(&data, current_rate, target_rate);
+                *playback_rate.lock().unwrap() = target_rate;
+                log::debug!("Resampled audio from {} Hz to {} Hz ({} samples)", current_rate, target_rate, data.len());
+            },
             Ok(AudioCommand::Play) => {
                 let data = audio_data.lock().unwrap().clone();
-                let sr = *sample_rate.lock().unwrap();
+                let sr = *playback_rate.lock().unwrap();
                 
                 if data.is_empty() {
                     continue;
@@ -287,9 +356,19 @@ fn audio_worker_thread(
                     PlaybackState::Stopped
                 };
                 
+                // 根据sink已播放的时长换算播放位置，停止后归零
+                let position_samples = match (&current_state, sink.lock().unwrap().as_ref()) {
+                    (PlaybackState::Stopped, _) | (_, None) => 0,
+                    (_, Some(current_sink)) => {
+                        (current_sink.get_pos().as_secs_f32() * *sample_rate.lock().unwrap()) as u64
+                    }
+                };
+
                 let status = AudioStatus {
                     state: current_state,
                     is_available: !data.is_empty(),
+                    position_samples,
+                    output_sample_rate,
                 };
                 
                 *current_status.lock().unwrap() = status.clone();
@@ -1,11 +1,18 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
+use log::{info, warn};
+
+/// 默认配置文件路径
+pub const DEFAULT_CONFIG_PATH: &str = "config.toml";
 
 /// 应用配置管理模块
 /// 集中管理所有配置项，提供默认值和配置验证
 
 /// 主配置结构
+/// 所有配置节及其字段都带默认值：旧版本保存的配置文件缺少新字段时仍能加载
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AppConfig {
     pub window: WindowConfig,
     pub database: DatabaseConfig,
@@ -13,10 +20,86 @@ pub struct AppConfig {
     pub plot: PlotConfig,
     pub calibration: CalibrationConfig,
     pub channels: ChannelConfig,
+    pub export: ExportConfig,
+    // 按场景名的配置覆盖（[scenarios.<name>]），可省略
+    #[serde(default)]
+    pub scenarios: BTreeMap<String, ScenarioOverrides>,
+    // 文本阅读器（旧配置文件中没有该节时使用默认值）
+    #[serde(default)]
+    pub text_reader: TextReaderConfig,
+    // 音频回放（旧配置文件中没有该节时使用默认值）
+    #[serde(default)]
+    pub audio: AudioConfig,
+    // 采集时的用户名/场景（旧配置文件中没有该节时使用默认值）
+    #[serde(default)]
+    pub session: SessionConfig,
+}
+
+/// 场景配置覆盖：选择该场景时，设置了的字段覆盖基础配置，未设置的保持不变
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioOverrides {
+    pub window_duration_seconds: Option<f64>,
+    pub save_window_duration_seconds: Option<f64>,
+    pub show_gyroscope: Option<bool>,
+    pub display_decimation: Option<usize>,
+    pub show_peaks: Option<bool>,
+    pub peak_threshold: Option<f64>,
+    pub calibration_duration_seconds: Option<f64>,
+    pub calibration_min_samples: Option<usize>,
+}
+
+impl ScenarioOverrides {
+    /// 将覆盖写入配置，返回被替换的原值（同样以覆盖的形式），再次apply即可还原
+    pub fn apply(&self, config: &mut AppConfig) -> ScenarioOverrides {
+        fn replace<T: Copy>(target: &mut T, value: Option<T>) -> Option<T> {
+            value.map(|value| std::mem::replace(target, value))
+        }
+
+        let plot = &mut config.plot;
+        let calibration = &mut config.calibration;
+        ScenarioOverrides {
+            window_duration_seconds: replace(&mut plot.window_duration_seconds, self.window_duration_seconds),
+            save_window_duration_seconds: replace(&mut plot.save_window_duration_seconds, self.save_window_duration_seconds),
+            show_gyroscope: replace(&mut plot.show_gyroscope, self.show_gyroscope),
+            display_decimation: replace(&mut plot.display_decimation, self.display_decimation),
+            show_peaks: replace(&mut plot.show_peaks, self.show_peaks),
+            peak_threshold: replace(&mut plot.peak_threshold, self.peak_threshold),
+            calibration_duration_seconds: replace(&mut calibration.duration_seconds, self.calibration_duration_seconds),
+            calibration_min_samples: replace(&mut calibration.min_samples, self.calibration_min_samples),
+        }
+    }
+
+    /// 用apply返回的原值还原场景覆盖；applied为当时写入的覆盖值
+    /// 覆盖后又在设置界面中修改过的字段视为对基础配置的修改，保留当前值
+    pub fn restore_unless_edited(&self, applied: &ScenarioOverrides, config: &mut AppConfig) {
+        fn restore<T: Copy + PartialEq>(target: &mut T, original: Option<T>, applied: Option<T>) {
+            if let (Some(original), Some(applied)) = (original, applied) {
+                if *target == applied {
+                    *target = original;
+                }
+            }
+        }
+
+        let plot = &mut config.plot;
+        let calibration = &mut config.calibration;
+        restore(&mut plot.window_duration_seconds, self.window_duration_seconds, applied.window_duration_seconds);
+        restore(&mut plot.save_window_duration_seconds, self.save_window_duration_seconds, applied.save_window_duration_seconds);
+        restore(&mut plot.show_gyroscope, self.show_gyroscope, applied.show_gyroscope);
+        restore(&mut plot.display_decimation, self.display_decimation, applied.display_decimation);
+        restore(&mut plot.show_peaks, self.show_peaks, applied.show_peaks);
+        restore(&mut plot.peak_threshold, self.peak_threshold, applied.peak_threshold);
+        restore(&mut calibration.duration_seconds, self.calibration_duration_seconds, applied.calibration_duration_seconds);
+        restore(&mut calibration.min_samples, self.calibration_min_samples, applied.calibration_min_samples);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == ScenarioOverrides::default()
+    }
 }
 
 /// 窗口配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct WindowConfig {
     pub width: f32,
     pub height: f32,
@@ -30,44 +113,313 @@ pub struct WindowConfig {
 
 /// 数据库配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct DatabaseConfig {
     pub path: String,
     pub channel_capacity: usize,
     pub auto_create_dir: bool,
+    // 每个session最多保存的加速度数据行数，超出时删除最旧的行；None表示不限制
+    pub max_acc_rows_per_session: Option<usize>,
+    // 采集中修改用户名/场景时，先按原用户名/场景保存当前窗口并清空缓冲区
+    pub save_on_identity_change: bool,
+    // 音频块在数据库中的存储格式，PCM_16兼容旧版本
+    pub audio_storage_format: AudioStorageFormat,
 }
 
 /// MQTT配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct MqttConfig {
     pub broker: String,
     pub port: u16,
     pub client_id: String,
-    pub topics: MqttTopics,
+    // 订阅的主题及其数据类型，收到的消息按主题精确匹配分发到对应的解析器和通道
+    pub routes: Vec<TopicRoute>,
+    // 旧版本的 [mqtt.topics] 表，加载时转换为routes，不再写回
+    #[serde(rename = "topics", skip_serializing)]
+    pub legacy_topics: Option<LegacyMqttTopics>,
     pub qos: u8,
     pub keep_alive: u16,
+    // 每N个传感器数据点只转发1个到显示通道，减轻高采样率下GUI的负担；保存通道始终接收全部数据点，修改后需重启
+    pub ingest_decimation: usize,
+    // 采集中超过该时长（秒）未收到传感器数据则自动停止采集，0表示不自动停止
+    pub auto_stop_after_silence_secs: u64,
+    // MQTT凭据只从环境变量读取（SENSEHUB_MQTT_USER/SENSEHUB_MQTT_PASS），不写入配置文件
+    #[serde(skip)]
+    pub username: Option<String>,
+    #[serde(skip)]
+    pub password: Option<String>,
+    // 数据对齐使用的时间戳：设备时间戳或主机接收时间戳（两者都会保存）
+    pub timestamp_source: TimestampSource,
+    // 连接失败后的重试等待策略，最多自动重试MQTT_MAX_RETRIES次，之后等待手动重连
+    pub reconnect_strategy: ReconnectStrategy,
 }
 
-/// MQTT主题配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MqttTopics {
+/// 数据对齐使用的时间戳来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimestampSource {
+    Device,   // 设备负载中的时间戳
+    Arrival,  // 主机收到消息的时间，适用于设备时钟不可靠的情况
+}
+
+impl TimestampSource {
+    pub const ALL: [TimestampSource; 2] = [TimestampSource::Device, TimestampSource::Arrival];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TimestampSource::Device => "Device clock",
+            TimestampSource::Arrival => "Host arrival time",
+        }
+    }
+}
+
+/// MQTT连接失败后的重试等待策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReconnectStrategy {
+    ExponentialBackoff,  // 等待时间按重试次数指数增长（带随机抖动），上限30秒
+    Fixed(u64),          // 每次固定等待n秒
+    Immediate,           // 不等待，立即重试
+}
+
+impl ReconnectStrategy {
+    /// 设置界面中选择Fixed时的默认等待秒数
+    pub const DEFAULT_FIXED_SECS: u64 = 5;
+
+    pub const ALL: [ReconnectStrategy; 3] = [
+        ReconnectStrategy::ExponentialBackoff,
+        ReconnectStrategy::Fixed(Self::DEFAULT_FIXED_SECS),
+        ReconnectStrategy::Immediate,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReconnectStrategy::ExponentialBackoff => "Exponential backoff",
+            ReconnectStrategy::Fixed(_) => "Fixed delay",
+            ReconnectStrategy::Immediate => "Immediate",
+        }
+    }
+}
+
+/// 主题承载的数据类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TopicKind {
+    Accelerometer,  // 加速度计/陀螺仪JSON
+    Audio,          // Base64编码的PCM音频JSON
+}
+
+impl TopicKind {
+    pub const ALL: [TopicKind; 2] = [TopicKind::Accelerometer, TopicKind::Audio];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TopicKind::Accelerometer => "Accelerometer",
+            TopicKind::Audio => "Audio",
+        }
+    }
+}
+
+/// MQTT主题路由：订阅topic，并按kind解析收到的消息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TopicRoute {
+    pub topic: String,
+    pub kind: TopicKind,
+}
+
+impl TopicRoute {
+    pub fn new(topic: &str, kind: TopicKind) -> Self {
+        Self { topic: topic.to_string(), kind }
+    }
+}
+
+/// 旧版本配置中的MQTT主题表
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct LegacyMqttTopics {
     pub accelerometer: String,
     pub audio: String,
 }
 
+impl LegacyMqttTopics {
+    /// 旧版本写入的默认值；旧客户端实际固定订阅 sensors/audio，从未使用过这两个主题
+    fn is_unused_default(&self) -> bool {
+        self.accelerometer == "sensor/accelerometer" && self.audio == "sensor/audio"
+    }
+}
+
+impl MqttConfig {
+    /// 把旧的 [mqtt.topics] 表转换为路由表；文件中同时配置了routes时以routes为准
+    fn migrate_legacy_topics(&mut self) {
+        let Some(topics) = self.legacy_topics.take() else {
+            return;
+        };
+        if self.routes != MqttConfig::default().routes || topics.is_unused_default() {
+            return;
+        }
+
+        info!("Migrating [mqtt.topics] to mqtt.routes: {} (accelerometer), {} (audio)", topics.accelerometer, topics.audio);
+        self.routes = vec![
+            TopicRoute::new(&topics.accelerometer, TopicKind::Accelerometer),
+            TopicRoute::new(&topics.audio, TopicKind::Audio),
+        ];
+    }
+}
+
 /// 绘图配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct PlotConfig {
     pub window_duration_seconds: f64,  // 统一的窗口长度，所有传感器共用
+    pub save_window_duration_seconds: f64,  // 保存窗口长度，可与显示窗口不同
     pub plot_height: f32,
     pub show_axes: bool,
+    pub show_grid: bool,  // 是否绘制网格线
+    pub background_color: Option<[u8; 3]>,  // 绘图背景色，未设置时使用主题默认
     pub allow_drag: bool,
     pub allow_zoom: bool,
     pub show_gyroscope: bool,  // 是否显示陀螺仪数据
+    pub show_temperature: bool,  // 是否显示芯片温度（设备发送时）
+    // 显示抽样：波形图每N个点只绘制1个，保存的数据不受影响，1表示不抽样
+    pub display_decimation: usize,
+    pub tick_count_x: usize,  // x轴刻度数量
+    pub tick_count_y: usize,  // y轴刻度数量
+    pub y_range: YRangeConfig,  // 各类信号的y轴范围模式
+    pub line_width: LineWidthConfig,  // 各类信号的线宽
+    pub y_padding: YPaddingConfig,  // 自动y轴范围的留白
+    pub show_peaks: bool,  // 是否在传感器波形上标出峰值
+    pub peak_threshold: f64,  // 峰值检测的最小高度
+    pub spectrogram_colormap: SpectrogramColormap,  // 频谱图色表
+    pub audio_display_mode: AudioDisplayMode,  // 实时音频的显示方式
+    pub acc_unit: AxisUnit,  // 加速度y轴标签单位
+    pub gyro_unit: AxisUnit,  // 陀螺仪y轴标签单位
+    pub acc_scale: Option<f64>,  // 传感器上报原始LSB时，换算到acc_unit的系数
+    pub gyro_scale: Option<f64>,  // 传感器上报原始LSB时，换算到gyro_unit的系数
     pub colors: PlotColors,
 }
 
+/// 频谱图色表
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpectrogramColormap {
+    Viridis,
+    Inferno,
+    Grayscale,
+}
+
+/// 实时音频的显示方式：原始波形点数多、绘制慢，长窗口时可改用RMS包络或频谱图
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AudioDisplayMode {
+    Waveform,
+    RmsEnvelope,
+    Spectrogram,
+}
+
+impl AudioDisplayMode {
+    pub const ALL: [AudioDisplayMode; 3] = [
+        AudioDisplayMode::Waveform,
+        AudioDisplayMode::RmsEnvelope,
+        AudioDisplayMode::Spectrogram,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AudioDisplayMode::Waveform => "Waveform",
+            AudioDisplayMode::RmsEnvelope => "RMS envelope",
+            AudioDisplayMode::Spectrogram => "Spectrogram",
+        }
+    }
+}
+
+/// y轴刻度标签的单位
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AxisUnit {
+    Raw,
+    G,
+    MetersPerSecSq,
+    RadPerSec,
+    DegPerSec,
+}
+
+impl AxisUnit {
+    pub const ALL: [AxisUnit; 5] = [
+        AxisUnit::Raw,
+        AxisUnit::G,
+        AxisUnit::MetersPerSecSq,
+        AxisUnit::RadPerSec,
+        AxisUnit::DegPerSec,
+    ];
+
+    /// 追加在刻度数值后的单位后缀，Raw不加后缀
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            AxisUnit::Raw => "",
+            AxisUnit::G => "g",
+            AxisUnit::MetersPerSecSq => "m/s²",
+            AxisUnit::RadPerSec => "rad/s",
+            AxisUnit::DegPerSec => "°/s",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AxisUnit::Raw => "Raw",
+            _ => self.suffix(),
+        }
+    }
+}
+
+/// y轴范围模式
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum YRangeMode {
+    Auto,                          // 按当前数据自动缩放
+    Locked,                        // 自动缩放，但范围只扩大不缩小
+    Fixed { min: f64, max: f64 },  // 用户指定的固定范围
+}
+
+impl YRangeMode {
+    /// 固定模式下返回用户指定的范围
+    pub fn fixed_bounds(&self) -> Option<(f64, f64)> {
+        match *self {
+            YRangeMode::Fixed { min, max } => Some((min, max)),
+            _ => None,
+        }
+    }
+}
+
+/// 各类信号的y轴范围配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct YRangeConfig {
+    pub accelerometer: YRangeMode,
+    pub gyroscope: YRangeMode,
+    pub audio: YRangeMode,
+}
+
+/// 自动y轴范围的留白（实时与历史波形共用）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct YPaddingConfig {
+    pub fraction: f64,   // 上下各留出数据范围的比例
+    pub min_range: f64,  // 数据范围的下限，避免近乎恒定的信号被放大成噪声
+}
+
+impl YPaddingConfig {
+    /// 为数据的最小/最大值加上留白，得到自动模式下的y轴范围
+    pub fn apply(&self, y_min: f64, y_max: f64) -> (f64, f64) {
+        let range = (y_max - y_min).max(self.min_range);
+        (y_min - range * self.fraction, y_max + range * self.fraction)
+    }
+}
+
+/// 各类信号的波形线宽（实时与历史波形共用）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LineWidthConfig {
+    pub accelerometer: f32,
+    pub gyroscope: f32,
+    pub audio: f32,
+}
+
 /// 绘图颜色配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct PlotColors {
     pub x_axis: [u8; 3],
     pub y_axis: [u8; 3],
@@ -80,20 +432,175 @@ pub struct PlotColors {
 
 /// 校准配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct CalibrationConfig {
     pub duration_seconds: f64,
     pub min_samples: usize,
     pub initial_sample_rate: usize,
     pub auto_start: bool,
+    pub buffer_audio: bool,  // 校准期间缓存音频，校准完成后并入第一个保存窗口；false则丢弃
+    pub discard_initial_samples: usize,  // 开始采集后丢弃的前N个加速度样本（及其时间范围内的音频），0表示不丢弃
+    pub max_retries: u32,  // 采样率不合理时最多重新校准的次数，之后退回initial_sample_rate
+    // 各轴零偏，只在实时波形显示时减去，数据库中保存的是原始值
+    pub axis_offsets: AxisOffsets,
+}
+
+/// 加速度计/陀螺仪各轴的零偏
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AxisOffsets {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub gx: f64,
+    pub gy: f64,
+    pub gz: f64,
+}
+
+impl AxisOffsets {
+    /// 取静止状态下各轴读数的均值作为零偏；均值绝对值最大的加速度轴视为重力方向，不修正
+    pub fn from_still_samples(samples: &[crate::types::DataPoint]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        let count = samples.len() as f64;
+        let mean = |axis: fn(&crate::types::DataPoint) -> f64| samples.iter().map(axis).sum::<f64>() / count;
+        let mut acc = [mean(|p| p.x), mean(|p| p.y), mean(|p| p.z)];
+        if let Some(gravity_axis) = (0..3).max_by(|&a, &b| acc[a].abs().total_cmp(&acc[b].abs())) {
+            acc[gravity_axis] = 0.0;
+        }
+
+        Some(Self {
+            x: acc[0],
+            y: acc[1],
+            z: acc[2],
+            gx: mean(|p| p.gx),
+            gy: mean(|p| p.gy),
+            gz: mean(|p| p.gz),
+        })
+    }
+
+    pub fn is_zero(&self) -> bool {
+        *self == Self::default()
+    }
 }
 
 /// 通道配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ChannelConfig {
     pub data_channel_capacity: usize,
     pub audio_channel_capacity: usize,
     pub db_task_channel_capacity: usize,
     pub save_result_channel_capacity: usize,
+    pub overflow_strategy: OverflowStrategy,  // 数据通道满时的处理策略
+    pub db_queue_full_policy: DbQueueFullPolicy,  // 数据库任务队列满时保存任务的处理方式
+    pub db_queue_block_timeout_ms: u64,  // Block策略下最多等待队列空出的时间
+}
+
+/// CSV导出配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportConfig {
+    pub csv_delimiter: char,  // 字段分隔符，欧洲地区的工具通常使用分号
+    pub csv_quote_strings: bool,  // 是否给字符串字段（表头）加双引号
+    pub csv_metadata_header: bool,  // 是否在CSV开头写入以#开头的session元数据注释行
+    pub auto_export_on_exit: bool,  // 正常退出时自动导出所有未导出的session
+}
+
+/// 文本阅读器配置（旧配置文件的该节中缺少的字段使用默认值）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TextReaderConfig {
+    pub last_file_path: Option<PathBuf>,  // 上次打开的文本文件，启动时自动加载
+    pub font_size: f32,  // 当前行的字号，上下文行按比例缩小
+    pub text_color: Option<[u8; 3]>,  // 当前行的文字颜色，未设置时跟随主题
+}
+
+/// 音频回放配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioConfig {
+    pub preferred_output_rate: Option<u32>,  // 回放前将音频线性重采样到该采样率（Hz），未设置时使用输出设备的采样率
+}
+
+/// 采集身份配置：启动时优先使用上次用过的用户名/场景，没有时使用默认值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionConfig {
+    pub default_username: String,
+    pub default_scenario: String,  // 场景输入框清空时也回退到该值
+    pub last_username: Option<String>,  // 上次使用的用户名，退出时记录
+    pub last_scenario: Option<String>,
+}
+
+impl SessionConfig {
+    /// 启动时使用的用户名
+    pub fn initial_username(&self) -> String {
+        self.last_username.clone().unwrap_or_else(|| self.default_username.clone())
+    }
+
+    /// 启动时使用的场景
+    pub fn initial_scenario(&self) -> String {
+        self.last_scenario
+            .clone()
+            .filter(|scenario| !scenario.is_empty())
+            .unwrap_or_else(|| self.default_scenario.clone())
+    }
+}
+
+/// 通道溢出策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OverflowStrategy {
+    DropOldest,  // 丢弃通道中最旧的数据，保证显示最新数据
+    DropNewest,  // 丢弃新到达的数据，保持已排队数据不变
+    Block,       // 阻塞等待（带超时），超时后丢弃新数据
+}
+
+/// 音频块的存储格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AudioStorageFormat {
+    Pcm16,  // 原始16位PCM
+    Flac,   // FLAC无损压缩
+}
+
+impl AudioStorageFormat {
+    pub const ALL: [AudioStorageFormat; 2] = [AudioStorageFormat::Pcm16, AudioStorageFormat::Flac];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AudioStorageFormat::Pcm16 => "PCM 16-bit (uncompressed)",
+            AudioStorageFormat::Flac => "FLAC (lossless)",
+        }
+    }
+}
+
+/// 数据库任务队列满时保存任务的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DbQueueFullPolicy {
+    Drop,             // 丢弃本次保存
+    Block,            // 短暂阻塞等待队列空出（GUI线程上，最多MAX_BLOCK_TIMEOUT_MS），超时后丢弃
+    PauseCollection,  // 暂存本次保存并暂停采集，积压处理完后自动恢复
+}
+
+impl DbQueueFullPolicy {
+    /// Block策略的等待在GUI线程上进行，超过几十毫秒就会造成明显卡顿
+    pub const MAX_BLOCK_TIMEOUT_MS: u64 = 50;
+
+    pub const ALL: [DbQueueFullPolicy; 3] = [
+        DbQueueFullPolicy::Drop,
+        DbQueueFullPolicy::Block,
+        DbQueueFullPolicy::PauseCollection,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DbQueueFullPolicy::Drop => "Drop the window",
+            DbQueueFullPolicy::Block => "Block briefly",
+            DbQueueFullPolicy::PauseCollection => "Pause collection",
+        }
+    }
 }
 
 impl Default for AppConfig {
@@ -105,6 +612,22 @@ impl Default for AppConfig {
             plot: PlotConfig::default(),
             calibration: CalibrationConfig::default(),
             channels: ChannelConfig::default(),
+            export: ExportConfig::default(),
+            scenarios: BTreeMap::new(),
+            text_reader: TextReaderConfig::default(),
+            audio: AudioConfig::default(),
+            session: SessionConfig::default(),
+        }
+    }
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            default_username: "test".to_string(),
+            default_scenario: "standard".to_string(),
+            last_username: None,
+            last_scenario: None,
         }
     }
 }
@@ -127,9 +650,12 @@ impl Default for WindowConfig {
 impl Default for DatabaseConfig {
     fn default() -> Self {
         Self {
-            path: "data/sensor_data.db".to_string(),
+            path: crate::database::manager::DEFAULT_DATABASE_PATH.to_string(),
             channel_capacity: 100,
             auto_create_dir: true,
+            max_acc_rows_per_session: None,
+            save_on_identity_change: true,
+            audio_storage_format: AudioStorageFormat::Pcm16,
         }
     }
 }
@@ -140,18 +666,20 @@ impl Default for MqttConfig {
             broker: "localhost".to_string(),
             port: 1883,
             client_id: "sensehub_client".to_string(),
-            topics: MqttTopics::default(),
+            // 与Android客户端发布的主题一致
+            routes: vec![
+                TopicRoute::new("sensors", TopicKind::Accelerometer),
+                TopicRoute::new("audio", TopicKind::Audio),
+            ],
+            legacy_topics: None,
             qos: 1,
             keep_alive: 60,
-        }
-    }
-}
-
-impl Default for MqttTopics {
-    fn default() -> Self {
-        Self {
-            accelerometer: "sensor/accelerometer".to_string(),
-            audio: "sensor/audio".to_string(),
+            ingest_decimation: 1,
+            auto_stop_after_silence_secs: 0,
+            username: None,
+            password: None,
+            timestamp_source: TimestampSource::Device,
+            reconnect_strategy: ReconnectStrategy::ExponentialBackoff,
         }
     }
 }
@@ -160,16 +688,63 @@ impl Default for PlotConfig {
     fn default() -> Self {
         Self {
             window_duration_seconds: 10.0,  // 统一的窗口长度，所有传感器共用
+            save_window_duration_seconds: 10.0,  // 默认与显示窗口一致
             plot_height: 150.0,
             show_axes: false,
+            show_grid: false,
+            background_color: None,
             allow_drag: false,
             allow_zoom: false,
             show_gyroscope: true,  // 默认显示陀螺仪数据
+            show_temperature: false,
+            display_decimation: 1,
+            tick_count_x: 5,
+            tick_count_y: 4,
+            y_range: YRangeConfig::default(),
+            line_width: LineWidthConfig::default(),
+            y_padding: YPaddingConfig::default(),
+            show_peaks: false,
+            peak_threshold: 15.0,  // 约1.5g，足以区分足跟着地冲击
+            spectrogram_colormap: SpectrogramColormap::Viridis,
+            audio_display_mode: AudioDisplayMode::Waveform,
+            acc_unit: AxisUnit::Raw,
+            gyro_unit: AxisUnit::Raw,
+            acc_scale: None,
+            gyro_scale: None,
             colors: PlotColors::default(),
         }
     }
 }
 
+impl Default for YRangeConfig {
+    fn default() -> Self {
+        Self {
+            accelerometer: YRangeMode::Auto,
+            gyroscope: YRangeMode::Auto,
+            audio: YRangeMode::Auto,
+        }
+    }
+}
+
+impl Default for YPaddingConfig {
+    fn default() -> Self {
+        Self {
+            fraction: 0.05,
+            min_range: 0.1,
+        }
+    }
+}
+
+impl Default for LineWidthConfig {
+    fn default() -> Self {
+        Self {
+            accelerometer: 1.0,
+            gyroscope: 1.0,
+            audio: 1.0,
+        }
+    }
+}
+
 impl Default for PlotColors {
     fn default() -> Self {
         Self {
@@ -191,6 +766,10 @@ impl Default for CalibrationConfig {
             min_samples: 2,
             initial_sample_rate: 393,
             auto_start: true,
+            buffer_audio: false,
+            discard_initial_samples: 0,
+            max_retries: 3,
+            axis_offsets: AxisOffsets::default(),
         }
     }
 }
@@ -202,6 +781,30 @@ impl Default for ChannelConfig {
             audio_channel_capacity: 100000,
             db_task_channel_capacity: 100,
             save_result_channel_capacity: 100,
+            overflow_strategy: OverflowStrategy::Block,
+            db_queue_full_policy: DbQueueFullPolicy::Drop,
+            db_queue_block_timeout_ms: 20,
+        }
+    }
+}
+
+impl Default for TextReaderConfig {
+    fn default() -> Self {
+        Self {
+            last_file_path: Some(PathBuf::from("documents/chinese.txt")),
+            font_size: 24.0,
+            text_color: None,
+        }
+    }
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        Self {
+            csv_delimiter: ',',
+            csv_quote_strings: false,
+            csv_metadata_header: false,
+            auto_export_on_exit: false,
         }
     }
 }
@@ -212,13 +815,26 @@ impl AppConfig {
         let content = std::fs::read_to_string(path)
             .map_err(|e| ConfigError::IoError(e))?;
 
-        let config: AppConfig = toml::from_str(&content)
+        let mut config: AppConfig = toml::from_str(&content)
             .map_err(|e| ConfigError::ParseError(e))?;
+        config.migrate_legacy_fields();
 
         config.validate()?;
         Ok(config)
     }
 
+    /// 转换旧版本配置中已被替代的字段
+    fn migrate_legacy_fields(&mut self) {
+        self.mqtt.migrate_legacy_topics();
+
+        // 旧版本允许最多5000ms的阻塞等待
+        if self.channels.db_queue_block_timeout_ms > DbQueueFullPolicy::MAX_BLOCK_TIMEOUT_MS {
+            info!("Lowering channels.db_queue_block_timeout_ms from {} to {} ms",
+                  self.channels.db_queue_block_timeout_ms, DbQueueFullPolicy::MAX_BLOCK_TIMEOUT_MS);
+            self.channels.db_queue_block_timeout_ms = DbQueueFullPolicy::MAX_BLOCK_TIMEOUT_MS;
+        }
+    }
+
     /// 保存配置到文件
     pub fn save_to_file<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), ConfigError> {
         let content = toml::to_string_pretty(self)
@@ -236,6 +852,64 @@ impl AppConfig {
             return Err(ConfigError::ValidationError("Window dimensions must be positive".to_string()));
         }
 
+        if self.plot.window_duration_seconds <= 0.0 || self.plot.save_window_duration_seconds <= 0.0 {
+            return Err(ConfigError::ValidationError("Plot and save window durations must be positive".to_string()));
+        }
+
+        if self.mqtt.broker.trim().is_empty() {
+            return Err(ConfigError::ValidationError("MQTT broker must not be empty".to_string()));
+        }
+
+        validate_topic_routes(&self.mqtt.routes)?;
+
+        if let ReconnectStrategy::Fixed(secs) = self.mqtt.reconnect_strategy {
+            if !(1..=3600).contains(&secs) {
+                return Err(ConfigError::ValidationError("Fixed MQTT reconnect delay must be between 1 and 3600 seconds".to_string()));
+            }
+        }
+
+        if self.mqtt.ingest_decimation == 0 {
+            return Err(ConfigError::ValidationError("Ingest decimation must be at least 1".to_string()));
+        }
+
+        if self.plot.display_decimation == 0 {
+            return Err(ConfigError::ValidationError("Display decimation must be at least 1".to_string()));
+        }
+
+        if self.plot.tick_count_x < 2 || self.plot.tick_count_y < 2 {
+            return Err(ConfigError::ValidationError("Plot tick counts must be at least 2".to_string()));
+        }
+
+        let y_ranges = &self.plot.y_range;
+        for mode in [y_ranges.accelerometer, y_ranges.gyroscope, y_ranges.audio] {
+            if let Some((min, max)) = mode.fixed_bounds() {
+                if min >= max {
+                    return Err(ConfigError::ValidationError("Fixed y-range min must be less than max".to_string()));
+                }
+            }
+        }
+
+        let line_widths = &self.plot.line_width;
+        if [line_widths.accelerometer, line_widths.gyroscope, line_widths.audio].iter().any(|w| !w.is_finite() || *w <= 0.0) {
+            return Err(ConfigError::ValidationError("Plot line widths must be positive".to_string()));
+        }
+
+        let y_padding = &self.plot.y_padding;
+        if !(0.0..=1.0).contains(&y_padding.fraction) {
+            return Err(ConfigError::ValidationError("Y-range padding must be between 0 and 1".to_string()));
+        }
+        if !y_padding.min_range.is_finite() || y_padding.min_range <= 0.0 {
+            return Err(ConfigError::ValidationError("Minimum y-range must be positive".to_string()));
+        }
+
+        if [self.plot.acc_scale, self.plot.gyro_scale].iter().flatten().any(|scale| !scale.is_finite() || *scale == 0.0) {
+            return Err(ConfigError::ValidationError("Axis scale factors must be finite and non-zero".to_string()));
+        }
+
+        if !self.plot.peak_threshold.is_finite() {
+            return Err(ConfigError::ValidationError("Peak threshold must be a finite number".to_string()));
+        }
+
         if self.calibration.duration_seconds <= 0.0 {
             return Err(ConfigError::ValidationError("Calibration duration must be positive".to_string()));
         }
@@ -244,10 +918,53 @@ impl AppConfig {
             return Err(ConfigError::ValidationError("Minimum samples must be at least 2".to_string()));
         }
 
+        if self.database.max_acc_rows_per_session == Some(0) {
+            return Err(ConfigError::ValidationError("Max accelerometer rows per session must be positive".to_string()));
+        }
+
         if self.channels.data_channel_capacity == 0 {
             return Err(ConfigError::ValidationError("Data channel capacity must be positive".to_string()));
         }
 
+        if self.channels.db_queue_full_policy == DbQueueFullPolicy::Block
+            && !(1..=DbQueueFullPolicy::MAX_BLOCK_TIMEOUT_MS).contains(&self.channels.db_queue_block_timeout_ms)
+        {
+            return Err(ConfigError::ValidationError(format!(
+                "DB queue block timeout must be between 1 and {} ms", DbQueueFullPolicy::MAX_BLOCK_TIMEOUT_MS
+            )));
+        }
+
+        if !(8.0..=96.0).contains(&self.text_reader.font_size) {
+            return Err(ConfigError::ValidationError("Text reader font size must be between 8 and 96".to_string()));
+        }
+
+        let offsets = self.calibration.axis_offsets;
+        if [offsets.x, offsets.y, offsets.z, offsets.gx, offsets.gy, offsets.gz].iter().any(|v| !v.is_finite()) {
+            return Err(ConfigError::ValidationError("Calibration axis offsets must be finite numbers".to_string()));
+        }
+
+        if self.audio.preferred_output_rate.is_some_and(|rate| !(8000..=192000).contains(&rate)) {
+            return Err(ConfigError::ValidationError("Preferred audio output rate must be between 8000 and 192000 Hz".to_string()));
+        }
+
+        if self.session.default_scenario.trim().is_empty() {
+            return Err(ConfigError::ValidationError("Default scenario must not be empty".to_string()));
+        }
+
+        let delimiter = self.export.csv_delimiter;
+        if !delimiter.is_ascii() || matches!(delimiter, '"' | '\n' | '\r') || delimiter.is_ascii_alphanumeric() {
+            return Err(ConfigError::ValidationError("CSV delimiter must be an ASCII punctuation or whitespace character".to_string()));
+        }
+
+        // 每个场景覆盖后的配置也必须有效
+        for (name, overrides) in &self.scenarios {
+            let mut scenario_config = self.clone();
+            scenario_config.scenarios.clear();
+            overrides.apply(&mut scenario_config);
+            scenario_config.validate()
+                .map_err(|e| ConfigError::ValidationError(format!("Scenario '{}': {}", name, e)))?;
+        }
+
         Ok(())
     }
 
@@ -262,6 +979,49 @@ impl AppConfig {
     }
 }
 
+/// MQTT主题名的最大字节数（协议中字符串长度用16位表示）
+const MQTT_TOPIC_MAX_BYTES: usize = 65535;
+
+/// 检查主题名是否为合法的MQTT发布主题：消息按主题名精确匹配分发，不能含通配符
+fn validate_mqtt_topic(topic: &str) -> Result<(), ConfigError> {
+    if topic.is_empty() {
+        return Err(ConfigError::ValidationError("MQTT topic must not be empty".to_string()));
+    }
+    if topic.len() > MQTT_TOPIC_MAX_BYTES {
+        return Err(ConfigError::ValidationError(format!(
+            "MQTT topic is {} bytes long, the maximum is {}", topic.len(), MQTT_TOPIC_MAX_BYTES
+        )));
+    }
+    if topic.contains('\0') {
+        return Err(ConfigError::ValidationError(format!("MQTT topic '{}' must not contain null characters", topic.escape_debug())));
+    }
+    if topic.contains(['+', '#']) {
+        return Err(ConfigError::ValidationError(format!("MQTT topic '{}' must not contain '+' or '#' wildcards", topic)));
+    }
+    Ok(())
+}
+
+/// 检查主题路由：每个主题合法且不重复，至少各有一个加速度计和音频主题
+fn validate_topic_routes(routes: &[TopicRoute]) -> Result<(), ConfigError> {
+    let mut seen = std::collections::HashSet::new();
+    for route in routes {
+        if route.topic.trim().is_empty() {
+            return Err(ConfigError::ValidationError("MQTT topics must not be empty".to_string()));
+        }
+        validate_mqtt_topic(&route.topic)?;
+        if !seen.insert(route.topic.as_str()) {
+            return Err(ConfigError::ValidationError(format!("MQTT topic '{}' is routed more than once", route.topic)));
+        }
+    }
+
+    for kind in TopicKind::ALL {
+        if !routes.iter().any(|route| route.kind == kind) {
+            return Err(ConfigError::ValidationError(format!("At least one {} MQTT topic must be configured", kind.label().to_lowercase())));
+        }
+    }
+    Ok(())
+}
+
 /// 配置错误类型
 #[derive(Debug, thiserror::Error)]
 pub enum ConfigError {
@@ -273,30 +1033,72 @@ pub enum ConfigError {
     SerializeError(toml::ser::Error),
     #[error("Validation error: {0}")]
     ValidationError(String),
+    #[error("Config file could not be loaded ({0}); fix or remove it before saving")]
+    LoadFailed(String),
 }
 
 /// 配置管理器
 pub struct ConfigManager {
     config: AppConfig,
     config_path: Option<PathBuf>,
+    active_scenario: Option<String>,
+    scenario_restore: ScenarioOverrides, // 当前场景覆盖前的原值，切换场景或保存时用于还原
+    scenario_applied: ScenarioOverrides, // 当前场景写入的覆盖值，用于识别之后在设置界面中修改过的字段
+    load_error: Option<String>, // 配置文件存在但加载失败时的错误，此时禁止保存以免覆盖用户的文件
+    env_restore: EnvOverrides, // 被环境变量覆盖的字段在配置文件中的原值，保存时还原
 }
 
 impl ConfigManager {
     /// 创建配置管理器
     pub fn new() -> Self {
+        Self::from_config(AppConfig::default(), None)
+    }
+
+    fn from_config(mut config: AppConfig, config_path: Option<PathBuf>) -> Self {
+        let env_restore = apply_env_overrides(&mut config);
         Self {
-            config: AppConfig::default(),
-            config_path: None,
+            config,
+            config_path,
+            active_scenario: None,
+            scenario_restore: ScenarioOverrides::default(),
+            scenario_applied: ScenarioOverrides::default(),
+            load_error: None,
+            env_restore,
         }
     }
 
     /// 从文件加载配置
     pub fn load_from_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self, ConfigError> {
         let config = AppConfig::load_from_file(&path)?;
-        Ok(Self {
-            config,
-            config_path: Some(path.as_ref().to_path_buf()),
-        })
+        Ok(Self::from_config(config, Some(path.as_ref().to_path_buf())))
+    }
+
+    /// 从文件加载配置，文件不存在时使用默认配置（保存时写入该文件）
+    /// 文件无效时同样使用默认配置运行，但记录错误并拒绝保存，避免用默认值覆盖用户的文件
+    pub fn load_or_default<P: AsRef<std::path::Path>>(path: P) -> Self {
+        let path = path.as_ref();
+        if !path.exists() {
+            info!("Config file {} not found, using default configuration", path.display());
+            return Self::from_config(AppConfig::default(), Some(path.to_path_buf()));
+        }
+
+        match Self::load_from_file(path) {
+            Ok(manager) => {
+                info!("Loaded configuration from {}", path.display());
+                manager
+            }
+            Err(e) => {
+                warn!("Failed to load config file {}: {}, using default configuration without saving", path.display(), e);
+                let mut manager = Self::from_config(AppConfig::default(), Some(path.to_path_buf()));
+                manager.load_error = Some(format!("{}: {}", path.display(), e));
+                manager
+            }
+        }
+    }
+
+    /// 配置文件加载失败时的错误信息
+    pub fn load_error(&self) -> Option<&str> {
+        self.load_error.as_deref()
     }
 
     /// 获取当前配置
@@ -309,17 +1111,103 @@ impl ConfigManager {
         &mut self.config
     }
 
+    /// 切换到指定场景：先还原上一个场景的覆盖，再应用该场景的 [scenarios.<name>] 覆盖
+    /// 返回配置是否发生了变化
+    pub fn apply_scenario(&mut self, scenario: &str) -> bool {
+        if self.active_scenario.as_deref() == Some(scenario) {
+            return false;
+        }
+
+        let restore = std::mem::take(&mut self.scenario_restore);
+        let applied = std::mem::take(&mut self.scenario_applied);
+        let mut changed = !restore.is_empty();
+        restore.restore_unless_edited(&applied, &mut self.config);
+
+        if let Some(overrides) = self.config.scenarios.get(scenario).cloned() {
+            info!("Applying configuration overrides for scenario '{}'", scenario);
+            self.scenario_restore = overrides.apply(&mut self.config);
+            changed |= !overrides.is_empty();
+            self.scenario_applied = overrides;
+        }
+
+        self.active_scenario = Some(scenario.to_string());
+        changed
+    }
+
+    /// 去掉场景覆盖和环境变量覆盖后的基础配置，保存时写入文件
+    fn base_config(&self) -> AppConfig {
+        let mut config = self.config.clone();
+        self.scenario_restore.restore_unless_edited(&self.scenario_applied, &mut config);
+        self.env_restore.restore(&mut config);
+        config
+    }
+
     /// 保存配置
     pub fn save(&self) -> Result<(), ConfigError> {
+        self.ensure_loaded()?;
         if let Some(path) = &self.config_path {
-            self.config.save_to_file(path)?;
+            self.base_config().save_to_file(path)?;
         }
         Ok(())
     }
 
+    /// 只把 [session] 节写回配置文件，文件中的其他内容保持不变
+    /// 退出时记住用户名/场景用，不会把设置界面中未保存的修改写进文件
+    pub fn save_session(&self) -> Result<(), ConfigError> {
+        let session = toml::Value::try_from(&self.config.session).map_err(ConfigError::SerializeError)?;
+        self.save_value(&["session"], session)
+    }
+
+    /// 只把 [calibration.axis_offsets] 写回配置文件，采集中记录零偏时使用
+    pub fn save_axis_offsets(&self) -> Result<(), ConfigError> {
+        let offsets = toml::Value::try_from(self.config.calibration.axis_offsets).map_err(ConfigError::SerializeError)?;
+        self.save_value(&["calibration", "axis_offsets"], offsets)
+    }
+
+    /// 把配置文件中key_path处的值替换为value，其余内容保持不变；值未变化时不写文件
+    fn save_value(&self, key_path: &[&str], value: toml::Value) -> Result<(), ConfigError> {
+        self.ensure_loaded()?;
+        let Some(path) = &self.config_path else {
+            return Ok(());
+        };
+        let Some((key, parents)) = key_path.split_last() else {
+            return Ok(());
+        };
+
+        let mut table = match std::fs::read_to_string(path) {
+            Ok(content) => content.parse::<toml::Table>().map_err(ConfigError::ParseError)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => toml::Table::new(),
+            Err(e) => return Err(ConfigError::IoError(e)),
+        };
+
+        let mut section = &mut table;
+        for parent in parents {
+            let entry = section.entry(parent.to_string()).or_insert_with(|| toml::Value::Table(toml::Table::new()));
+            if !entry.is_table() {
+                *entry = toml::Value::Table(toml::Table::new());
+            }
+            section = entry.as_table_mut().expect("entry was just made a table");
+        }
+        if section.get(*key) == Some(&value) {
+            return Ok(());
+        }
+
+        section.insert(key.to_string(), value);
+        let content = toml::to_string_pretty(&table).map_err(ConfigError::SerializeError)?;
+        std::fs::write(path, content).map_err(ConfigError::IoError)
+    }
+
     /// 保存配置到指定文件
     pub fn save_to_file<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), ConfigError> {
-        self.config.save_to_file(path)
+        self.ensure_loaded()?;
+        self.base_config().save_to_file(path)
+    }
+
+    fn ensure_loaded(&self) -> Result<(), ConfigError> {
+        match &self.load_error {
+            Some(error) => Err(ConfigError::LoadFailed(error.clone())),
+            None => Ok(()),
+        }
     }
 }
 
@@ -328,3 +1216,233 @@ impl Default for ConfigManager {
         Self::new()
     }
 }
+
+/// 被环境变量覆盖的字段：(配置文件中的值, 环境变量的值)
+#[derive(Debug, Default)]
+pub struct EnvOverrides {
+    mqtt_broker: Option<(String, String)>,
+    mqtt_port: Option<(u16, u16)>,
+    database_path: Option<(String, String)>,
+}
+
+impl EnvOverrides {
+    /// 还原仍为环境变量值的字段，在设置界面中修改过的字段保留修改后的值
+    fn restore(&self, config: &mut AppConfig) {
+        fn restore_field<T: Clone + PartialEq>(target: &mut T, entry: &Option<(T, T)>) {
+            if let Some((file_value, env_value)) = entry {
+                if target == env_value {
+                    *target = file_value.clone();
+                }
+            }
+        }
+
+        restore_field(&mut config.mqtt.broker, &self.mqtt_broker);
+        restore_field(&mut config.mqtt.port, &self.mqtt_port);
+        restore_field(&mut config.database.path, &self.database_path);
+        // 凭据只来自环境变量
+        config.mqtt.username = None;
+        config.mqtt.password = None;
+    }
+}
+
+/// 用环境变量覆盖配置文件中的值（同时加载 .env 文件），返回被覆盖字段的原值
+/// 旧的 MQTT_HOST/MQTT_PORT/MQTT_USER/MQTT_PASS 仍然支持，但优先级低于 SENSEHUB_* 变量
+pub fn apply_env_overrides(config: &mut AppConfig) -> EnvOverrides {
+    dotenv::dotenv().ok();
+    let mut overrides = EnvOverrides::default();
+
+    if let Some((name, broker)) = env_override("SENSEHUB_MQTT_BROKER", "MQTT_HOST") {
+        info!("{} overrides mqtt.broker: {}", name, broker);
+        let file_value = std::mem::replace(&mut config.mqtt.broker, broker.clone());
+        overrides.mqtt_broker = Some((file_value, broker));
+    }
+
+    if let Some((name, port)) = env_override("SENSEHUB_MQTT_PORT", "MQTT_PORT") {
+        match port.parse::<u16>() {
+            Ok(port) => {
+                info!("{} overrides mqtt.port: {}", name, port);
+                overrides.mqtt_port = Some((config.mqtt.port, port));
+                config.mqtt.port = port;
+            }
+            Err(_) => warn!("Ignoring {}: '{}' is not a valid port", name, port),
+        }
+    }
+
+    if let Some((name, user)) = env_override("SENSEHUB_MQTT_USER", "MQTT_USER") {
+        info!("{} overrides mqtt username: {}", name, user);
+        config.mqtt.username = Some(user);
+    }
+
+    if let Some((name, pass)) = env_override("SENSEHUB_MQTT_PASS", "MQTT_PASS") {
+        info!("{} overrides mqtt password", name);
+        config.mqtt.password = Some(pass);
+    }
+
+    if let Some(path) = env_value("SENSEHUB_DB_PATH") {
+        info!("SENSEHUB_DB_PATH overrides database.path: {}", path);
+        let file_value = std::mem::replace(&mut config.database.path, path.clone());
+        overrides.database_path = Some((file_value, path));
+    }
+
+    // 日志级别在 logger::init_logger 中生效（RUST_LOG 优先），这里只记录覆盖情况
+    if let Some(level) = env_value(crate::logger::LOG_LEVEL_ENV) {
+        if std::env::var_os("RUST_LOG").is_some() {
+            warn!("Ignoring {}: RUST_LOG is set", crate::logger::LOG_LEVEL_ENV);
+        } else if level.trim().parse::<log::LevelFilter>().is_ok() {
+            info!("{} overrides log level: {}", crate::logger::LOG_LEVEL_ENV, level);
+        } else {
+            warn!("Ignoring {}: '{}' is not a valid log level", crate::logger::LOG_LEVEL_ENV, level);
+        }
+    }
+
+    overrides
+}
+
+/// 读取非空的环境变量
+fn env_value(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.trim().is_empty())
+}
+
+/// 优先读取新变量，未设置时回退到旧变量，返回实际生效的变量名和值
+fn env_override(name: &'static str, legacy_name: &'static str) -> Option<(&'static str, String)> {
+    env_value(name)
+        .map(|v| (name, v))
+        .or_else(|| env_value(legacy_name).map(|v| (legacy_name, v)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> AppConfig {
+        let mut config: AppConfig = toml::from_str(content).expect("config should parse");
+        config.migrate_legacy_fields();
+        config
+    }
+
+    #[test]
+    fn legacy_topics_table_is_migrated_to_routes() {
+        let config = parse("[mqtt]\nbroker = \"broker.local\"\n\n[mqtt.topics]\naccelerometer = \"imu\"\naudio = \"mic\"\n");
+        assert_eq!(config.mqtt.routes, vec![
+            TopicRoute::new("imu", TopicKind::Accelerometer),
+            TopicRoute::new("mic", TopicKind::Audio),
+        ]);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn unused_legacy_default_topics_keep_default_routes() {
+        let config = parse("[mqtt.topics]\naccelerometer = \"sensor/accelerometer\"\naudio = \"sensor/audio\"\n");
+        assert_eq!(config.mqtt.routes, MqttConfig::default().routes);
+    }
+
+    #[test]
+    fn explicit_routes_win_over_legacy_topics() {
+        let config = parse(
+            "[mqtt.topics]\naccelerometer = \"imu\"\naudio = \"mic\"\n\n\
+             [[mqtt.routes]]\ntopic = \"a\"\nkind = \"Accelerometer\"\n\n\
+             [[mqtt.routes]]\ntopic = \"b\"\nkind = \"Audio\"\n",
+        );
+        assert_eq!(config.mqtt.routes, vec![
+            TopicRoute::new("a", TopicKind::Accelerometer),
+            TopicRoute::new("b", TopicKind::Audio),
+        ]);
+    }
+
+    #[test]
+    fn ingest_decimation_round_trips_and_rejects_zero() {
+        let config = parse("[mqtt]\ningest_decimation = 4\n");
+        assert_eq!(config.mqtt.ingest_decimation, 4);
+        assert_eq!(config.plot.display_decimation, 1);
+        assert!(toml::to_string_pretty(&config).unwrap().contains("ingest_decimation = 4"));
+
+        let mut config = AppConfig::default();
+        config.mqtt.ingest_decimation = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn mqtt_topic_accepts_plain_topic() {
+        assert!(validate_mqtt_topic("sensors").is_ok());
+        assert!(validate_mqtt_topic("sensehub/device-1/audio").is_ok());
+    }
+
+    #[test]
+    fn mqtt_topic_rejects_single_level_wildcard() {
+        assert!(validate_mqtt_topic("sensors/+/acc").is_err());
+    }
+
+    #[test]
+    fn mqtt_topic_rejects_multi_level_wildcard() {
+        assert!(validate_mqtt_topic("sensors/#").is_err());
+    }
+
+    #[test]
+    fn mqtt_topic_rejects_null_character() {
+        assert!(validate_mqtt_topic("sen\0sors").is_err());
+    }
+
+    #[test]
+    fn mqtt_topic_length_limit_is_65535_bytes() {
+        assert!(validate_mqtt_topic(&"a".repeat(MQTT_TOPIC_MAX_BYTES)).is_ok());
+        assert!(validate_mqtt_topic(&"a".repeat(MQTT_TOPIC_MAX_BYTES + 1)).is_err());
+    }
+
+    #[test]
+    fn mqtt_topic_rejects_empty_topic() {
+        assert!(validate_mqtt_topic("").is_err());
+    }
+
+    #[test]
+    fn saved_config_does_not_contain_legacy_topics() {
+        let config = parse("[mqtt.topics]\naccelerometer = \"imu\"\naudio = \"mic\"\n");
+        let saved = toml::to_string_pretty(&config).unwrap();
+        assert!(!saved.contains("[mqtt.topics]"));
+        assert_eq!(parse(&saved).mqtt.routes, config.mqtt.routes);
+    }
+
+    #[test]
+    fn save_session_only_writes_session_section() {
+        let path = std::env::temp_dir().join(format!("sensehub_session_{}.toml", std::process::id()));
+        std::fs::write(&path, "[window]\ntitle = \"From file\"\n").unwrap();
+
+        let mut manager = ConfigManager::load_from_file(&path).unwrap();
+        manager.get_config_mut().window.title = "Unsaved edit".to_string();
+        manager.get_config_mut().session.last_username = Some("alice".to_string());
+        manager.save_session().unwrap();
+
+        let saved = AppConfig::load_from_file(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(saved.window.title, "From file");
+        assert_eq!(saved.session.last_username.as_deref(), Some("alice"));
+    }
+
+    #[test]
+    fn save_axis_offsets_only_writes_offsets() {
+        let path = std::env::temp_dir().join(format!("sensehub_offsets_{}.toml", std::process::id()));
+        std::fs::write(&path, "[calibration]\nduration_seconds = 7.0\n").unwrap();
+
+        let mut manager = ConfigManager::load_from_file(&path).unwrap();
+        manager.get_config_mut().calibration.duration_seconds = 3.0;
+        manager.get_config_mut().calibration.axis_offsets.gx = 0.25;
+        manager.save_axis_offsets().unwrap();
+
+        let saved = AppConfig::load_from_file(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(saved.calibration.duration_seconds, 7.0);
+        assert_eq!(saved.calibration.axis_offsets.gx, 0.25);
+    }
+
+    #[test]
+    fn zero_offset_capture_leaves_the_gravity_axis() {
+        let samples = [
+            crate::types::DataPoint::new(0.02, -0.01, 0.98, 0.5, 0.0, -0.5, 0),
+            crate::types::DataPoint::new(0.04, -0.03, 1.02, 0.7, 0.2, -0.3, 1),
+        ];
+        let offsets = AxisOffsets::from_still_samples(&samples).unwrap();
+
+        assert!((offsets.x - 0.03).abs() < 1e-9 && (offsets.y + 0.02).abs() < 1e-9);
+        assert_eq!(offsets.z, 0.0);
+        assert!((offsets.gx - 0.6).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,317 @@
+use std::io::Cursor;
+
+/// FLAC编码的音频块在format列中的取值
+pub const FLAC_FORMAT: &str = "FLAC";
+
+/// FLAC每帧的样本数
+const FLAC_BLOCK_SIZE: usize = 4096;
+/// Rice参数的最大值（4位参数，15保留为转义）
+const MAX_RICE_PARAMETER: u32 = 14;
+
+/// 按位写入的缓冲区，高位在前
+struct BitWriter {
+    bytes: Vec<u8>,
+    current: u64,
+    bit_count: u32,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self { bytes: Vec::new(), current: 0, bit_count: 0 }
+    }
+
+    fn write_bits(&mut self, value: u64, bits: u32) {
+        for i in (0..bits).rev() {
+            self.current = (self.current << 1) | ((value >> i) & 1);
+            self.bit_count += 1;
+            if self.bit_count == 8 {
+                self.bytes.push(self.current as u8);
+                self.current = 0;
+                self.bit_count = 0;
+            }
+        }
+    }
+
+    fn write_signed(&mut self, value: i64, bits: u32) {
+        self.write_bits((value as u64) & ((1u64 << bits) - 1), bits);
+    }
+
+    fn write_unary(&mut self, zeros: u64) {
+        for _ in 0..zeros {
+            self.write_bits(0, 1);
+        }
+        self.write_bits(1, 1);
+    }
+
+    /// 补零到字节边界
+    fn align(&mut self) {
+        if self.bit_count > 0 {
+            self.write_bits(0, 8 - self.bit_count);
+        }
+    }
+
+    fn into_bytes(mut self) -> Vec<u8> {
+        self.align();
+        self.bytes
+    }
+}
+
+fn crc8(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |mut crc, &byte| {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 { (crc << 1) ^ 0x07 } else { crc << 1 };
+        }
+        crc
+    })
+}
+
+fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0u16, |mut crc, &byte| {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x8005 } else { crc << 1 };
+        }
+        crc
+    })
+}
+
+/// 帧号采用FLAC的类UTF-8变长编码
+fn write_utf8_number(writer: &mut BitWriter, value: u64) {
+    if value < 0x80 {
+        writer.write_bits(value, 8);
+        return;
+    }
+
+    let continuation_bytes = match value {
+        0..=0x7FF => 1,
+        0x800..=0xFFFF => 2,
+        0x1_0000..=0x1F_FFFF => 3,
+        0x20_0000..=0x3FF_FFFF => 4,
+        _ => 5,
+    };
+    let first_prefix = (0xFF00u16 >> (continuation_bytes + 1)) as u64 & 0xFF;
+    writer.write_bits(first_prefix | (value >> (6 * continuation_bytes)), 8);
+    for i in (0..continuation_bytes).rev() {
+        writer.write_bits(0x80 | ((value >> (6 * i)) & 0x3F), 8);
+    }
+}
+
+/// 固定阶预测的残差，order为0到4
+fn fixed_residuals(samples: &[i64], order: usize) -> Vec<i64> {
+    (order..samples.len())
+        .map(|i| {
+            let s = |k: usize| samples[i - k];
+            let prediction = match order {
+                0 => 0,
+                1 => s(1),
+                2 => 2 * s(1) - s(2),
+                3 => 3 * s(1) - 3 * s(2) + s(3),
+                _ => 4 * s(1) - 6 * s(2) + 4 * s(3) - s(4),
+            };
+            samples[i] - prediction
+        })
+        .collect()
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+/// 选择使残差编码最短的Rice参数，返回 (参数, 编码位数)
+fn best_rice_parameter(residuals: &[i64]) -> (u32, u64) {
+    (0..=MAX_RICE_PARAMETER)
+        .map(|parameter| {
+            let bits = residuals
+                .iter()
+                .map(|&r| (zigzag(r) >> parameter) + 1 + parameter as u64)
+                .sum();
+            (parameter, bits)
+        })
+        .min_by_key(|&(_, bits)| bits)
+        .unwrap_or((0, 0))
+}
+
+/// 写入一个单声道子帧：在各固定阶预测中选择最短的一种，都不如原始样本时写入VERBATIM子帧
+fn write_subframe(writer: &mut BitWriter, samples: &[i64]) {
+    let verbatim_bits = samples.len() as u64 * 16;
+    let best_fixed = (0..=4usize)
+        .filter(|&order| order < samples.len())
+        .map(|order| {
+            let residuals = fixed_residuals(samples, order);
+            let (parameter, bits) = best_rice_parameter(&residuals);
+            (order, residuals, parameter, order as u64 * 16 + 10 + bits)
+        })
+        .min_by_key(|(_, _, _, bits)| *bits);
+
+    match best_fixed {
+        Some((order, residuals, parameter, bits)) if bits < verbatim_bits => {
+            writer.write_bits(0, 1);
+            writer.write_bits(0b001000 | order as u64, 6);
+            writer.write_bits(0, 1);
+            for &warmup in &samples[..order] {
+                writer.write_signed(warmup, 16);
+            }
+            // Rice编码（4位参数），分区阶数为0
+            writer.write_bits(0, 2);
+            writer.write_bits(0, 4);
+            writer.write_bits(parameter as u64, 4);
+            for residual in residuals {
+                let value = zigzag(residual);
+                writer.write_unary(value >> parameter);
+                writer.write_bits(value & ((1u64 << parameter) - 1), parameter);
+            }
+        }
+        _ => {
+            writer.write_bits(0, 1);
+            writer.write_bits(0b000001, 6);
+            writer.write_bits(0, 1);
+            for &sample in samples {
+                writer.write_signed(sample, 16);
+            }
+        }
+    }
+}
+
+/// 把单声道16位样本编码为FLAC字节流（无损）
+///
+/// TODO: 换成维护中的编码库（flacenc），调用方只依赖这个签名。目前的离线构建环境里拿不到该crate，
+/// 在此之前编码结果由下面的测试用rodio（symphonia）解码回读校验
+pub fn encode_flac(samples: &[i16], sample_rate: u32) -> Vec<u8> {
+    let mut output = Vec::new();
+    output.extend_from_slice(b"fLaC");
+
+    // STREAMINFO元数据块（同时是最后一个元数据块），MD5留空
+    let mut stream_info = BitWriter::new();
+    stream_info.write_bits(1, 1);
+    stream_info.write_bits(0, 7);
+    stream_info.write_bits(34, 24);
+    stream_info.write_bits(FLAC_BLOCK_SIZE as u64, 16);
+    stream_info.write_bits(FLAC_BLOCK_SIZE as u64, 16);
+    stream_info.write_bits(0, 24);
+    stream_info.write_bits(0, 24);
+    stream_info.write_bits(sample_rate as u64, 20);
+    stream_info.write_bits(0, 3);
+    stream_info.write_bits(15, 5);
+    stream_info.write_bits(samples.len() as u64, 36);
+    stream_info.write_bits(0, 64);
+    stream_info.write_bits(0, 64);
+    output.extend(stream_info.into_bytes());
+
+    for (frame_number, block) in samples.chunks(FLAC_BLOCK_SIZE).enumerate() {
+        let mut frame = BitWriter::new();
+        // 同步码 + 固定块大小；块大小写在头部末尾，采样率取自STREAMINFO，单声道，16位
+        frame.write_bits(0xFFF8, 16);
+        frame.write_bits(0b0111, 4);
+        frame.write_bits(0b0000, 4);
+        frame.write_bits(0b0000, 4);
+        frame.write_bits(0b100, 3);
+        frame.write_bits(0, 1);
+        write_utf8_number(&mut frame, frame_number as u64);
+        frame.write_bits(block.len() as u64 - 1, 16);
+        let header = frame.bytes.clone();
+        frame.write_bits(crc8(&header) as u64, 8);
+
+        let block: Vec<i64> = block.iter().map(|&s| s as i64).collect();
+        write_subframe(&mut frame, &block);
+
+        let mut frame_bytes = frame.into_bytes();
+        let crc = crc16(&frame_bytes);
+        frame_bytes.extend_from_slice(&crc.to_be_bytes());
+        output.extend(frame_bytes);
+    }
+
+    output
+}
+
+/// 跳过"fLaC"标记和所有元数据块，返回第一个音频帧的偏移
+fn first_frame_offset(bytes: &[u8]) -> Option<usize> {
+    if !bytes.starts_with(b"fLaC") {
+        return None;
+    }
+
+    let mut offset = 4;
+    loop {
+        let header = bytes.get(offset..offset + 4)?;
+        let is_last = header[0] & 0x80 != 0;
+        let length = u32::from_be_bytes([0, header[1], header[2], header[3]]) as usize;
+        offset += 4 + length;
+        if is_last {
+            return Some(offset);
+        }
+    }
+}
+
+/// 解码FLAC字节流为16位样本，多声道时只取交错样本中的第一个声道
+pub fn decode_flac(bytes: &[u8]) -> Result<Vec<i16>, String> {
+    // 没有音频帧的流（空音频）解码器会报错，直接返回空
+    if first_frame_offset(bytes).is_some_and(|offset| offset >= bytes.len()) {
+        return Ok(Vec::new());
+    }
+
+    let decoder = rodio::Decoder::new_flac(Cursor::new(bytes.to_vec()))
+        .map_err(|e| format!("Failed to decode FLAC audio: {}", e))?;
+    let channels = rodio::Source::channels(&decoder).max(1) as usize;
+
+    Ok(decoder
+        .step_by(channels)
+        .map(|sample| (sample * 32768.0).round().clamp(-32768.0, 32767.0) as i16)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(samples: &[i16]) {
+        let encoded = encode_flac(samples, 16000);
+        let decoded = decode_flac(&encoded).expect("FLAC stream should decode");
+        assert_eq!(decoded.len(), samples.len());
+        assert!(decoded == samples, "decoded samples differ from the input");
+    }
+
+    /// 确定性的伪随机噪声，覆盖Rice编码的大残差
+    fn noise(len: usize, seed: u32) -> Vec<i16> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (state >> 16) as i16
+            })
+            .collect()
+    }
+
+    #[test]
+    fn round_trips_empty_input() {
+        round_trip(&[]);
+    }
+
+    #[test]
+    fn round_trips_partial_block() {
+        let samples: Vec<i16> = (0..1000).map(|i| ((i as f64 * 0.05).sin() * 8000.0) as i16).collect();
+        round_trip(&samples);
+        round_trip(&samples[..1]);
+        round_trip(&samples[..5]);
+    }
+
+    #[test]
+    fn round_trips_silence() {
+        round_trip(&vec![0; FLAC_BLOCK_SIZE]);
+    }
+
+    #[test]
+    fn round_trips_full_scale_samples() {
+        let samples: Vec<i16> = (0..FLAC_BLOCK_SIZE)
+            .map(|i| if i % 2 == 0 { i16::MIN } else { i16::MAX })
+            .collect();
+        round_trip(&samples);
+        round_trip(&[i16::MIN; 300]);
+        round_trip(&[i16::MAX; 300]);
+    }
+
+    #[test]
+    fn round_trips_multiple_frames() {
+        round_trip(&noise(FLAC_BLOCK_SIZE * 3 + 17, 7));
+        round_trip(&noise(FLAC_BLOCK_SIZE * 2, 11));
+    }
+}
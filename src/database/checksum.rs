@@ -0,0 +1,75 @@
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::Path;
+
+/// 计算文件的SHA-256摘要（十六进制小写），流式读取，导出的CSV可能很大，不需要一次读入内存
+pub fn sha256_file_hex(path: impl AsRef<Path>) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// 在导出文件旁写入 `<file_stem>.sha256`，格式与 `sha256sum` 的输出一致，可直接用 `sha256sum -c` 校验
+pub fn write_sha256_sidecar(file_path: &str, file_stem: &str) -> std::io::Result<String> {
+    let digest = sha256_file_hex(file_path)?;
+    let file_name = Path::new(file_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| file_path.to_string());
+    let sidecar_path = format!("{}.sha256", file_stem);
+    std::fs::write(&sidecar_path, format!("{}  {}\n", digest, file_name))?;
+    Ok(sidecar_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 用 `<file_stem>.sha256` 校验导出文件，摘要一致时返回true（与 `sha256sum -c` 的判断一致）
+    fn verify_sha256_sidecar(file_path: &str, file_stem: &str) -> std::io::Result<bool> {
+        let sidecar = std::fs::read_to_string(format!("{}.sha256", file_stem))?;
+        let expected = sidecar.split_whitespace().next().unwrap_or_default();
+        Ok(expected.eq_ignore_ascii_case(&sha256_file_hex(file_path)?))
+    }
+
+    fn temp_stem(name: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("sensehub_checksum_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join(name).to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn digest_matches_known_vector() {
+        let stem = temp_stem("abc");
+        let path = format!("{}.csv", stem);
+        std::fs::write(&path, b"abc").unwrap();
+
+        assert_eq!(
+            sha256_file_hex(&path).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn sidecar_round_trip_and_tamper_detection() {
+        let stem = temp_stem("export");
+        let path = format!("{}.csv", stem);
+        std::fs::write(&path, "timestamp,x,y,z\n1,0.1,0.2,0.3\n").unwrap();
+
+        let sidecar_path = write_sha256_sidecar(&path, &stem).unwrap();
+        let sidecar = std::fs::read_to_string(&sidecar_path).unwrap();
+        assert!(sidecar.ends_with("  export.csv\n"));
+        assert!(verify_sha256_sidecar(&path, &stem).unwrap());
+
+        std::fs::write(&path, "timestamp,x,y,z\n1,0.1,0.2,0.4\n").unwrap();
+        assert!(!verify_sha256_sidecar(&path, &stem).unwrap());
+    }
+}
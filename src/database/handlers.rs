@@ -1,32 +1,57 @@
-use crate::database::export_session_to_csv_internal;
+use crate::database::{export_session_to_csv_internal, export_session_range_internal, export_sessions_comparison_internal, update_export_manifest};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use crossbeam_channel::{Receiver, Sender};
 use log::{info, error, warn};
 
-use crate::types::{DatabaseTask, ExportType, ExportResult, SaveResult, DataPoint, AudioData};
-use super::manager::DatabaseManager;
+use crate::types::{DatabaseTask, ExportType, ExportResult, ExportProgress, SaveResult, DataPoint, AudioData, SessionSizeEstimate, SessionStatistics, LoadProgress, SessionLookup, DEFAULT_AUDIO_SAMPLE_RATE};
+use crate::config::{DatabaseConfig, ExportConfig, TimestampSource};
+use super::manager::{DatabaseManager, fallback_database_path};
+use super::status::{DatabaseStatus, SharedDatabaseStatus};
+
+/// 数据库文件被锁定时的最大重试次数
+const DB_OPEN_MAX_ATTEMPTS: u32 = 5;
+/// 首次重试前的等待时间，之后每次翻倍
+const DB_OPEN_INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+/// 累计删除这么多个session后自动重建表统计信息
+const REINDEX_AFTER_DELETES: usize = 100;
 
 pub fn run_database_handler(
     task_receiver: Receiver<DatabaseTask>,
     result_sender: Sender<SaveResult>,
-    shutdown_signal: Arc<AtomicBool>
+    shutdown_signal: Arc<AtomicBool>,
+    database_config: DatabaseConfig,
+    timestamp_source: TimestampSource,
+    db_status: SharedDatabaseStatus,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // 在保存线程中创建数据库连接
-    let db_manager = match DatabaseManager::new() {
-        Ok(db) => {
+    let mut db_manager = match open_database_with_retry(&database_config.path, &shutdown_signal, &db_status) {
+        Some(db) => {
             info!("Database handler thread: DuckDB initialized successfully");
             db
         }
-        Err(e) => {
-            error!("Database handler thread: Failed to initialize DuckDB: {}", e);
-            return Err(e.into());
+        None => {
+            // 原数据库无法打开时不退出线程，等待GUI请求改用新的数据库文件
+            match wait_for_fallback_database(&task_receiver, &result_sender, &shutdown_signal, &db_status) {
+                Some(db) => db,
+                None => {
+                    info!("Database handler thread exiting without an open database");
+                    return Ok(());
+                }
+            }
         }
     };
 
+    db_manager.set_max_acc_rows_per_session(database_config.max_acc_rows_per_session);
+    db_manager.set_timestamp_source(timestamp_source);
+    db_manager.set_audio_storage_format(database_config.audio_storage_format);
+
     info!("Database handler thread started");
 
+    // 上次重建统计信息以来删除的session数，只在本线程内计数
+    let mut deletes_since_reindex = 0;
+
     while !shutdown_signal.load(Ordering::Relaxed) {
         match task_receiver.recv_timeout(Duration::from_millis(100)) {
             Ok(task) => {
@@ -39,8 +64,14 @@ pub fn run_database_handler(
                             }
                         }
                     }
-                    DatabaseTask::Export { export_type, response_sender } => {
-                        let result = handle_export_request(&db_manager, export_type);
+                    DatabaseTask::Export { export_type, options, response_sender } => {
+                        let result = handle_export_request(&db_manager, export_type, &options, None);
+                        if let Err(e) = response_sender.try_send(result) {
+                            warn!("Database handler: Failed to send export result: {}", e);
+                        }
+                    }
+                    DatabaseTask::ExportWithProgress { export_type, options, progress_sender, response_sender } => {
+                        let result = handle_export_request(&db_manager, export_type, &options, Some(&progress_sender));
                         if let Err(e) = response_sender.try_send(result) {
                             warn!("Database handler: Failed to send export result: {}", e);
                         }
@@ -57,10 +88,10 @@ pub fn run_database_handler(
                             warn!("Database handler: Failed to send unexported sessions: {}", e);
                         }
                     }
-                    DatabaseTask::GetAllSessionsWithExportStatus { response_sender } => {
-                        let sessions = db_manager.get_all_sessions_with_export_status().unwrap_or_default();
+                    DatabaseTask::GetSessionsWithMetadata { response_sender } => {
+                        let sessions = db_manager.get_all_sessions_with_metadata().unwrap_or_default();
                         if let Err(e) = response_sender.try_send(sessions) {
-                            warn!("Database handler: Failed to send sessions with export status: {}", e);
+                            warn!("Database handler: Failed to send sessions with metadata: {}", e);
                         }
                     }
                     DatabaseTask::GetUsernames { response_sender } => {
@@ -87,6 +118,18 @@ pub fn run_database_handler(
                             warn!("Database handler: Failed to send sessions by username: {}", e);
                         }
                     }
+                    DatabaseTask::GetNodeIds { response_sender } => {
+                        let node_ids = db_manager.get_all_node_ids().unwrap_or_default();
+                        if let Err(e) = response_sender.try_send(node_ids) {
+                            warn!("Database handler: Failed to send node ids: {}", e);
+                        }
+                    }
+                    DatabaseTask::GetSessionsByNodeId { node_id, response_sender } => {
+                        let sessions = db_manager.get_sessions_by_node_id(&node_id).unwrap_or_default();
+                        if let Err(e) = response_sender.try_send(sessions) {
+                            warn!("Database handler: Failed to send sessions by node id: {}", e);
+                        }
+                    }
                     DatabaseTask::GetSessionsByUsernameAndScenario { username, scenario, response_sender } => {
                         let sessions = db_manager.get_sessions_by_username_and_scenario(&username, &scenario).unwrap_or_default();
                         if let Err(e) = response_sender.try_send(sessions) {
@@ -99,23 +142,129 @@ pub fn run_database_handler(
                             warn!("Database handler: Failed to send export status: {}", e);
                         }
                     }
-                    DatabaseTask::LoadHistoryData { session_id, response_sender } => {
-                        let result = handle_load_history_data(&db_manager, &session_id);
-                        if let Err(e) = response_sender.try_send(result) {
-                            warn!("Database handler: Failed to send history data: {}", e);
+                    DatabaseTask::LoadHistoryData { session_id, progress, response_sender } => {
+                        // 被取消的加载不再回传结果，GUI已经丢弃了对应的接收器
+                        if let Some(result) = handle_load_history_data(&db_manager, &session_id, &progress) {
+                            if let Err(e) = response_sender.try_send(result) {
+                                warn!("Database handler: Failed to send history data: {}", e);
+                            }
                         }
                     }
-                    DatabaseTask::LoadAlignedHistoryData { session_id, response_sender } => {
-                        let result = handle_load_aligned_history_data(&db_manager, &session_id);
+                    DatabaseTask::LoadAlignedHistoryData { session_id, progress, response_sender } => {
+                        if let Some(result) = handle_load_aligned_history_data(&db_manager, &session_id, &progress) {
+                            if let Err(e) = response_sender.try_send(result) {
+                                warn!("Database handler: Failed to send aligned history data: {}", e);
+                            }
+                        }
+                    }
+                    DatabaseTask::ResolveSession { session_id, response_sender } => {
+                        let result = handle_resolve_session(&db_manager, &session_id);
                         if let Err(e) = response_sender.try_send(result) {
-                            warn!("Database handler: Failed to send aligned history data: {}", e);
+                            warn!("Database handler: Failed to send resolved session: {}", e);
                         }
                     }
                     DatabaseTask::DeleteSession { session_id, response_sender } => {
-                        let result = handle_delete_session(&db_manager, &session_id);
+                        let result = handle_delete_session(&db_manager, &session_id, &mut deletes_since_reindex);
                         if let Err(e) = response_sender.try_send(result) {
                             warn!("Database handler: Failed to send delete result: {}", e);
                         }
+                        reindex_if_needed(&db_manager, &mut deletes_since_reindex);
+                    }
+                    DatabaseTask::BatchDelete { session_ids, response_sender } => {
+                        let result = handle_batch_delete(&db_manager, &session_ids, &mut deletes_since_reindex);
+                        if let Err(e) = response_sender.try_send(result) {
+                            warn!("Database handler: Failed to send batch delete result: {}", e);
+                        }
+                        reindex_if_needed(&db_manager, &mut deletes_since_reindex);
+                    }
+                    DatabaseTask::RenameScenario { old_name, new_name, username, response_sender } => {
+                        let result = handle_rename_scenario(&db_manager, &old_name, &new_name, &username);
+                        if let Err(e) = response_sender.try_send(result) {
+                            warn!("Database handler: Failed to send rename scenario result: {}", e);
+                        }
+                    }
+                    DatabaseTask::DuplicateSession { session_id, response_sender } => {
+                        let result = handle_duplicate_session(&db_manager, &session_id);
+                        if let Err(e) = response_sender.try_send(result) {
+                            warn!("Database handler: Failed to send duplicate session result: {}", e);
+                        }
+                    }
+                    DatabaseTask::OpenFallbackDatabase => {
+                        info!("Database handler: Database already open, ignoring fallback request");
+                    }
+                    DatabaseTask::Reindex => {
+                        if let Err(e) = db_manager.reindex_sessions() {
+                            error!("Database handler: Failed to rebuild table statistics: {}", e);
+                        }
+                        deletes_since_reindex = 0;
+                    }
+                    DatabaseTask::GetSessionSummaries { session_ids, response_sender } => {
+                        let summaries = db_manager.get_session_summaries(&session_ids).unwrap_or_default();
+                        if let Err(e) = response_sender.try_send(summaries) {
+                            warn!("Database handler: Failed to send session summaries: {}", e);
+                        }
+                    }
+                    DatabaseTask::SetReviewed { session_id, reviewed, response_sender } => {
+                        let result = db_manager.set_session_reviewed(&session_id, reviewed).map_err(|e| {
+                            error!("Database handler: Failed to set reviewed flag for session {}: {}", session_id, e);
+                            e.to_string()
+                        });
+                        if let Err(e) = response_sender.try_send(result) {
+                            warn!("Database handler: Failed to send reviewed flag result: {}", e);
+                        }
+                    }
+                    DatabaseTask::GetReviewed { response_sender } => {
+                        let reviewed = db_manager.get_reviewed_sessions().unwrap_or_else(|e| {
+                            error!("Database handler: Failed to load reviewed sessions: {}", e);
+                            Vec::new()
+                        });
+                        if let Err(e) = response_sender.try_send(reviewed) {
+                            warn!("Database handler: Failed to send reviewed sessions: {}", e);
+                        }
+                    }
+                    DatabaseTask::CheckIntegrity { response_sender } => {
+                        let result = db_manager.check_integrity().map_err(|e| {
+                            error!("Database handler: Integrity check failed: {}", e);
+                            e.to_string()
+                        });
+                        if let Err(e) = response_sender.try_send(result) {
+                            warn!("Database handler: Failed to send integrity report: {}", e);
+                        }
+                    }
+                    DatabaseTask::ComputeSessionStatistics { session_id, response_sender } => {
+                        let statistics = db_manager.get_session_statistics(&session_id).unwrap_or_else(|e| {
+                            error!("Database handler: Failed to compute statistics for session {}: {}", session_id, e);
+                            SessionStatistics::new(session_id.clone())
+                        });
+                        if let Err(e) = response_sender.try_send(statistics) {
+                            warn!("Database handler: Failed to send session statistics: {}", e);
+                        }
+                    }
+                    DatabaseTask::GetAudioSampleRate { session_id, response_sender } => {
+                        let sample_rate = db_manager.get_audio_sample_rate_for_session(&session_id).unwrap_or(DEFAULT_AUDIO_SAMPLE_RATE);
+                        if let Err(e) = response_sender.try_send(sample_rate) {
+                            warn!("Database handler: Failed to send audio sample rate: {}", e);
+                        }
+                    }
+                    DatabaseTask::GetDatabaseSize { response_sender } => {
+                        match db_manager.get_database_size_bytes() {
+                            Ok(size) => {
+                                if let Err(e) = response_sender.try_send(size) {
+                                    warn!("Database handler: Failed to send database size: {}", e);
+                                }
+                            }
+                            // 丢弃发送端，GUI收到Disconnected后在下一个刷新周期重试
+                            Err(e) => error!("Database handler: Failed to get database size: {}", e),
+                        }
+                    }
+                    DatabaseTask::GetSessionSizeEstimate { session_id, response_sender } => {
+                        let acc_rows = db_manager.get_accelerometer_row_count(&session_id).unwrap_or(0);
+                        let audio_samples = db_manager.get_audio_sample_count(&session_id).unwrap_or(0);
+                        let audio_bytes = db_manager.get_audio_data_size_bytes(&session_id).unwrap_or(0);
+                        let estimate = SessionSizeEstimate::new(session_id, acc_rows, audio_samples, audio_bytes);
+                        if let Err(e) = response_sender.try_send(estimate) {
+                            warn!("Database handler: Failed to send session size estimate: {}", e);
+                        }
                     }
                 }
             }
@@ -148,14 +297,30 @@ fn handle_save_task(
     scenario: String,
 ) -> Result<(), bool> {
     let mut acc_saved = 0;
+    let mut acc_truncated = 0;
     let mut audio_saved = 0;
     let mut error_msg = None;
 
+    // 加速度和音频数据在同一事务中写入，避免只保存了其中一种
+    if let Err(e) = db_manager.begin_transaction() {
+        error!("Database handler: Failed to begin save transaction: {}", e);
+        error_msg = Some(format!("Error starting save transaction: {}", e));
+    }
+
+    // 审计记录与数据在同一事务中写入，不会出现有数据没有记录或有记录没有数据的情况
+    if error_msg.is_none() {
+        if let Err(e) = db_manager.audit_log("save", &session_id, &username) {
+            error!("Database handler: Failed to write audit log for saving session {}: {}", session_id, e);
+            error_msg = Some(format!("Error writing audit log: {}", e));
+        }
+    }
+
     // 保存加速度数据
-    if !accelerometer_data.is_empty() {
+    if !accelerometer_data.is_empty() && error_msg.is_none() {
         match db_manager.save_accelerometer_data(&accelerometer_data, &session_id, &username, &scenario) {
-            Ok(count) => {
+            Ok((count, truncated)) => {
                 acc_saved = count;
+                acc_truncated = truncated;
                 info!("Database handler: Saved {} accelerometer data points", count);
             }
             Err(e) => {
@@ -179,11 +344,24 @@ fn handle_save_task(
         }
     }
 
+    // 提交事务，任一步失败则整体回滚
+    if error_msg.is_none() {
+        if let Err(e) = db_manager.commit_transaction() {
+            error!("Database handler: Failed to commit save transaction: {}", e);
+            error_msg = Some(format!("Error committing save transaction: {}", e));
+        }
+    }
+
     // 发送保存结果
-    let result = SaveResult {
-        acc_saved,
-        audio_saved,
-        error: error_msg,
+    let result = match error_msg {
+        None => SaveResult::success(acc_saved, audio_saved).with_truncated(acc_truncated),
+        Some(msg) => {
+            if let Err(e) = db_manager.rollback_transaction() {
+                warn!("Database handler: Failed to roll back save transaction: {}", e);
+            }
+            warn!("Database handler: Save transaction rolled back ({} acc, {} audio discarded)", acc_saved, audio_saved);
+            SaveResult::error(format!("{} (nothing saved)", msg))
+        }
     };
 
     // 使用 try_send 避免阻塞，並檢查通道狀態
@@ -204,109 +382,294 @@ fn handle_save_task(
     }
 }
 
-pub fn handle_export_request(db_manager: &DatabaseManager, export_type: ExportType) -> ExportResult {
+/// 打开数据库，文件被锁定时按指数退避重试；成功后更新共享状态
+fn open_database_with_retry(
+    db_path: &str,
+    shutdown_signal: &AtomicBool,
+    db_status: &SharedDatabaseStatus,
+) -> Option<DatabaseManager> {
+    let mut backoff = DB_OPEN_INITIAL_BACKOFF;
+    let mut attempt = 1;
+
+    loop {
+        db_status.set(DatabaseStatus::Opening);
+        match DatabaseManager::open(db_path) {
+            Ok(db) => {
+                db_status.set(DatabaseStatus::Ready { path: db_path.to_string() });
+                return Some(db);
+            }
+            Err(e) if e.is_transient() && attempt < DB_OPEN_MAX_ATTEMPTS && !shutdown_signal.load(Ordering::Relaxed) => {
+                warn!("Database handler thread: {} (attempt {}/{}), retrying in {:?}",
+                      e, attempt, DB_OPEN_MAX_ATTEMPTS, backoff);
+                std::thread::sleep(backoff);
+                backoff *= 2;
+                attempt += 1;
+            }
+            Err(e) => {
+                error!("Database handler thread: Failed to initialize DuckDB at {}: {}", db_path, e);
+                db_status.set(DatabaseStatus::Failed(e));
+                return None;
+            }
+        }
+    }
+}
+
+/// 数据库不可用期间处理任务：保存任务返回错误，收到回退请求时打开新的带时间戳的数据库文件
+fn wait_for_fallback_database(
+    task_receiver: &Receiver<DatabaseTask>,
+    result_sender: &Sender<SaveResult>,
+    shutdown_signal: &AtomicBool,
+    db_status: &SharedDatabaseStatus,
+) -> Option<DatabaseManager> {
+    while !shutdown_signal.load(Ordering::Relaxed) {
+        match task_receiver.recv_timeout(Duration::from_millis(100)) {
+            Ok(DatabaseTask::OpenFallbackDatabase) => {
+                let fallback_path = fallback_database_path();
+                info!("Database handler thread: Opening fallback database {}", fallback_path);
+                if let Some(db) = open_database_with_retry(&fallback_path, shutdown_signal, db_status) {
+                    return Some(db);
+                }
+            }
+            Ok(DatabaseTask::Save { .. }) => {
+                let _ = result_sender.try_send(SaveResult::error("Database unavailable, data not saved".to_string()));
+            }
+            Ok(_) => {
+                // 丢弃任务会关闭其响应通道，GUI端据此得知请求失败
+                warn!("Database handler thread: Database unavailable, dropping task");
+            }
+            Err(crossbeam_channel::RecvTimeoutError::Timeout) => {}
+            Err(crossbeam_channel::RecvTimeoutError::Disconnected) => return None,
+        }
+    }
+
+    None
+}
+
+pub fn handle_export_request(db_manager: &DatabaseManager, export_type: ExportType, options: &ExportConfig, progress_sender: Option<&Sender<ExportProgress>>) -> ExportResult {
     match export_type {
         ExportType::SelectedSessions(session_ids) => {
-            handle_selected_sessions_export(db_manager, session_ids)
+            handle_selected_sessions_export(db_manager, session_ids, options, progress_sender)
         }
         ExportType::NewSessions => {
-            handle_new_sessions_export(db_manager)
+            handle_new_sessions_export(db_manager, options, progress_sender)
+        }
+        ExportType::Comparison(session_ids) => {
+            handle_comparison_export(db_manager, session_ids, options)
+        }
+        ExportType::TimeRange { session_id, start_ms, end_ms } => {
+            handle_range_export(db_manager, &session_id, start_ms, end_ms, options)
         }
     }
 }
 
-fn handle_selected_sessions_export(db_manager: &DatabaseManager, session_ids: Vec<String>) -> ExportResult {
+/// 片段导出不写入导出清单：清单按session记录整段导出，片段不代表该session已导出
+fn handle_range_export(db_manager: &DatabaseManager, session_id: &str, start_ms: i64, end_ms: i64, options: &ExportConfig) -> ExportResult {
+    match export_session_range_internal(db_manager, session_id, start_ms, end_ms, options) {
+        Ok(entry) => {
+            info!("Successfully exported range {}-{}ms of session {}", start_ms, end_ms, session_id);
+            ExportResult::new(1, 0, format!("Range exported to {} ({} rows)", entry.file_path, entry.row_count))
+        }
+        Err(e) => {
+            error!("Failed to export range of session {}: {}", session_id, e);
+            ExportResult::new(0, 1, format!("Range export failed: {}", e))
+        }
+    }
+}
+
+fn handle_comparison_export(db_manager: &DatabaseManager, session_ids: Vec<String>, options: &ExportConfig) -> ExportResult {
+    match export_sessions_comparison_internal(db_manager, &session_ids, options) {
+        Ok(filename) => {
+            // 列前缀与session的对应关系
+            let mapping: Vec<String> = session_ids.iter()
+                .enumerate()
+                .map(|(index, session_id)| format!("s{}={}", index + 1, session_id))
+                .collect();
+            ExportResult::new(session_ids.len(), 0,
+                format!("Comparison exported to {} ({})", filename, mapping.join(", ")))
+        }
+        Err(e) => {
+            error!("Failed to export session comparison: {}", e);
+            ExportResult::new(0, session_ids.len(), format!("Comparison export failed: {}", e))
+        }
+    }
+}
+
+/// 统计已写入的文件和字节数，每导出完一个session向GUI发送一次进度
+struct ExportProgressReporter<'a> {
+    sender: Option<&'a Sender<ExportProgress>>,
+    files_total: usize,
+    files_done: usize,
+    bytes_written: u64,
+    started: Instant,
+}
+
+impl<'a> ExportProgressReporter<'a> {
+    fn new(sender: Option<&'a Sender<ExportProgress>>, files_total: usize) -> Self {
+        Self {
+            sender,
+            files_total,
+            files_done: 0,
+            bytes_written: 0,
+            started: Instant::now(),
+        }
+    }
+
+    /// 记录一个处理完的session，导出失败时没有文件
+    fn file_done(&mut self, file_path: Option<&str>) {
+        self.files_done += 1;
+        if let Some(path) = file_path {
+            self.bytes_written += std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        }
+
+        let Some(sender) = self.sender else {
+            return;
+        };
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let progress = ExportProgress {
+            files_done: self.files_done,
+            files_total: self.files_total,
+            bytes_written: self.bytes_written,
+            bytes_per_sec: if elapsed > 0.0 { self.bytes_written as f64 / elapsed } else { 0.0 },
+        };
+        if let Err(e) = sender.try_send(progress) {
+            warn!("Database handler: Failed to send export progress: {}", e);
+        }
+    }
+}
+
+fn handle_selected_sessions_export(db_manager: &DatabaseManager, session_ids: Vec<String>, options: &ExportConfig, progress_sender: Option<&Sender<ExportProgress>>) -> ExportResult {
     let mut success_count = 0;
-    let mut error_count = 0;
+    let mut failures = Vec::new();
+    let mut manifest_entries = Vec::new();
+    let mut progress = ExportProgressReporter::new(progress_sender, session_ids.len());
 
     for session_id in &session_ids {
-        match export_session_to_csv_internal(db_manager, session_id) {
-            Ok(()) => {
+        match export_session_to_csv_internal(db_manager, session_id, options) {
+            Ok(entry) => {
                 success_count += 1;
+                progress.file_done(Some(&entry.file_path));
+                manifest_entries.push(entry);
                 info!("Successfully exported session: {}", session_id);
             }
             Err(e) => {
-                error_count += 1;
+                progress.file_done(None);
                 error!("Failed to export session {}: {}", session_id, e);
+                failures.push((session_id.clone(), e));
             }
         }
     }
 
+    if let Err(e) = update_export_manifest(&manifest_entries) {
+        warn!("Failed to update export manifest: {}", e);
+    }
+
+    let error_count = failures.len();
     ExportResult {
         success_count,
         error_count,
         message: format!("Export completed: {} succeeded, {} failed", success_count, error_count),
+        failures,
     }
 }
 
-fn handle_new_sessions_export(db_manager: &DatabaseManager) -> ExportResult {
+fn handle_new_sessions_export(db_manager: &DatabaseManager, options: &ExportConfig, progress_sender: Option<&Sender<ExportProgress>>) -> ExportResult {
     let mut success_count = 0;
-    let mut error_count = 0;
+    let mut failures = Vec::new();
+    let mut manifest_entries = Vec::new();
 
     match db_manager.get_all_sessions() {
         Ok(sessions) => {
-            for session_id in &sessions {
-                // 检查是否已导出（通过文件系统检查）
-                if !db_manager.is_session_exported(session_id).unwrap_or(false) {
-                    match export_session_to_csv_internal(db_manager, session_id) {
-                        Ok(()) => {
-                            success_count += 1;
-                            info!("Successfully exported new session: {}", session_id);
-                        }
-                        Err(e) => {
-                            error_count += 1;
-                            error!("Failed to export session {}: {}", session_id, e);
-                        }
+            // 检查是否已导出（通过文件系统检查）
+            let (new_sessions, exported_sessions): (Vec<&String>, Vec<&String>) = sessions.iter()
+                .partition(|session_id| !db_manager.is_session_exported(session_id).unwrap_or(false));
+            for session_id in exported_sessions {
+                info!("Session {} already exported, skipping", session_id);
+            }
+
+            let mut progress = ExportProgressReporter::new(progress_sender, new_sessions.len());
+            for session_id in new_sessions {
+                match export_session_to_csv_internal(db_manager, session_id, options) {
+                    Ok(entry) => {
+                        success_count += 1;
+                        progress.file_done(Some(&entry.file_path));
+                        manifest_entries.push(entry);
+                        info!("Successfully exported new session: {}", session_id);
+                    }
+                    Err(e) => {
+                        progress.file_done(None);
+                        error!("Failed to export session {}: {}", session_id, e);
+                        failures.push((session_id.clone(), e));
                     }
-                } else {
-                    info!("Session {} already exported, skipping", session_id);
                 }
             }
 
+            if let Err(e) = update_export_manifest(&manifest_entries) {
+                warn!("Failed to update export manifest: {}", e);
+            }
+
+            let error_count = failures.len();
             if success_count == 0 && error_count == 0 {
-                ExportResult {
-                    success_count: 0,
-                    error_count: 0,
-                    message: "No new sessions to export".to_string(),
-                }
+                ExportResult::no_data()
             } else {
                 ExportResult {
                     success_count,
                     error_count,
                     message: format!("New sessions export completed: {} succeeded, {} failed", success_count, error_count),
+                    failures,
                 }
             }
         }
-        Err(e) => ExportResult {
-            success_count: 0,
-            error_count: 1,
-            message: format!("Failed to get sessions: {}", e),
-        }
+        Err(e) => ExportResult::new(0, 1, format!("Failed to get sessions: {}", e)),
     }
 }
 
-fn handle_load_history_data(db_manager: &DatabaseManager, session_id: &str) -> (Vec<DataPoint>, Vec<f64>) {
+/// 查询session所属的用户和场景，空值按历史面板的约定映射为unknown_user/standard
+fn handle_resolve_session(db_manager: &DatabaseManager, session_id: &str) -> SessionLookup {
+    let acc_rows = db_manager.get_accelerometer_row_count(session_id).unwrap_or(0);
+    let audio_bytes = db_manager.get_audio_data_size_bytes(session_id).unwrap_or(0);
+    if acc_rows == 0 && audio_bytes == 0 {
+        warn!("Database handler: Session {} not found", session_id);
+        return None;
+    }
+
+    let username = db_manager.get_username_for_session(session_id).unwrap_or_default();
+    let scenario = db_manager.get_scenario_for_session(session_id).unwrap_or_default();
+
+    let username = if username.is_empty() { "unknown_user".to_string() } else { username };
+    let scenario = if scenario.is_empty() { "standard".to_string() } else { scenario };
+
+    Some((username, scenario))
+}
+
+fn handle_load_history_data(db_manager: &DatabaseManager, session_id: &str, progress: &LoadProgress) -> Option<(Vec<DataPoint>, Vec<f64>)> {
     let mut acc_data = Vec::new();
     let mut audio_data = Vec::new();
 
     // 加载加速度数据
-    match db_manager.get_accelerometer_data_by_session(session_id) {
-        Ok(data) => {
+    match db_manager.get_accelerometer_data_by_session_cancellable(session_id, progress) {
+        Ok(Some(data)) => {
             acc_data = data;
             info!("Database handler: Loaded {} accelerometer points for session {}", acc_data.len(), session_id);
         }
+        Ok(None) => {
+            info!("Database handler: History load for session {} cancelled", session_id);
+            return None;
+        }
         Err(e) => {
             error!("Database handler: Failed to load accelerometer data for session {}: {}", session_id, e);
         }
     }
 
+    if progress.is_cancelled() {
+        info!("Database handler: History load for session {} cancelled", session_id);
+        return None;
+    }
+
     // 加载音频数据
     match db_manager.get_audio_data_by_session(session_id) {
         Ok(data) => {
-            // 将所有音频片段的样本合并到一个向量中
-            for (_, _, samples, _, _, _) in data {
-                audio_data.extend(samples);
-            }
+            // 将所有音频片段的样本合并到一个向量中，片段之间的时间间隔补静音
+            audio_data = crate::database::tasks::merge_audio_blocks_with_gaps(data);
             info!("Database handler: Loaded {} audio samples for session {}", audio_data.len(), session_id);
         }
         Err(e) => {
@@ -314,24 +677,33 @@ fn handle_load_history_data(db_manager: &DatabaseManager, session_id: &str) -> (
         }
     }
 
-    (acc_data, audio_data)
+    Some((acc_data, audio_data))
 }
 
-fn handle_load_aligned_history_data(db_manager: &DatabaseManager, session_id: &str) -> (Vec<DataPoint>, Vec<f64>, i64) {
+fn handle_load_aligned_history_data(db_manager: &DatabaseManager, session_id: &str, progress: &LoadProgress) -> Option<(Vec<DataPoint>, Vec<f64>, i64)> {
     let mut acc_data = Vec::new();
     let mut audio_data_raw = Vec::new();
 
     // 加载原始加速度数据
-    match db_manager.get_accelerometer_data_by_session(session_id) {
-        Ok(data) => {
+    match db_manager.get_accelerometer_data_by_session_cancellable(session_id, progress) {
+        Ok(Some(data)) => {
             acc_data = data;
             info!("Database handler: Loaded {} raw accelerometer points for session {}", acc_data.len(), session_id);
         }
+        Ok(None) => {
+            info!("Database handler: Aligned history load for session {} cancelled", session_id);
+            return None;
+        }
         Err(e) => {
             error!("Database handler: Failed to load accelerometer data for session {}: {}", session_id, e);
         }
     }
 
+    if progress.is_cancelled() {
+        info!("Database handler: Aligned history load for session {} cancelled", session_id);
+        return None;
+    }
+
     // 加载原始音频数据
     match db_manager.get_audio_data_by_session(session_id) {
         Ok(data) => {
@@ -345,9 +717,11 @@ fn handle_load_aligned_history_data(db_manager: &DatabaseManager, session_id: &s
 
     // 如果没有数据，返回空结果
     if acc_data.is_empty() && audio_data_raw.is_empty() {
-        return (Vec::new(), Vec::new(), 0);
+        return Some((Vec::new(), Vec::new(), 0));
     }
 
+    crate::database::tasks::apply_timestamp_source(db_manager, session_id, &mut acc_data, audio_data_raw.iter_mut().map(|(start, end, ..)| (start, end)));
+
     // 使用对齐算法处理数据
     let (aligned_acc_data, aligned_audio_data, common_time_range_ms) =
         crate::database::tasks::align_session_data_internal(&acc_data, &audio_data_raw);
@@ -361,18 +735,105 @@ fn handle_load_aligned_history_data(db_manager: &DatabaseManager, session_id: &s
     info!("Database handler: Aligned data - {} acc points, {} audio samples, {}ms common range", 
           aligned_acc_data.len(), final_audio_data.len(), common_time_range_ms);
 
-    (aligned_acc_data, final_audio_data, common_time_range_ms)
+    Some((aligned_acc_data, final_audio_data, common_time_range_ms))
+}
+
+fn handle_rename_scenario(db_manager: &DatabaseManager, old_name: &str, new_name: &str, username: &str) -> Result<usize, String> {
+    match db_manager.rename_scenario(old_name, new_name, username) {
+        Ok(updated_count) => {
+            info!("Database handler: Renamed scenario '{}' to '{}' ({} records)", old_name, new_name, updated_count);
+            Ok(updated_count)
+        }
+        Err(e) => {
+            error!("Database handler: Failed to rename scenario '{}': {}", old_name, e);
+            Err(format!("Failed to rename scenario: {}", e))
+        }
+    }
 }
 
-fn handle_delete_session(db_manager: &DatabaseManager, session_id: &str) -> Result<(), String> {
-    match db_manager.delete_session(session_id) {
+fn handle_duplicate_session(db_manager: &DatabaseManager, session_id: &str) -> Result<String, String> {
+    // 加速度和音频在同一事务中复制，失败时不留下只有部分数据的新session
+    if let Err(e) = db_manager.begin_transaction() {
+        error!("Database handler: Failed to begin transaction for duplicating session {}: {}", session_id, e);
+        return Err(format!("Failed to duplicate session: {}", e));
+    }
+
+    match db_manager.duplicate_session(session_id) {
+        Ok(new_session_id) => {
+            if let Err(e) = db_manager.commit_transaction() {
+                error!("Database handler: Failed to commit duplicate of session {}: {}", session_id, e);
+                return Err(format!("Failed to duplicate session: {}", e));
+            }
+            Ok(new_session_id)
+        }
+        Err(e) => {
+            error!("Database handler: Failed to duplicate session {}: {}", session_id, e);
+            if let Err(rollback_error) = db_manager.rollback_transaction() {
+                error!("Database handler: Failed to rollback duplicate of session {}: {}", session_id, rollback_error);
+            }
+            Err(format!("Failed to duplicate session: {}", e))
+        }
+    }
+}
+
+/// 审计记录与删除在同一事务中执行，提交成功后才计入deletes_since_reindex
+fn handle_delete_session(db_manager: &DatabaseManager, session_id: &str, deletes_since_reindex: &mut usize) -> Result<(), String> {
+    // 删除后无法再查到用户名，先读取再写审计日志
+    let username = db_manager.get_username_for_session(session_id).unwrap_or_default();
+
+    db_manager.begin_transaction()
+        .map_err(|e| format!("Failed to begin delete transaction: {}", e))?;
+
+    let result = db_manager.audit_log("delete", session_id, &username)
+        .map_err(|e| format!("Failed to write audit log: {}", e))
+        .and_then(|()| db_manager.delete_session(session_id)
+            .map_err(|e| format!("Failed to delete session: {}", e)))
+        .and_then(|deleted_count| db_manager.commit_transaction()
+            .map(|()| deleted_count)
+            .map_err(|e| format!("Failed to commit delete transaction: {}", e)));
+
+    match result {
         Ok(deleted_count) => {
+            *deletes_since_reindex += 1;
             info!("Database handler: Successfully deleted {} records for session {}", deleted_count, session_id);
             Ok(())
         }
         Err(e) => {
+            if let Err(rollback_error) = db_manager.rollback_transaction() {
+                error!("Database handler: Failed to roll back delete of session {}: {}", session_id, rollback_error);
+            }
             error!("Database handler: Failed to delete session {}: {}", session_id, e);
-            Err(format!("Failed to delete session: {}", e))
+            Err(e)
         }
     }
 }
+
+/// 删除计数达到阈值时重建表统计信息并清零计数，失败时保留计数在下次删除后重试
+fn reindex_if_needed(db_manager: &DatabaseManager, deletes_since_reindex: &mut usize) {
+    if *deletes_since_reindex < REINDEX_AFTER_DELETES {
+        return;
+    }
+
+    info!("Database handler: {} sessions deleted since last reindex, rebuilding table statistics", deletes_since_reindex);
+    match db_manager.reindex_sessions() {
+        Ok(()) => *deletes_since_reindex = 0,
+        Err(e) => error!("Database handler: Failed to rebuild table statistics: {}", e),
+    }
+}
+
+fn handle_batch_delete(db_manager: &DatabaseManager, session_ids: &[String], deletes_since_reindex: &mut usize) -> Result<(), String> {
+    let failures: Vec<String> = session_ids.iter()
+        .filter_map(|session_id| {
+            handle_delete_session(db_manager, session_id, deletes_since_reindex)
+                .err()
+                .map(|e| format!("{}: {}", session_id, e))
+        })
+        .collect();
+
+    info!("Database handler: Batch deleted {} of {} sessions", session_ids.len() - failures.len(), session_ids.len());
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(failures.join("; "))
+    }
+}
@@ -2,45 +2,110 @@ use duckdb::{Connection, Result as DuckResult};
 use std::fs;
 use log::{info, error, warn};
 use crate::{DataPoint, AudioData};
+use crate::config::{AudioStorageFormat, TimestampSource};
+use crate::types::{LoadProgress, SessionInfo, SessionSummary, SessionStatistics, IntegrityIssue, IntegrityIssueKind, IntegrityReport, DEFAULT_AUDIO_SAMPLE_RATE};
 use chrono::Utc;
 use super::schema::DatabaseSchema;
+use super::status::DatabaseOpenError;
+use super::audio_codec::{decode_flac, encode_flac, FLAC_FORMAT};
+
+/// 加载历史数据时每读取这么多行检查一次取消标志
+const LOAD_BATCH_ROWS: usize = 10_000;
+
+/// 完整性检查认为合理的加速度采样率范围（Hz），与采集校准的合理范围一致
+const PLAUSIBLE_ACC_SAMPLE_RATE: std::ops::RangeInclusive<f64> = 50.0..=2000.0;
+/// 完整性检查认为合理的音频采样率范围（Hz）
+const PLAUSIBLE_AUDIO_SAMPLE_RATE: std::ops::RangeInclusive<i64> = 1000..=192_000;
 
 pub struct DatabaseManager {
     conn: Connection,
+    max_acc_rows_per_session: Option<usize>,
+    timestamp_source: TimestampSource,
+    audio_storage_format: AudioStorageFormat,
 }
 
+/// 默认数据库文件路径
+pub const DEFAULT_DATABASE_PATH: &str = "data/sensor_data.db";
+
 impl DatabaseManager {
-    pub fn new() -> DuckResult<Self> {
+    /// 打开指定路径的数据库并建表/迁移，失败时区分文件锁定与文件损坏/版本不兼容
+    pub fn open(db_path: &str) -> Result<Self, DatabaseOpenError> {
         // 确保data目录存在
-        if let Err(e) = fs::create_dir_all("data") {
-            error!("Failed to create data directory: {}", e);
+        if let Some(parent) = std::path::Path::new(db_path).parent() {
+            if let Err(e) = fs::create_dir_all(parent) {
+                error!("Failed to create data directory: {}", e);
+            }
         }
 
-        let db_path = "data/sensor_data.db";
-        let conn = Connection::open(db_path)?;
+        let conn = Connection::open(db_path).map_err(|e| DatabaseOpenError::classify(&e))?;
         
         info!("Database connection established at: {}", db_path);
         
-        let manager = DatabaseManager { conn };
-        DatabaseSchema::create_tables_and_migrate(&manager.conn)?;
+        let manager = DatabaseManager { conn, max_acc_rows_per_session: None, timestamp_source: TimestampSource::Device, audio_storage_format: AudioStorageFormat::Pcm16 };
+        DatabaseSchema::create_tables_and_migrate(&manager.conn)
+            .map_err(|e| DatabaseOpenError::classify(&e))?;
 
         Ok(manager)
     }
 
+    // 开启事务
+    pub fn begin_transaction(&self) -> DuckResult<()> {
+        self.conn.execute("BEGIN TRANSACTION", [])?;
+        Ok(())
+    }
+
+    // 提交事务
+    pub fn commit_transaction(&self) -> DuckResult<()> {
+        self.conn.execute("COMMIT", [])?;
+        Ok(())
+    }
+
+    // 回滚事务
+    pub fn rollback_transaction(&self) -> DuckResult<()> {
+        self.conn.execute("ROLLBACK", [])?;
+        Ok(())
+    }
+
+    // 设置每个session的加速度数据行数上限
+    pub fn set_max_acc_rows_per_session(&mut self, limit: Option<usize>) {
+        self.max_acc_rows_per_session = limit;
+    }
+
+    // 设置数据对齐使用的时间戳来源
+    pub fn set_timestamp_source(&mut self, source: TimestampSource) {
+        self.timestamp_source = source;
+    }
+
+    // 设置新保存的音频块使用的存储格式，已保存的块按各自的format列解码
+    pub fn set_audio_storage_format(&mut self, format: AudioStorageFormat) {
+        self.audio_storage_format = format;
+    }
 
-    pub fn save_accelerometer_data(&self, data: &[DataPoint], session_id: &str, username: &str, scenario: &str) -> DuckResult<usize> {
+    pub fn timestamp_source(&self) -> TimestampSource {
+        self.timestamp_source
+    }
+
+    // 保存加速度数据，返回 (保存的行数, 为满足行数上限而删除/丢弃的行数)
+    pub fn save_accelerometer_data(&self, data: &[DataPoint], session_id: &str, username: &str, scenario: &str) -> DuckResult<(usize, usize)> {
         if data.is_empty() {
             warn!("No accelerometer data to save");
-            return Ok(0);
+            return Ok((0, 0));
         }
 
+        let (data, truncated) = match self.max_acc_rows_per_session {
+            Some(limit) => self.enforce_acc_row_limit(data, session_id, limit)?,
+            None => (data, 0),
+        };
+
         let mut stmt = self.conn.prepare(
-            "INSERT INTO accelerometer_data (timestamp_ms, x, y, z, gx, gy, gz, session_id, username, scenario) 
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+            "INSERT INTO accelerometer_data (timestamp_ms, x, y, z, gx, gy, gz, qw, qx, qy, qz, node_id, arrival_timestamp_ms, temperature_c, session_id, username, scenario) 
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
         )?;
 
         let mut count = 0;
         for point in data {
+            // 没有姿态数据的设备写入NULL
+            let [qw, qx, qy, qz] = point.orientation.map_or([None; 4], |q| q.map(Some));
             // 直接保存Unix毫秒时间戳
             stmt.execute(duckdb::params![
                 point.timestamp,
@@ -50,6 +115,13 @@ impl DatabaseManager {
                 point.gx,
                 point.gy,
                 point.gz,
+                qw,
+                qx,
+                qy,
+                qz,
+                point.node_id,
+                point.arrival_timestamp,
+                point.temperature_c,
                 session_id,
                 username,
                 scenario
@@ -58,7 +130,34 @@ impl DatabaseManager {
         }
 
         info!("Saved {} accelerometer data points to database for user {} in scenario {}", count, username, scenario);
-        Ok(count)
+        Ok((count, truncated))
+    }
+
+    // 为即将写入的数据腾出空间：先删除session中最旧的行，新数据本身超过上限时只保留最新的部分
+    fn enforce_acc_row_limit<'a>(&self, data: &'a [DataPoint], session_id: &str, limit: usize) -> DuckResult<(&'a [DataPoint], usize)> {
+        let existing = self.get_accelerometer_row_count(session_id)?;
+        let excess = (existing + data.len()).saturating_sub(limit);
+        if excess == 0 {
+            return Ok((data, 0));
+        }
+
+        let to_delete = excess.min(existing);
+        let deleted = if to_delete > 0 {
+            self.conn.execute(
+                "DELETE FROM accelerometer_data WHERE session_id = ? AND id IN (
+                    SELECT id FROM accelerometer_data WHERE session_id = ? ORDER BY timestamp_ms LIMIT ?
+                 )",
+                duckdb::params![session_id, session_id, to_delete as i64],
+            )?
+        } else {
+            0
+        };
+
+        let skipped = data.len().saturating_sub(limit);
+        warn!("Session {} reached the {} row limit: deleted {} oldest rows, skipped {} incoming rows",
+              session_id, limit, deleted, skipped);
+
+        Ok((&data[skipped..], deleted + skipped))
     }
 
     pub fn save_audio_data(&self, audio_samples: &[f64], audio_metadata: Option<&AudioData>, session_id: &str, start_timestamp_ms: Option<i64>, end_timestamp_ms: Option<i64>, username: &str) -> DuckResult<usize> {
@@ -67,12 +166,15 @@ impl DatabaseManager {
             return Ok(0);
         }
 
-        // 将f64音频样本转换为i16字节数组
-        let mut audio_bytes = Vec::with_capacity(audio_samples.len() * 2);
-        for &sample in audio_samples {
-            let sample_i16 = (sample * 32767.0).clamp(-32768.0, 32767.0) as i16;
-            audio_bytes.extend_from_slice(&sample_i16.to_le_bytes());
-        }
+        // 将f64音频样本转换为i16样本
+        let samples_i16: Vec<i16> = audio_samples
+            .iter()
+            .map(|&sample| (sample * 32767.0).clamp(-32768.0, 32767.0) as i16)
+            .collect();
+
+        // 设备时钟与主机时钟的偏差取自最近一个音频块，用于推算窗口的主机接收时间
+        let arrival_offset_ms = audio_metadata
+            .and_then(|metadata| metadata.arrival_timestamp.map(|arrival| arrival - metadata.timestamp));
 
         let (default_timestamp_ms, sample_rate, channels, format) = if let Some(metadata) = audio_metadata {
             (
@@ -84,7 +186,7 @@ impl DatabaseManager {
         } else {
             (
                 Utc::now().timestamp_millis(),
-                16000, // 默认采样率
+                DEFAULT_AUDIO_SAMPLE_RATE as i32, // 默认采样率
                 1,     // 默认单声道
                 "PCM_16".to_string()
             )
@@ -94,14 +196,28 @@ impl DatabaseManager {
         let start_timestamp = start_timestamp_ms.unwrap_or(default_timestamp_ms);
         let end_timestamp = end_timestamp_ms.unwrap_or(default_timestamp_ms);
 
+        // 按配置的存储格式编码，format列记录实际的编码方式
+        let (audio_bytes, format) = match self.audio_storage_format {
+            AudioStorageFormat::Pcm16 => {
+                let mut audio_bytes = Vec::with_capacity(samples_i16.len() * 2);
+                for sample in &samples_i16 {
+                    audio_bytes.extend_from_slice(&sample.to_le_bytes());
+                }
+                (audio_bytes, format)
+            }
+            AudioStorageFormat::Flac => (encode_flac(&samples_i16, sample_rate as u32), FLAC_FORMAT.to_string()),
+        };
+
         let mut stmt = self.conn.prepare(
-            "INSERT INTO audio_data (start_timestamp_ms, end_timestamp_ms, sample_rate, channels, format, samples_count, audio_blob, session_id, username) 
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"
+            "INSERT INTO audio_data (start_timestamp_ms, end_timestamp_ms, arrival_start_timestamp_ms, arrival_end_timestamp_ms, sample_rate, channels, format, samples_count, audio_blob, session_id, username) 
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
         )?;
         
         stmt.execute(duckdb::params![
             start_timestamp,
             end_timestamp,
+            arrival_offset_ms.map(|offset| start_timestamp + offset),
+            arrival_offset_ms.map(|offset| end_timestamp + offset),
             sample_rate,
             channels,
             format,
@@ -153,44 +269,119 @@ impl DatabaseManager {
 
     // 获取所有session及其导出状态（优化版本）
     pub fn get_all_sessions_with_export_status(&self) -> DuckResult<Vec<(String, bool)>> {
-        let mut sessions_with_status = Vec::new();
-        
-        // 使用单个查询获取所有session及其用户名和场景信息
+        let sessions = self.get_all_sessions_with_metadata()?;
+
+        Ok(sessions
+            .into_iter()
+            .map(|session| (session.session_id, session.is_exported))
+            .collect())
+    }
+
+    // 单次查询获取所有session的用户名、场景、样本数、时长及导出状态
+    pub fn get_all_sessions_with_metadata(&self) -> DuckResult<Vec<SessionInfo>> {
         let mut stmt = self.conn.prepare(
-            "SELECT DISTINCT 
-                a.session_id,
-                COALESCE(NULLIF(a.username, ''), 'unknown_user') as username,
-                COALESCE(NULLIF(a.scenario, ''), 'standard') as scenario
-             FROM accelerometer_data a
-             UNION
-             SELECT DISTINCT 
-                a.session_id,
-                COALESCE(NULLIF(a.username, ''), 'unknown_user') as username,
-                COALESCE(NULLIF(a.scenario, ''), 'standard') as scenario
-             FROM audio_data ad
-             JOIN accelerometer_data a ON ad.session_id = a.session_id
+            "SELECT
+                session_id,
+                MIN(COALESCE(NULLIF(username, ''), 'unknown_user')) as username,
+                MIN(COALESCE(NULLIF(scenario, ''), 'standard')) as scenario,
+                COUNT(*) as acc_count,
+                COALESCE(MAX(timestamp_ms) - MIN(timestamp_ms), 0) as duration_ms
+             FROM accelerometer_data
+             GROUP BY session_id
              ORDER BY session_id DESC"
         )?;
-        
+
         let rows = stmt.query_map([], |row| {
             Ok((
                 row.get::<_, String>(0)?,  // session_id
                 row.get::<_, String>(1)?,  // username
                 row.get::<_, String>(2)?,  // scenario
+                row.get::<_, i64>(3)? as usize,  // acc_count
+                row.get::<_, i64>(4)?,  // duration_ms
             ))
         })?;
-        
+
+        // 一次性扫描导出目录，避免逐个session检查文件
+        let exported_files = collect_exported_files();
+
+        let mut sessions = Vec::new();
         for row in rows {
-            let (session_id, username, scenario) = row?;
-            
-            // 构建文件路径并检查是否存在
-            let file_path = format!("data_export/{}/{}/{}.csv", username, scenario, session_id);
-            let is_exported = std::path::Path::new(&file_path).exists();
-            
-            sessions_with_status.push((session_id, is_exported));
+            let (session_id, username, scenario, acc_count, duration_ms) = row?;
+            let is_exported = exported_files.contains(&format!("{}/{}/{}", username, scenario, session_id));
+
+            sessions.push(SessionInfo {
+                session_id,
+                username,
+                scenario,
+                acc_count,
+                duration_ms,
+                is_exported,
+            });
         }
-        
-        Ok(sessions_with_status)
+
+        Ok(sessions)
+    }
+
+    // 单次查询获取指定session的行数、音频大小、时长及导出状态，用于历史列表排序
+    pub fn get_session_summaries(&self, session_ids: &[String]) -> DuckResult<Vec<SessionSummary>> {
+        let wanted: std::collections::HashSet<&str> = session_ids.iter().map(|s| s.as_str()).collect();
+
+        let mut stmt = self.conn.prepare(
+            "SELECT
+                acc.session_id,
+                acc.username,
+                acc.scenario,
+                acc.acc_count,
+                acc.duration_ms,
+                CAST(COALESCE(audio.audio_bytes, 0) AS BIGINT) as audio_bytes
+             FROM (
+                SELECT
+                    session_id,
+                    MIN(COALESCE(NULLIF(username, ''), 'unknown_user')) as username,
+                    MIN(COALESCE(NULLIF(scenario, ''), 'standard')) as scenario,
+                    COUNT(*) as acc_count,
+                    COALESCE(MAX(timestamp_ms) - MIN(timestamp_ms), 0) as duration_ms
+                FROM accelerometer_data
+                GROUP BY session_id
+             ) acc
+             LEFT JOIN (
+                SELECT session_id, SUM(OCTET_LENGTH(audio_blob)) as audio_bytes
+                FROM audio_data
+                GROUP BY session_id
+             ) audio ON acc.session_id = audio.session_id"
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,  // session_id
+                row.get::<_, String>(1)?,  // username
+                row.get::<_, String>(2)?,  // scenario
+                row.get::<_, i64>(3)? as usize,  // acc_count
+                row.get::<_, i64>(4)?,  // duration_ms
+                row.get::<_, i64>(5)?.max(0) as u64,  // audio_bytes
+            ))
+        })?;
+
+        let exported_files = collect_exported_files();
+
+        let mut summaries = Vec::new();
+        for row in rows {
+            let (session_id, username, scenario, acc_rows, duration_ms, audio_bytes) = row?;
+            if !wanted.contains(session_id.as_str()) {
+                continue;
+            }
+            let is_exported = exported_files.contains(&format!("{}/{}/{}", username, scenario, session_id));
+
+            summaries.push(SessionSummary {
+                session_id,
+                acc_rows,
+                audio_bytes,
+                duration_ms,
+                is_exported,
+            });
+        }
+
+        Ok(summaries)
     }
 
     // 获取未导出的session ID列表（优化版本）
@@ -342,6 +533,44 @@ impl DatabaseManager {
     }
 
     // 获取指定用户的session列表
+    // 获取所有上报过node_id的传感器节点
+    pub fn get_all_node_ids(&self) -> DuckResult<Vec<String>> {
+        let mut node_ids = Vec::new();
+
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT node_id FROM accelerometer_data 
+             WHERE node_id IS NOT NULL AND node_id <> ''
+             ORDER BY node_id"
+        )?;
+
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+
+        for row in rows {
+            node_ids.push(row?);
+        }
+
+        Ok(node_ids)
+    }
+
+    // 获取指定节点的所有session
+    pub fn get_sessions_by_node_id(&self, node_id: &str) -> DuckResult<Vec<String>> {
+        let mut sessions = Vec::new();
+
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT session_id FROM accelerometer_data 
+             WHERE node_id = ? 
+             ORDER BY session_id DESC"
+        )?;
+
+        let rows = stmt.query_map([node_id], |row| row.get::<_, String>(0))?;
+
+        for row in rows {
+            sessions.push(row?);
+        }
+
+        Ok(sessions)
+    }
+
     pub fn get_sessions_by_username(&self, username: &str) -> DuckResult<Vec<String>> {
         let mut sessions = Vec::new();
         
@@ -497,10 +726,18 @@ impl DatabaseManager {
 
     // 获取指定session的加速度数据
     pub fn get_accelerometer_data_by_session(&self, session_id: &str) -> DuckResult<Vec<DataPoint>> {
+        let data = self.get_accelerometer_data_by_session_cancellable(session_id, &LoadProgress::default())?;
+        Ok(data.unwrap_or_default())
+    }
+
+    // 分批读取指定session的加速度数据，每批之后更新进度并检查取消标志；被取消时返回None
+    pub fn get_accelerometer_data_by_session_cancellable(&self, session_id: &str, progress: &LoadProgress) -> DuckResult<Option<Vec<DataPoint>>> {
+        progress.add_total_rows(self.get_accelerometer_row_count(session_id)?);
+
         let mut data = Vec::new();
         
         let mut stmt = self.conn.prepare(
-            "SELECT timestamp_ms, x, y, z, gx, gy, gz FROM accelerometer_data 
+            "SELECT timestamp_ms, x, y, z, gx, gy, gz, qw, qx, qy, qz, node_id, arrival_timestamp_ms, temperature_c FROM accelerometer_data 
              WHERE session_id = ? 
              ORDER BY timestamp_ms"
         )?;
@@ -514,22 +751,95 @@ impl DatabaseManager {
                 gx: row.get::<_, f64>(4)?,
                 gy: row.get::<_, f64>(5)?,
                 gz: row.get::<_, f64>(6)?,
+                orientation: orientation_from_columns(
+                    row.get::<_, Option<f64>>(7)?,
+                    row.get::<_, Option<f64>>(8)?,
+                    row.get::<_, Option<f64>>(9)?,
+                    row.get::<_, Option<f64>>(10)?,
+                ),
+                node_id: row.get::<_, Option<String>>(11)?,
+                arrival_timestamp: row.get::<_, Option<i64>>(12)?,
+                temperature_c: row.get::<_, Option<f64>>(13)?,
             })
         })?;
         
         for row in rows {
             data.push(row?);
+
+            if data.len().is_multiple_of(LOAD_BATCH_ROWS) {
+                progress.add_loaded_rows(LOAD_BATCH_ROWS);
+                if progress.is_cancelled() {
+                    info!("Loading accelerometer data for session {} cancelled after {} rows", session_id, data.len());
+                    return Ok(None);
+                }
+            }
         }
+        progress.add_loaded_rows(data.len() % LOAD_BATCH_ROWS);
         
-        Ok(data)
+        Ok(Some(data))
+    }
+
+    // 以游标方式逐行读取指定session的6轴数据并累计统计量，内存占用与行数无关
+    pub fn get_session_statistics(&self, session_id: &str) -> DuckResult<SessionStatistics> {
+        let mut stmt = self.conn.prepare(
+            "SELECT x, y, z, gx, gy, gz FROM accelerometer_data WHERE session_id = ?"
+        )?;
+
+        let rows = stmt.query_map([session_id], |row| {
+            Ok([
+                row.get::<_, f64>(0)?,
+                row.get::<_, f64>(1)?,
+                row.get::<_, f64>(2)?,
+                row.get::<_, f64>(3)?,
+                row.get::<_, f64>(4)?,
+                row.get::<_, f64>(5)?,
+            ])
+        })?;
+
+        compute_online_statistics(session_id, rows)
+    }
+
+    // 获取指定session音频的主机接收时间相对设备时间的平均偏差（毫秒），没有接收时间戳时返回None
+    pub fn get_audio_arrival_offset_ms(&self, session_id: &str) -> DuckResult<Option<i64>> {
+        self.conn.query_row(
+            "SELECT CAST(ROUND(AVG(arrival_start_timestamp_ms - start_timestamp_ms)) AS BIGINT) FROM audio_data 
+             WHERE session_id = ? AND arrival_start_timestamp_ms IS NOT NULL",
+            [session_id],
+            |row| row.get::<_, Option<i64>>(0),
+        )
     }
 
     // 获取指定session的音频数据
     pub fn get_audio_data_by_session(&self, session_id: &str) -> DuckResult<Vec<(i64, i64, Vec<f64>, u32, u8, String)>> {
         let mut data = Vec::new();
-        
+        self.for_each_audio_block_by_session(session_id, |block| {
+            data.push(block);
+            true
+        })?;
+        Ok(data)
+    }
+
+    // 只获取指定session各音频块的起止时间戳（不读取音频内容），跳过规则与get_audio_data_by_session一致
+    pub fn get_audio_block_ranges_by_session(&self, session_id: &str) -> DuckResult<Vec<(i64, i64)>> {
         let mut stmt = self.conn.prepare(
-            "SELECT start_timestamp_ms, end_timestamp_ms, audio_blob, sample_rate, channels, format FROM audio_data 
+            "SELECT start_timestamp_ms, end_timestamp_ms FROM audio_data 
+             WHERE session_id = ? 
+               AND (format <> 'PCM_16' OR OCTET_LENGTH(audio_blob) = COALESCE(samples_count, -1) * 2)
+             ORDER BY start_timestamp_ms"
+        )?;
+
+        let rows = stmt.query_map([session_id], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)))?;
+        rows.collect()
+    }
+
+    // 逐块读取并解码指定session的音频数据，每次只在内存中保留一个块
+    // 回调返回false时停止读取
+    pub fn for_each_audio_block_by_session<F>(&self, session_id: &str, mut on_block: F) -> DuckResult<()>
+    where
+        F: FnMut((i64, i64, Vec<f64>, u32, u8, String)) -> bool,
+    {
+        let mut stmt = self.conn.prepare(
+            "SELECT start_timestamp_ms, end_timestamp_ms, audio_blob, sample_rate, channels, format, samples_count FROM audio_data 
              WHERE session_id = ? 
              ORDER BY start_timestamp_ms"
         )?;
@@ -541,25 +851,123 @@ impl DatabaseManager {
             let sample_rate: i32 = row.get(3)?;
             let channels: i32 = row.get(4)?;
             let format: String = row.get(5)?;
-            
-            // 将音频字节数据转换回f64样本
-            let mut samples = Vec::new();
-            for chunk in audio_blob.chunks_exact(2) {
-                let sample_i16 = i16::from_le_bytes([chunk[0], chunk[1]]);
-                let sample_f64 = sample_i16 as f64 / 32767.0;
-                samples.push(sample_f64);
+            let samples_count: Option<i32> = row.get(6)?;
+
+            // 校验PCM_16音频块长度，损坏或写入不完整的块直接跳过
+            if format == "PCM_16" {
+                let expected_len = samples_count.unwrap_or(-1) as i64 * 2;
+                if audio_blob.len() as i64 != expected_len {
+                    warn!("Skipping corrupted audio block in session {} at {}: blob is {} bytes, expected {} ({} samples)",
+                          session_id, start_timestamp, audio_blob.len(), expected_len, samples_count.unwrap_or(0));
+                    return Ok(None);
+                }
             }
             
-            Ok((start_timestamp, end_timestamp, samples, sample_rate as u32, channels as u8, format))
+            // 将音频字节数据转换回f64样本，FLAC块先解码为i16样本
+            let samples = if format == FLAC_FORMAT {
+                match decode_flac(&audio_blob) {
+                    Ok(samples_i16) => samples_i16.into_iter().map(|sample| sample as f64 / 32767.0).collect(),
+                    Err(e) => {
+                        warn!("Skipping undecodable audio block in session {} at {}: {}", session_id, start_timestamp, e);
+                        return Ok(None);
+                    }
+                }
+            } else {
+                let mut samples = Vec::with_capacity(audio_blob.len() / 2);
+                for chunk in audio_blob.chunks_exact(2) {
+                    let sample_i16 = i16::from_le_bytes([chunk[0], chunk[1]]);
+                    let sample_f64 = sample_i16 as f64 / 32767.0;
+                    samples.push(sample_f64);
+                }
+                samples
+            };
+            
+            Ok(Some((start_timestamp, end_timestamp, samples, sample_rate as u32, channels as u8, format)))
         })?;
         
         for row in rows {
-            data.push(row?);
+            if let Some(block) = row? {
+                if !on_block(block) {
+                    break;
+                }
+            }
         }
         
-        Ok(data)
+        Ok(())
+    }
+
+
+    // 获取数据库占用的磁盘空间（含WAL文件），无法取得文件路径（如内存数据库）时按已分配的块数估算
+    pub fn get_database_size_bytes(&self) -> DuckResult<u64> {
+        let path: Option<String> = self.conn.query_row(
+            "SELECT path FROM duckdb_databases() WHERE database_name = current_database()",
+            [],
+            |row| row.get(0),
+        )?;
+
+        if let Some(metadata) = path.as_deref().filter(|path| !path.is_empty()).and_then(|path| fs::metadata(path).ok()) {
+            let wal_size = path
+                .and_then(|path| fs::metadata(format!("{}.wal", path)).ok())
+                .map_or(0, |wal| wal.len());
+            return Ok(metadata.len() + wal_size);
+        }
+
+        let size: i64 = self.conn.query_row(
+            "SELECT CAST(total_blocks * block_size AS BIGINT) FROM pragma_database_size() WHERE database_name = current_database()",
+            [],
+            |row| row.get(0),
+        )?;
+
+        Ok(size.max(0) as u64)
     }
 
+    // 获取指定session的音频数据总字节数
+    pub fn get_audio_data_size_bytes(&self, session_id: &str) -> DuckResult<u64> {
+        let size: i64 = self.conn.query_row(
+            "SELECT CAST(COALESCE(SUM(OCTET_LENGTH(audio_blob)), 0) AS BIGINT) FROM audio_data WHERE session_id = ?",
+            [session_id],
+            |row| row.get(0),
+        )?;
+
+        Ok(size.max(0) as u64)
+    }
+
+    // 获取指定session的音频样本总数（与存储格式无关）
+    pub fn get_audio_sample_count(&self, session_id: &str) -> DuckResult<u64> {
+        let count: i64 = self.conn.query_row(
+            "SELECT CAST(COALESCE(SUM(samples_count), 0) AS BIGINT) FROM audio_data WHERE session_id = ?",
+            [session_id],
+            |row| row.get(0),
+        )?;
+
+        Ok(count.max(0) as u64)
+    }
+
+    // 获取指定session音频的采样率，没有音频数据时返回默认采样率
+    pub fn get_audio_sample_rate_for_session(&self, session_id: &str) -> DuckResult<u32> {
+        let result = self.conn.query_row(
+            "SELECT DISTINCT sample_rate FROM audio_data WHERE session_id = ? LIMIT 1",
+            [session_id],
+            |row| row.get::<_, i32>(0),
+        );
+
+        match result {
+            Ok(rate) if rate > 0 => Ok(rate as u32),
+            Ok(_) | Err(duckdb::Error::QueryReturnedNoRows) => Ok(DEFAULT_AUDIO_SAMPLE_RATE),
+            Err(e) => Err(e),
+        }
+    }
+
+    // 获取指定session的加速度数据行数
+    pub fn get_accelerometer_row_count(&self, session_id: &str) -> DuckResult<usize> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM accelerometer_data WHERE session_id = ?",
+            [session_id],
+            |row| row.get(0),
+        )?;
+
+        Ok(count.max(0) as usize)
+    }
 
     // 标记session为已导出（现在不需要，因为通过文件存在性检查）
     pub fn mark_session_exported(&self, _session_id: &str) -> DuckResult<()> {
@@ -567,6 +975,93 @@ impl DatabaseManager {
         Ok(())
     }
 
+    // 批量重命名指定用户的场景，返回更新的记录数
+    pub fn rename_scenario(&self, old_name: &str, new_name: &str, username: &str) -> DuckResult<usize> {
+        let acc_updated = self.conn.execute(
+            "UPDATE accelerometer_data SET scenario = ? WHERE scenario = ? AND username = ?",
+            [new_name, old_name, username],
+        )?;
+
+        // audio_data表没有scenario列，音频通过session_id关联到加速度数据，随之生效
+
+        info!("Renamed scenario '{}' to '{}' for user {}: {} accelerometer records",
+              old_name, new_name, username, acc_updated);
+
+        Ok(acc_updated)
+    }
+
+    // 把session的所有加速度和音频数据复制到新生成的session ID下，返回新ID
+    pub fn duplicate_session(&self, session_id: &str) -> DuckResult<String> {
+        // session ID精确到秒，同一秒内重复复制时追加序号避免与已有session重名
+        let base_session_id = generate_session_id();
+        let mut new_session_id = base_session_id.clone();
+        let mut suffix = 2;
+        while self.session_exists(&new_session_id)? {
+            new_session_id = format!("{}_{}", base_session_id, suffix);
+            suffix += 1;
+        }
+
+        let acc_copied = self.conn.execute(
+            "INSERT INTO accelerometer_data (timestamp_ms, x, y, z, gx, gy, gz, session_id, username, scenario,
+                                             qw, qx, qy, qz, node_id, arrival_timestamp_ms, temperature_c)
+             SELECT timestamp_ms, x, y, z, gx, gy, gz, ?, username, scenario,
+                    qw, qx, qy, qz, node_id, arrival_timestamp_ms, temperature_c
+             FROM accelerometer_data WHERE session_id = ? ORDER BY id",
+            [new_session_id.as_str(), session_id],
+        )?;
+
+        let audio_copied = self.conn.execute(
+            "INSERT INTO audio_data (start_timestamp_ms, end_timestamp_ms, sample_rate, channels, format, samples_count,
+                                     audio_blob, session_id, username, arrival_start_timestamp_ms, arrival_end_timestamp_ms)
+             SELECT start_timestamp_ms, end_timestamp_ms, sample_rate, channels, format, samples_count,
+                    audio_blob, ?, username, arrival_start_timestamp_ms, arrival_end_timestamp_ms
+             FROM audio_data WHERE session_id = ? ORDER BY id",
+            [new_session_id.as_str(), session_id],
+        )?;
+
+        info!("Duplicated session {} as {}: {} accelerometer records, {} audio records",
+              session_id, new_session_id, acc_copied, audio_copied);
+
+        Ok(new_session_id)
+    }
+
+    fn session_exists(&self, session_id: &str) -> DuckResult<bool> {
+        self.conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM accelerometer_data WHERE session_id = ?)
+                 OR EXISTS (SELECT 1 FROM audio_data WHERE session_id = ?)",
+            [session_id, session_id],
+            |row| row.get(0),
+        )
+    }
+
+    // 向审计日志追加一条操作记录（保存、删除、导出），审计日志表只追加，不提供删除接口
+    pub fn audit_log(&self, action: &str, session_id: &str, user: &str) -> DuckResult<()> {
+        self.conn.execute(
+            "INSERT INTO audit_log (action, session_id, username, occurred_at) VALUES (?, ?, ?, CURRENT_TIMESTAMP)",
+            [action, session_id, user],
+        )?;
+        Ok(())
+    }
+
+    // 设置session的审阅标记
+    pub fn set_session_reviewed(&self, session_id: &str, reviewed: bool) -> DuckResult<()> {
+        self.conn.execute(
+            "INSERT INTO session_metadata (session_id, reviewed) VALUES (?, ?)
+             ON CONFLICT (session_id) DO UPDATE SET reviewed = excluded.reviewed",
+            duckdb::params![session_id, reviewed],
+        )?;
+        Ok(())
+    }
+
+    // 获取所有已审阅的session
+    pub fn get_reviewed_sessions(&self) -> DuckResult<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT session_id FROM session_metadata WHERE reviewed ORDER BY session_id",
+        )?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        rows.collect()
+    }
+
     // 删除指定session的所有数据
     pub fn delete_session(&self, session_id: &str) -> DuckResult<usize> {
         let mut total_deleted = 0;
@@ -584,15 +1079,230 @@ impl DatabaseManager {
             [session_id],
         )?;
         total_deleted += audio_deleted;
+
+        self.conn.execute(
+            "DELETE FROM session_metadata WHERE session_id = ?",
+            [session_id],
+        )?;
         
         info!("Deleted session {}: {} accelerometer records, {} audio records", 
               session_id, acc_deleted, audio_deleted);
         
         Ok(total_deleted)
     }
+
+    // 扫描所有session的数据问题：缺少某类数据、时间戳倒退、空音频块、音频长度不符、采样率不合理
+    pub fn check_integrity(&self) -> DuckResult<IntegrityReport> {
+        let mut report = IntegrityReport {
+            sessions_checked: self.get_all_sessions()?.len(),
+            issues: Vec::new(),
+        };
+        let mut push = |session_id: String, kind: IntegrityIssueKind, detail: String| {
+            report.issues.push(IntegrityIssue { session_id, kind, detail });
+        };
+
+        for (session_id, _) in self.query_session_counts(
+            "SELECT session_id, COUNT(*) FROM accelerometer_data acc
+             WHERE NOT EXISTS (SELECT 1 FROM audio_data audio WHERE audio.session_id = acc.session_id)
+             GROUP BY session_id"
+        )? {
+            push(session_id, IntegrityIssueKind::MissingAudio, "Accelerometer data without any audio blocks".to_string());
+        }
+
+        for (session_id, _) in self.query_session_counts(
+            "SELECT session_id, COUNT(*) FROM audio_data audio
+             WHERE NOT EXISTS (SELECT 1 FROM accelerometer_data acc WHERE acc.session_id = audio.session_id)
+             GROUP BY session_id"
+        )? {
+            push(session_id, IntegrityIssueKind::MissingAccelerometer, "Audio blocks without any accelerometer data".to_string());
+        }
+
+        // 按写入顺序比较相邻数据点的时间戳；自动保存的窗口互相重叠时同一时间戳会被写入多次，
+        // 只保留每个时间戳第一次写入的那一行，否则每个新窗口的开头都会被误判为倒退
+        for (session_id, count) in self.query_session_counts(
+            "SELECT session_id, COUNT(*) FROM (
+                 SELECT session_id, timestamp_ms, LAG(timestamp_ms) OVER (PARTITION BY session_id ORDER BY first_id) AS previous_ms
+                 FROM (
+                     SELECT session_id, timestamp_ms, MIN(id) AS first_id
+                     FROM accelerometer_data
+                     GROUP BY session_id, timestamp_ms
+                 )
+             ) WHERE timestamp_ms < previous_ms
+             GROUP BY session_id"
+        )? {
+            push(session_id, IntegrityIssueKind::NonMonotonicTimestamps, format!("{} accelerometer timestamps go backwards", count));
+        }
+
+        for (session_id, count) in self.query_session_counts(
+            "SELECT session_id, COUNT(*) FROM audio_data
+             WHERE end_timestamp_ms < start_timestamp_ms
+             GROUP BY session_id"
+        )? {
+            push(session_id, IntegrityIssueKind::NonMonotonicTimestamps, format!("{} audio blocks end before they start", count));
+        }
+
+        for (session_id, count) in self.query_session_counts(
+            "SELECT session_id, COUNT(*) FROM audio_data
+             WHERE COALESCE(samples_count, 0) = 0 OR COALESCE(OCTET_LENGTH(audio_blob), 0) = 0
+             GROUP BY session_id"
+        )? {
+            push(session_id, IntegrityIssueKind::EmptyAudioBlob, format!("{} audio blocks have no samples", count));
+        }
+
+        // 与读取时的校验一致，长度不符的块在加载和导出时会被跳过
+        for (session_id, count) in self.query_session_counts(
+            "SELECT session_id, COUNT(*) FROM audio_data
+             WHERE format = 'PCM_16' AND OCTET_LENGTH(audio_blob) > 0
+               AND OCTET_LENGTH(audio_blob) != COALESCE(samples_count, 0) * 2
+             GROUP BY session_id"
+        )? {
+            push(session_id, IntegrityIssueKind::BlobLengthMismatch, format!("{} audio blocks do not match their sample count", count));
+        }
+
+        let mut stmt = self.conn.prepare(
+            "SELECT session_id, MIN(sample_rate), MAX(sample_rate) FROM audio_data
+             GROUP BY session_id
+             HAVING MIN(sample_rate) < ? OR MAX(sample_rate) > ?"
+        )?;
+        let rows = stmt.query_map([PLAUSIBLE_AUDIO_SAMPLE_RATE.start(), PLAUSIBLE_AUDIO_SAMPLE_RATE.end()], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, i64>(2)?))
+        })?;
+        for row in rows {
+            let (session_id, min_rate, max_rate) = row?;
+            push(session_id, IntegrityIssueKind::ImplausibleSampleRate, format!("Audio sample rate {}-{} Hz", min_rate, max_rate));
+        }
+
+        let mut stmt = self.conn.prepare(
+            "SELECT session_id, COUNT(*), MIN(timestamp_ms), MAX(timestamp_ms) FROM accelerometer_data
+             GROUP BY session_id
+             HAVING COUNT(*) > 1"
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, i64>(2)?, row.get::<_, i64>(3)?))
+        })?;
+        for row in rows {
+            let (session_id, count, first_ms, last_ms) = row?;
+            let duration_ms = last_ms - first_ms;
+            let sample_rate = if duration_ms > 0 { (count - 1) as f64 * 1000.0 / duration_ms as f64 } else { f64::INFINITY };
+            if !PLAUSIBLE_ACC_SAMPLE_RATE.contains(&sample_rate) {
+                push(session_id, IntegrityIssueKind::ImplausibleSampleRate, format!("Accelerometer sample rate {:.1} Hz", sample_rate));
+            }
+        }
+
+        report.issues.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+        info!("Integrity check: {} issues in {} sessions", report.issues.len(), report.sessions_checked);
+        Ok(report)
+    }
+
+    // 执行返回 (session_id, 计数) 的查询
+    fn query_session_counts(&self, sql: &str) -> DuckResult<Vec<(String, i64)>> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))?;
+        rows.collect()
+    }
+
+    // 重新收集表统计信息，大量删除后统计过时会使查询计划变差
+    pub fn reindex_sessions(&self) -> DuckResult<()> {
+        self.conn.execute_batch("ANALYZE accelerometer_data; ANALYZE audio_data;")?;
+        info!("Rebuilt table statistics for accelerometer_data and audio_data");
+        Ok(())
+    }
+}
+
+/// 扫描 data_export/<用户>/<场景>/<session>.csv，返回 "用户/场景/session" 集合
+fn collect_exported_files() -> std::collections::HashSet<String> {
+    let mut exported = std::collections::HashSet::new();
+    let Ok(user_dirs) = fs::read_dir("data_export") else {
+        return exported;
+    };
+
+    for user_dir in user_dirs.flatten() {
+        let username = user_dir.file_name().to_string_lossy().to_string();
+        let Ok(scenario_dirs) = fs::read_dir(user_dir.path()) else {
+            continue;
+        };
+
+        for scenario_dir in scenario_dirs.flatten() {
+            let scenario = scenario_dir.file_name().to_string_lossy().to_string();
+            let Ok(files) = fs::read_dir(scenario_dir.path()) else {
+                continue;
+            };
+
+            for file in files.flatten() {
+                let path = file.path();
+                if path.extension().is_some_and(|ext| ext == "csv") {
+                    if let Some(stem) = path.file_stem() {
+                        exported.insert(format!("{}/{}/{}", username, scenario, stem.to_string_lossy()));
+                    }
+                }
+            }
+        }
+    }
+
+    exported
+}
+
+/// 生成带时间戳的新数据库文件路径，用于原文件无法打开时的回退
+pub fn fallback_database_path() -> String {
+    format!("data/sensor_data_{}.db", Utc::now().format("%Y%m%d_%H%M%S"))
 }
 
 pub fn generate_session_id() -> String {
     use chrono::Utc;
     format!("session_{}", Utc::now().format("%Y%m%d_%H%M%S"))
 }
+
+/// 查询内置DuckDB库的版本号
+pub fn duckdb_version() -> String {
+    Connection::open_in_memory()
+        .and_then(|conn| conn.query_row("SELECT version()", [], |row| row.get::<_, String>(0)))
+        .unwrap_or_else(|e| format!("unknown ({})", e))
+}
+
+/// 四个四元数分量都存在时才视为有效的姿态数据
+fn orientation_from_columns(qw: Option<f64>, qx: Option<f64>, qy: Option<f64>, qz: Option<f64>) -> Option<[f64; 4]> {
+    Some([qw?, qx?, qy?, qz?])
+}
+
+/// 用Welford在线算法逐行累计各轴的均值和标准差，不保存原始数据
+fn compute_online_statistics(session_id: &str, rows: impl Iterator<Item = DuckResult<[f64; 6]>>) -> DuckResult<SessionStatistics> {
+    let mut statistics = SessionStatistics::new(session_id.to_string());
+    for row in rows {
+        statistics.push(row?);
+    }
+    Ok(statistics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(timestamps: std::ops::Range<i64>) -> Vec<DataPoint> {
+        timestamps.map(|t| DataPoint::new(0.0, 0.0, 9.81, 0.0, 0.0, 0.0, t)).collect()
+    }
+
+    fn non_monotonic_sessions(db_manager: &DatabaseManager) -> Vec<String> {
+        db_manager.check_integrity().unwrap().issues.into_iter()
+            .filter(|issue| issue.kind == IntegrityIssueKind::NonMonotonicTimestamps)
+            .map(|issue| issue.session_id)
+            .collect()
+    }
+
+    #[test]
+    fn overlapping_save_windows_are_not_reported_as_going_backwards() {
+        let db_manager = DatabaseManager::open(":memory:").unwrap();
+        db_manager.save_accelerometer_data(&points(1000..1100), "overlap", "tester", "standard").unwrap();
+        db_manager.save_accelerometer_data(&points(1050..1150), "overlap", "tester", "standard").unwrap();
+
+        assert!(non_monotonic_sessions(&db_manager).is_empty());
+    }
+
+    #[test]
+    fn timestamps_going_backwards_are_reported() {
+        let db_manager = DatabaseManager::open(":memory:").unwrap();
+        db_manager.save_accelerometer_data(&points(2000..2100), "backwards", "tester", "standard").unwrap();
+        db_manager.save_accelerometer_data(&points(1000..1050), "backwards", "tester", "standard").unwrap();
+
+        assert_eq!(non_monotonic_sessions(&db_manager), vec!["backwards".to_string()]);
+    }
+}
@@ -0,0 +1,75 @@
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+/// 导出清单文件路径
+const MANIFEST_JSON_PATH: &str = "data_export/manifest.json";
+const MANIFEST_CSV_PATH: &str = "data_export/manifest.csv";
+
+/// 导出清单中的一条记录，对应一个已导出的session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportManifestEntry {
+    pub session_id: String,
+    pub username: String,
+    pub scenario: String,
+    pub row_count: usize,
+    pub duration_ms: i64,
+    pub exported_at: String,
+    pub file_path: String,
+}
+
+/// 将本次导出的记录合并进 data_export/manifest.json 和 manifest.csv
+/// 已存在的session记录会被新记录覆盖，清单按session ID排序
+pub fn update_export_manifest(entries: &[ExportManifestEntry]) -> Result<(), String> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    let mut manifest = load_manifest();
+    for entry in entries {
+        match manifest.iter_mut().find(|existing| existing.session_id == entry.session_id) {
+            Some(existing) => *existing = entry.clone(),
+            None => manifest.push(entry.clone()),
+        }
+    }
+    manifest.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+
+    std::fs::create_dir_all("data_export")
+        .map_err(|e| format!("Failed to create export directory: {}", e))?;
+
+    let json = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    std::fs::write(MANIFEST_JSON_PATH, json)
+        .map_err(|e| format!("Failed to write {}: {}", MANIFEST_JSON_PATH, e))?;
+
+    write_manifest_csv(&manifest)?;
+
+    info!("Export manifest updated: {} new/updated entries, {} total", entries.len(), manifest.len());
+    Ok(())
+}
+
+/// 读取已有清单，文件不存在或损坏时从空清单开始
+fn load_manifest() -> Vec<ExportManifestEntry> {
+    let content = match std::fs::read_to_string(MANIFEST_JSON_PATH) {
+        Ok(content) => content,
+        Err(_) => return Vec::new(),
+    };
+
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        warn!("Failed to parse {}, rebuilding manifest: {}", MANIFEST_JSON_PATH, e);
+        Vec::new()
+    })
+}
+
+/// 表头取自字段名，含逗号、引号或换行的字段由csv按规则加引号
+fn write_manifest_csv(manifest: &[ExportManifestEntry]) -> Result<(), String> {
+    let mut writer = csv::WriterBuilder::new()
+        .from_path(MANIFEST_CSV_PATH)
+        .map_err(|e| format!("Failed to create {}: {}", MANIFEST_CSV_PATH, e))?;
+
+    for entry in manifest {
+        writer.serialize(entry)
+            .map_err(|e| format!("Failed to write manifest entry: {}", e))?;
+    }
+
+    writer.flush().map_err(|e| format!("Failed to flush manifest: {}", e))
+}
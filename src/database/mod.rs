@@ -2,7 +2,13 @@ pub mod manager;
 pub mod schema;
 pub mod handlers;
 pub mod tasks;
+pub mod manifest;
+pub mod status;
+pub mod audio_codec;
+pub mod checksum;
 
-pub use manager::generate_session_id;
+pub use manager::{generate_session_id, duckdb_version};
+pub use status::{DatabaseChannelHandoff, DatabaseOpenError, DatabaseStatus, SharedDatabaseStatus};
 pub use handlers::{run_database_handler, handle_export_request};
-pub use tasks::{export_session_to_csv_internal, align_session_data_internal};
+pub use tasks::{export_session_to_csv_internal, export_session_range_internal, export_sessions_comparison_internal, align_session_data_internal};
+pub use manifest::update_export_manifest;
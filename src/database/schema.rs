@@ -11,6 +11,10 @@ impl DatabaseSchema {
         Self::migrate_accelerometer_table(conn)?;
         Self::migrate_username_columns(conn)?;
         Self::migrate_scenario_column(conn)?;
+        Self::migrate_orientation_columns(conn)?;
+        Self::migrate_node_id_column(conn)?;
+        Self::migrate_arrival_timestamp_columns(conn)?;
+        Self::migrate_temperature_column(conn)?;
 
         info!("Database migration completed successfully");
         Ok(())
@@ -56,6 +60,32 @@ impl DatabaseSchema {
             [],
         )?;
 
+        // 审计日志：记录保存、删除、导出操作，只追加不删除
+        conn.execute(
+            "CREATE SEQUENCE IF NOT EXISTS audit_log_seq",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS audit_log (
+                id INTEGER PRIMARY KEY DEFAULT nextval('audit_log_seq'),
+                action VARCHAR,
+                session_id VARCHAR,
+                username VARCHAR,
+                occurred_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+            )",
+            [],
+        )?;
+
+        // 会话元数据（人工审阅标记等）
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS session_metadata (
+                session_id VARCHAR PRIMARY KEY,
+                reviewed BOOLEAN DEFAULT FALSE
+            )",
+            [],
+        )?;
+
         Ok(())
     }
 
@@ -180,4 +210,122 @@ impl DatabaseSchema {
             }
         }
     }
-}
\ No newline at end of file
+
+    fn migrate_orientation_columns(conn: &Connection) -> DuckResult<()> {
+        let has_orientation = Self::check_orientation_columns_exist(conn)?;
+
+        if !has_orientation {
+            info!("Adding orientation quaternion columns to accelerometer_data table");
+
+            conn.execute("ALTER TABLE accelerometer_data ADD COLUMN qw DOUBLE DEFAULT NULL", [])?;
+            conn.execute("ALTER TABLE accelerometer_data ADD COLUMN qx DOUBLE DEFAULT NULL", [])?;
+            conn.execute("ALTER TABLE accelerometer_data ADD COLUMN qy DOUBLE DEFAULT NULL", [])?;
+            conn.execute("ALTER TABLE accelerometer_data ADD COLUMN qz DOUBLE DEFAULT NULL", [])?;
+
+            info!("Successfully added orientation columns");
+        } else {
+            info!("Orientation columns already exist in accelerometer_data table");
+        }
+
+        Ok(())
+    }
+
+    fn check_orientation_columns_exist(conn: &Connection) -> DuckResult<bool> {
+        let result = conn.execute("SELECT qw, qx, qy, qz FROM accelerometer_data LIMIT 1", []);
+
+        match result {
+            Ok(_) => {
+                info!("Orientation columns found in database");
+                Ok(true)
+            },
+            Err(_) => {
+                info!("Orientation columns not found in database");
+                Ok(false)
+            }
+        }
+    }
+
+    fn migrate_node_id_column(conn: &Connection) -> DuckResult<()> {
+        let has_node_id = Self::check_node_id_column_exists(conn)?;
+
+        if !has_node_id {
+            info!("Adding node_id column to accelerometer_data table");
+            conn.execute("ALTER TABLE accelerometer_data ADD COLUMN node_id VARCHAR DEFAULT NULL", [])?;
+            info!("Successfully added node_id column to accelerometer_data table");
+        } else {
+            info!("Node_id column already exists in accelerometer_data table");
+        }
+
+        Ok(())
+    }
+
+    fn check_node_id_column_exists(conn: &Connection) -> DuckResult<bool> {
+        let result = conn.execute("SELECT node_id FROM accelerometer_data LIMIT 1", []);
+
+        match result {
+            Ok(_) => {
+                info!("Node_id column found in accelerometer_data table");
+                Ok(true)
+            },
+            Err(_) => {
+                info!("Node_id column not found in accelerometer_data table");
+                Ok(false)
+            }
+        }
+    }
+
+    fn migrate_temperature_column(conn: &Connection) -> DuckResult<()> {
+        let has_temperature = Self::check_temperature_column_exists(conn)?;
+
+        if !has_temperature {
+            info!("Adding temperature_c column to accelerometer_data table");
+            conn.execute("ALTER TABLE accelerometer_data ADD COLUMN temperature_c DOUBLE DEFAULT NULL", [])?;
+            info!("Successfully added temperature_c column to accelerometer_data table");
+        } else {
+            info!("Temperature_c column already exists in accelerometer_data table");
+        }
+
+        Ok(())
+    }
+
+    fn check_temperature_column_exists(conn: &Connection) -> DuckResult<bool> {
+        let result = conn.execute("SELECT temperature_c FROM accelerometer_data LIMIT 1", []);
+
+        match result {
+            Ok(_) => {
+                info!("Temperature_c column found in accelerometer_data table");
+                Ok(true)
+            },
+            Err(_) => {
+                info!("Temperature_c column not found in accelerometer_data table");
+                Ok(false)
+            }
+        }
+    }
+
+    fn migrate_arrival_timestamp_columns(conn: &Connection) -> DuckResult<()> {
+        if !Self::check_arrival_timestamp_column_exists(conn, "accelerometer_data", "arrival_timestamp_ms") {
+            info!("Adding arrival timestamp column to accelerometer_data table");
+            conn.execute("ALTER TABLE accelerometer_data ADD COLUMN arrival_timestamp_ms BIGINT DEFAULT NULL", [])?;
+        }
+
+        if !Self::check_arrival_timestamp_column_exists(conn, "audio_data", "arrival_start_timestamp_ms, arrival_end_timestamp_ms") {
+            info!("Adding arrival timestamp columns to audio_data table");
+            conn.execute("ALTER TABLE audio_data ADD COLUMN arrival_start_timestamp_ms BIGINT DEFAULT NULL", [])?;
+            conn.execute("ALTER TABLE audio_data ADD COLUMN arrival_end_timestamp_ms BIGINT DEFAULT NULL", [])?;
+        }
+
+        Ok(())
+    }
+
+    fn check_arrival_timestamp_column_exists(conn: &Connection, table_name: &str, columns: &str) -> bool {
+        let query = format!("SELECT {} FROM {} LIMIT 1", columns, table_name);
+        let exists = conn.execute(&query, []).is_ok();
+        if exists {
+            info!("Arrival timestamp columns found in {} table", table_name);
+        } else {
+            info!("Arrival timestamp columns not found in {} table", table_name);
+        }
+        exists
+    }
+}
@@ -0,0 +1,96 @@
+use std::sync::{Arc, Mutex};
+use crossbeam_channel::{Receiver, Sender};
+use crate::types::{DatabaseTask, SaveResult};
+
+/// 打开数据库失败的原因
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum DatabaseOpenError {
+    #[error("Database file is locked by another process: {0}")]
+    Locked(String),
+    #[error("Database file is corrupt or from an incompatible DuckDB version: {0}")]
+    Incompatible(String),
+    #[error("Failed to open database: {0}")]
+    Other(String),
+}
+
+impl DatabaseOpenError {
+    /// 根据DuckDB的错误信息区分文件被锁定、文件损坏/版本不兼容和其他错误
+    pub fn classify(error: &duckdb::Error) -> Self {
+        let message = error.to_string();
+        let lower = message.to_lowercase();
+
+        if lower.contains("lock") {
+            DatabaseOpenError::Locked(message)
+        } else if lower.contains("version")
+            || lower.contains("serialization")
+            || lower.contains("not a valid duckdb database")
+            || lower.contains("corrupt")
+        {
+            DatabaseOpenError::Incompatible(message)
+        } else {
+            DatabaseOpenError::Other(message)
+        }
+    }
+
+    /// 文件锁定通常是暂时的（例如另一个实例正在退出），值得重试
+    pub fn is_transient(&self) -> bool {
+        matches!(self, DatabaseOpenError::Locked(_))
+    }
+}
+
+/// 数据库线程的连接状态，供GUI显示
+#[derive(Debug, Clone)]
+pub enum DatabaseStatus {
+    Opening,
+    Ready { path: String },
+    Failed(DatabaseOpenError),
+}
+
+/// GUI与数据库线程共享的连接状态
+#[derive(Debug, Clone)]
+pub struct SharedDatabaseStatus(Arc<Mutex<DatabaseStatus>>);
+
+impl SharedDatabaseStatus {
+    pub fn new() -> Self {
+        Self(Arc::new(Mutex::new(DatabaseStatus::Opening)))
+    }
+
+    pub fn get(&self) -> DatabaseStatus {
+        self.0.lock().unwrap().clone()
+    }
+
+    pub fn set(&self, status: DatabaseStatus) {
+        *self.0.lock().unwrap() = status;
+    }
+}
+
+impl Default for SharedDatabaseStatus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// GUI一侧的数据库通道：任务发送端和保存结果接收端
+pub type DatabaseChannels = (Sender<DatabaseTask>, Receiver<SaveResult>);
+
+/// 数据库线程重启后新建的通道，由监控线程放入、GUI取走并替换原来已断开的通道
+#[derive(Clone, Default)]
+pub struct DatabaseChannelHandoff(Arc<Mutex<Option<DatabaseChannels>>>);
+
+impl DatabaseChannelHandoff {
+    pub fn publish(&self, task_sender: Sender<DatabaseTask>, result_receiver: Receiver<SaveResult>) {
+        *self.0.lock().unwrap() = Some((task_sender, result_receiver));
+    }
+
+    pub fn take(&self) -> Option<DatabaseChannels> {
+        self.0.lock().unwrap().take()
+    }
+}
+
+impl std::fmt::Debug for DatabaseChannelHandoff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DatabaseChannelHandoff")
+            .field("pending", &self.0.lock().unwrap().is_some())
+            .finish()
+    }
+}
@@ -1,36 +1,116 @@
 use std::io::Write;
-use log::info;
+use log::{info, warn, error};
 
-use crate::types::DataPoint;
+use crate::types::{DataPoint, DEFAULT_AUDIO_SAMPLE_RATE};
+use crate::config::{ExportConfig, TimestampSource};
 use super::manager::DatabaseManager;
+use super::manifest::ExportManifestEntry;
+use super::checksum::write_sha256_sidecar;
+
+/// 导出文件的根目录，按 用户名/场景 分子目录
+const EXPORT_ROOT: &str = "data_export";
+
+/// 音频片段之间最多补多长的静音（毫秒），session暂停很久后续录时不为整段间隔分配内存
+const MAX_AUDIO_GAP_PADDING_MS: i64 = 10_000;
+
+/// 写入表头并返回写数据行的CSV写入器，两者都使用配置的分隔符；
+/// 表头按配置给列名加双引号，数据行只在字段需要时才加引号
+fn csv_writer<W: Write>(mut output: W, header: &[String], options: &ExportConfig) -> csv::Result<csv::Writer<W>> {
+    let delimiter = options.csv_delimiter as u8;
+    let header_style = if options.csv_quote_strings { csv::QuoteStyle::Always } else { csv::QuoteStyle::Necessary };
+
+    let mut header_writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .quote_style(header_style)
+        .from_writer(&mut output);
+    header_writer.write_record(header)?;
+    header_writer.flush()?;
+    drop(header_writer);
+
+    Ok(csv::WriterBuilder::new().delimiter(delimiter).from_writer(output))
+}
+
+/// 一个数据点的6轴字段加音频字段，缺失的音频留空
+fn imu_fields(point: &DataPoint, audio_sample: Option<f64>) -> Vec<String> {
+    vec![
+        point.x.to_string(),
+        point.y.to_string(),
+        point.z.to_string(),
+        point.gx.to_string(),
+        point.gy.to_string(),
+        point.gz.to_string(),
+        audio_sample.map(|sample| sample.to_string()).unwrap_or_default(),
+    ]
+}
+
+/// 内部导出函数（在数据库线程中运行），返回用于导出清单的记录
+pub fn export_session_to_csv_internal(db_manager: &DatabaseManager, session_id: &str, options: &ExportConfig) -> Result<ExportManifestEntry, String> {
+    export_session_csv(db_manager, EXPORT_ROOT, session_id, options, None)
+}
 
-/// 内部导出函数（在数据库线程中运行）
-pub fn export_session_to_csv_internal(db_manager: &DatabaseManager, session_id: &str) -> Result<(), String> {
+/// 只导出session中的一段时间范围（毫秒，相对第一个加速度数据点），CSV之外另存该段音频的WAV文件
+pub fn export_session_range_internal(db_manager: &DatabaseManager, session_id: &str, start_ms: i64, end_ms: i64, options: &ExportConfig) -> Result<ExportManifestEntry, String> {
+    if end_ms <= start_ms {
+        return Err(format!("Invalid time range: {}ms - {}ms", start_ms, end_ms));
+    }
+    export_session_csv(db_manager, EXPORT_ROOT, session_id, options, Some((start_ms, end_ms)))
+}
+
+/// 导出一个session到export_root下的CSV，range为相对时间范围时只保留范围内的加速度数据和音频样本
+fn export_session_csv(db_manager: &DatabaseManager, export_root: &str, session_id: &str, options: &ExportConfig, range: Option<(i64, i64)>) -> Result<ExportManifestEntry, String> {
     // 获取session对应的用户名
     let username = db_manager.get_username_for_session(session_id)
         .map_err(|e| format!("Failed to get username for session: {}", e))?;
+
+    if let Err(e) = db_manager.audit_log("export", session_id, &username) {
+        error!("Failed to write audit log for exporting session {}: {}", session_id, e);
+    }
     
     // 获取session对应的场景
     let scenario = db_manager.get_scenario_for_session(session_id)
         .map_err(|e| format!("Failed to get scenario for session: {}", e))?;
     // 获取加速度数据
-    let acc_data = db_manager.get_accelerometer_data_by_session(session_id)
+    let mut acc_data = db_manager.get_accelerometer_data_by_session(session_id)
         .map_err(|e| format!("Failed to get accelerometer data: {}", e))?;
 
-    // 获取音频数据
-    let audio_data = db_manager.get_audio_data_by_session(session_id)
+    // 只获取音频块的时间范围，音频样本在写入时再逐块读取，避免大音频一次性载入内存
+    let mut audio_ranges = db_manager.get_audio_block_ranges_by_session(session_id)
         .map_err(|e| format!("Failed to get audio data: {}", e))?;
 
-    if acc_data.is_empty() && audio_data.is_empty() {
+    if acc_data.is_empty() && audio_ranges.is_empty() {
         return Err("No data in session".to_string());
     }
 
-    // 执行数据对齐算法（同时处理加速度计和音频数据）
-    let (aligned_acc_data, trimmed_audio_data, common_time_range_ms) = align_session_data_internal(&acc_data, &audio_data);
+    // 记录调整前的音频起点，流式读取音频块时用于换算调整后的时间戳
+    let raw_audio_start = audio_ranges.first().map(|(start, _)| *start);
+    apply_timestamp_source(db_manager, session_id, &mut acc_data, audio_ranges.iter_mut().map(|(start, end)| (start, end)));
+
+    let acc_sample_rate = estimate_sample_rate(&acc_data);
+
+    // 执行数据对齐算法（与align_session_data_internal对加速度数据的处理一致）
+    let (aligned_acc_data, common_time_range_ms) = match (audio_ranges.first(), audio_ranges.last()) {
+        (Some((audio_first_timestamp, _)), Some((_, audio_last_timestamp))) if !acc_data.is_empty() => {
+            let (aligned_acc_data, time_diff_ms) = align_acc_to_audio_range(&acc_data, *audio_first_timestamp, *audio_last_timestamp);
+            (aligned_acc_data, time_diff_ms.abs())
+        }
+        _ => (acc_data, 0),
+    };
+
+    // 时间范围以第一个加速度数据点为零点（与历史面板的横轴一致），没有加速度数据时以音频起点为零点
+    let audio_offset_ms = audio_ranges.first().zip(raw_audio_start).map_or(0, |((start, _), raw_start)| start - raw_start);
+    let session_start_ms = aligned_acc_data.first().map(|point| point.timestamp)
+        .or_else(|| audio_ranges.first().map(|(start, _)| *start))
+        .unwrap_or(0);
+    let absolute_range = range.map(|(start, end)| (session_start_ms + start, session_start_ms + end));
+    let aligned_acc_data: Vec<DataPoint> = match absolute_range {
+        Some((start, end)) => aligned_acc_data.into_iter()
+            .filter(|point| (start..=end).contains(&point.timestamp))
+            .collect(),
+        None => aligned_acc_data,
+    };
 
     // 确保基础导出目录存在
-    let base_export_dir = "data_export";
-    if let Err(e) = std::fs::create_dir_all(base_export_dir) {
+    if let Err(e) = std::fs::create_dir_all(export_root) {
         return Err(format!("Failed to create base export directory: {}", e));
     }
 
@@ -48,83 +128,232 @@ pub fn export_session_to_csv_internal(db_manager: &DatabaseManager, session_id:
         &scenario
     };
     
-    let export_dir = format!("{}/{}/{}", base_export_dir, user_dir, scenario_dir);
+    let export_dir = format!("{}/{}/{}", export_root, user_dir, scenario_dir);
     if let Err(e) = std::fs::create_dir_all(&export_dir) {
         return Err(format!("Failed to create user/scenario export directory: {}", e));
     }
 
-    // 创建CSV文件
-    let filename = format!("{}/{}.csv", export_dir, session_id);
-    let mut file = std::fs::File::create(&filename)
-        .map_err(|e| format!("Failed to create file: {}", e))?;
+    // 创建CSV文件，时间范围导出在文件名中带上范围，避免覆盖整段导出
+    let file_stem = match range {
+        Some((start, end)) => format!("{}/{}_{}-{}ms", export_dir, session_id, start, end),
+        None => format!("{}/{}", export_dir, session_id),
+    };
+    let filename = format!("{}.csv", file_stem);
+    let mut file = std::io::BufWriter::new(std::fs::File::create(&filename)
+        .map_err(|e| format!("Failed to create file: {}", e))?);
+
+    let exported_at = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+
+    // 可选的元数据注释行，使导出文件自带来源信息
+    if options.csv_metadata_header {
+        let audio_sample_rate = if audio_ranges.is_empty() {
+            "none".to_string()
+        } else {
+            db_manager.get_audio_sample_rate_for_session(session_id)
+                .map_err(|e| format!("Failed to get audio sample rate: {}", e))?
+                .to_string()
+        };
+        let mut metadata = vec![
+            ("session_id", session_id.to_string()),
+            ("username", user_dir.to_string()),
+            ("scenario", scenario_dir.to_string()),
+            ("acc_sample_rate_hz", acc_sample_rate.map(|rate| format!("{:.2}", rate)).unwrap_or_else(|| "unknown".to_string())),
+            ("audio_sample_rate_hz", audio_sample_rate),
+            ("exported_at", exported_at.clone()),
+            ("alignment_offset_ms", common_time_range_ms.to_string()),
+        ];
+        if let Some((start, end)) = range {
+            metadata.push(("range_start_ms", start.to_string()));
+            metadata.push(("range_end_ms", end.to_string()));
+        }
+        for (key, value) in metadata {
+            writeln!(file, "# {}: {}", key, value)
+                .map_err(|e| format!("Failed to write CSV metadata: {}", e))?;
+        }
+    }
 
     // 写入CSV头部
-    writeln!(file, "acc_x,acc_y,acc_z,gyro_x,gyro_y,gyro_z,audio_sample")
+    let header = ["acc_x", "acc_y", "acc_z", "gyro_x", "gyro_y", "gyro_z", "audio_sample"].map(String::from);
+    let mut writer = csv_writer(file, &header, options)
         .map_err(|e| format!("Failed to write CSV header: {}", e))?;
 
-    // 收集所有音频样本到一个向量中
-    let mut all_audio_samples: Vec<f64> = Vec::new();
-    for (_start_timestamp, _end_timestamp, samples, _sample_rate, _channels, _format) in &trimmed_audio_data {
-        all_audio_samples.extend(samples);
+    let acc_count = aligned_acc_data.len();
+    let mut audio_count = 0;
+    let mut write_error = None;
+    let mut past_range = false;
+    let mut range_audio = Vec::new();
+    let mut previous_block_end: Option<i64> = None;
+
+    // 逐块读取音频并边读边写：与加速度计数据重叠的行同时写入两者，
+    // 加速度计数据写完后只写入剩余的音频数据，另一种不补0；块之间的间隔与历史加载一样补静音
+    db_manager.for_each_audio_block_by_session(session_id, |(block_start, block_end, samples, sample_rate, _, _)| {
+        let gap_start = previous_block_end.unwrap_or(block_start);
+        let gap_samples = previous_block_end.map_or(0, |previous_end| audio_gap_samples(previous_end, block_start, sample_rate));
+        previous_block_end = Some(block_end);
+
+        // 按块起点（补静音时按前一块终点）和采样率推算样本时间戳
+        let padding = (1..=gap_samples)
+            .map(|index| (gap_start + (index as f64 * 1000.0 / sample_rate as f64).round() as i64, 0.0));
+        let block = samples.into_iter().enumerate()
+            .map(|(index, sample)| (block_start + (index as f64 * 1000.0 / sample_rate as f64).round() as i64, sample));
+
+        for (sample_timestamp, audio_sample) in padding.chain(block) {
+            // 音频块按起点排序，超过范围终点后不再读取
+            if let Some((start, end)) = absolute_range {
+                let timestamp = sample_timestamp + audio_offset_ms;
+                if timestamp > end {
+                    past_range = true;
+                    break;
+                }
+                if timestamp < start {
+                    continue;
+                }
+                range_audio.push(audio_sample);
+            }
+            let result = match aligned_acc_data.get(audio_count) {
+                Some(point) => writer.write_record(imu_fields(point, Some(audio_sample)))
+                    .map_err(|e| format!("Failed to write combined data: {}", e)),
+                None => {
+                    let mut fields = vec![String::new(); 6];
+                    fields.push(audio_sample.to_string());
+                    writer.write_record(&fields)
+                        .map_err(|e| format!("Failed to write remaining audio data: {}", e))
+                }
+            };
+            if let Err(e) = result {
+                write_error = Some(e);
+                return false;
+            }
+            audio_count += 1;
+        }
+        !past_range
+    }).map_err(|e| format!("Failed to get audio data: {}", e))?;
+
+    if let Some(e) = write_error {
+        return Err(e);
     }
 
-    let acc_count = aligned_acc_data.len();
-    let audio_count = all_audio_samples.len();
-    let min_rows = acc_count.min(audio_count);
-
-    let mut row_count = 0;
-
-    // 前min_rows行：同时写入加速度计和音频数据
-    for i in 0..min_rows {
-        let point = &aligned_acc_data[i];
-        let audio_sample = all_audio_samples[i];
-        writeln!(file, "{},{},{},{},{},{},{}", point.x, point.y, point.z, point.gx, point.gy, point.gz, audio_sample)
-            .map_err(|e| format!("Failed to write combined data: {}", e))?;
-        row_count += 1;
-    }
-
-    // 剩余行：只写入数据多的那一种，另一种不补0
-    if acc_count > audio_count {
-        // 加速度计数据更多，继续写入剩余的加速度计数据
-        for i in min_rows..acc_count {
-            let point = &aligned_acc_data[i];
-            writeln!(file, "{},{},{},{},{},{},", point.x, point.y, point.z, point.gx, point.gy, point.gz)
-                .map_err(|e| format!("Failed to write remaining ACC data: {}", e))?;
-            row_count += 1;
+    // 加速度计数据更多，继续写入剩余的加速度计数据
+    for point in aligned_acc_data.iter().skip(audio_count) {
+        writer.write_record(imu_fields(point, None))
+            .map_err(|e| format!("Failed to write remaining ACC data: {}", e))?;
+    }
+
+    writer.flush()
+        .map_err(|e| format!("Failed to write CSV file: {}", e))?;
+
+    let row_count = acc_count.max(audio_count);
+
+    if range.is_some() {
+        if row_count == 0 {
+            drop(writer);
+            let _ = std::fs::remove_file(&filename);
+            return Err("No data in selected time range".to_string());
         }
-    } else if audio_count > acc_count {
-        // 音频数据更多，继续写入剩余的音频数据
-        for i in min_rows..audio_count {
-            let audio_sample = all_audio_samples[i];
-            writeln!(file, ",,,,,,{}", audio_sample)
-                .map_err(|e| format!("Failed to write remaining audio data: {}", e))?;
-            row_count += 1;
+        if !range_audio.is_empty() {
+            let sample_rate = db_manager.get_audio_sample_rate_for_session(session_id)
+                .map_err(|e| format!("Failed to get audio sample rate: {}", e))?;
+            let wav_filename = format!("{}.wav", file_stem);
+            write_wav(&wav_filename, &range_audio, sample_rate)
+                .map_err(|e| format!("Failed to write WAV file: {}", e))?;
+            info!("Exported {} audio samples of session {} to {}", range_audio.len(), session_id, wav_filename);
         }
     }
 
+    // 写入SHA-256校验文件，便于之后校验导出文件是否完整；失败不影响导出本身
+    drop(writer);
+    match write_sha256_sidecar(&filename, &file_stem) {
+        Ok(sidecar_path) => info!("Wrote checksum for {} to {}", filename, sidecar_path),
+        Err(e) => warn!("Failed to write checksum for {}: {}", filename, e),
+    }
+
     info!("Successfully exported session {} for user '{}' in scenario '{}' to {} ({} rows, common time range: {}ms)", 
           session_id, user_dir, scenario_dir, filename, row_count, common_time_range_ms);
-    Ok(())
+    Ok(ExportManifestEntry {
+        session_id: session_id.to_string(),
+        username: user_dir.to_string(),
+        scenario: scenario_dir.to_string(),
+        row_count,
+        duration_ms: common_time_range_ms,
+        exported_at,
+        file_path: filename,
+    })
 }
 
-/// 内部对齐算法（在数据库线程中运行）
-/// 以音频为基准，通过插值和移动来对齐加速度数据
-pub fn align_session_data_internal(
-    acc_data: &[DataPoint],
-    audio_data: &[(i64, i64, Vec<f64>, u32, u8, String)]
-) -> (Vec<DataPoint>, Vec<(i64, i64, Vec<f64>, u32, u8, String)>, i64) {
-    if acc_data.is_empty() || audio_data.is_empty() {
-        info!("Empty data provided, returning original data");
-        return (acc_data.to_vec(), audio_data.to_vec(), 0);
+/// 把单声道样本写成16位PCM的WAV文件，样本范围与数据库中PCM_16的解码一致（-1.0到1.0）
+fn write_wav(path: &str, samples: &[f64], sample_rate: u32) -> std::io::Result<()> {
+    let data_len = (samples.len() * 2) as u32;
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+
+    file.write_all(b"RIFF")?;
+    file.write_all(&(36 + data_len).to_le_bytes())?;
+    file.write_all(b"WAVE")?;
+    file.write_all(b"fmt ")?;
+    file.write_all(&16u32.to_le_bytes())?;
+    file.write_all(&1u16.to_le_bytes())?; // PCM
+    file.write_all(&1u16.to_le_bytes())?; // 单声道
+    file.write_all(&sample_rate.to_le_bytes())?;
+    file.write_all(&(sample_rate * 2).to_le_bytes())?; // 每秒字节数
+    file.write_all(&2u16.to_le_bytes())?; // 每帧字节数
+    file.write_all(&16u16.to_le_bytes())?; // 位深
+    file.write_all(b"data")?;
+    file.write_all(&data_len.to_le_bytes())?;
+    for sample in samples {
+        let value = (sample.clamp(-1.0, 1.0) * 32767.0).round() as i16;
+        file.write_all(&value.to_le_bytes())?;
     }
+    file.flush()
+}
 
+/// 按数据库线程配置的时间戳来源准备对齐输入：选择主机接收时间时，用接收时间替换设备时间戳
+/// 加速度或音频任一缺少接收时间戳（旧数据或模拟数据源）时整体保留设备时间戳，避免两种时钟混用
+/// 音频只需传入各块起止时间戳的可变引用，既可用于完整的音频块，也可用于只读出时间范围的流式导出
+pub fn apply_timestamp_source<'a>(
+    db_manager: &DatabaseManager,
+    session_id: &str,
+    acc_data: &mut [DataPoint],
+    audio_ranges: impl IntoIterator<Item = (&'a mut i64, &'a mut i64)>,
+) {
+    if db_manager.timestamp_source() != TimestampSource::Arrival {
+        return;
+    }
+
+    let audio_ranges: Vec<(&mut i64, &mut i64)> = audio_ranges.into_iter().collect();
+    let audio_offset_ms = if audio_ranges.is_empty() {
+        Some(0)
+    } else {
+        db_manager.get_audio_arrival_offset_ms(session_id).unwrap_or_else(|e| {
+            warn!("Failed to get audio arrival offset for session {}: {}", session_id, e);
+            None
+        })
+    };
+    let acc_has_arrival = acc_data.iter().all(|point| point.arrival_timestamp.is_some());
+
+    let Some(audio_offset_ms) = audio_offset_ms.filter(|_| acc_has_arrival) else {
+        warn!("Session {} has no arrival timestamps for all data, aligning by device timestamps", session_id);
+        return;
+    };
+
+    for point in acc_data.iter_mut() {
+        if let Some(arrival) = point.arrival_timestamp {
+            point.timestamp = arrival;
+        }
+    }
+    for (start, end) in audio_ranges {
+        *start += audio_offset_ms;
+        *end += audio_offset_ms;
+    }
+    info!("Session {}: aligning by host arrival timestamps (audio offset {}ms)", session_id, audio_offset_ms);
+}
+
+/// 按音频的时间范围平移加速度数据（以音频最后时间戳为基准），返回平移后的数据和时间差（音频 - 加速度）
+/// 调用方需保证加速度数据非空
+fn align_acc_to_audio_range(acc_data: &[DataPoint], audio_first_timestamp: i64, audio_last_timestamp: i64) -> (Vec<DataPoint>, i64) {
     // 获取初始和最后一个数据点的时间戳
     let acc_first_timestamp = acc_data.first().map(|p| p.timestamp).unwrap_or(0);
     let acc_last_timestamp = acc_data.last().map(|p| p.timestamp).unwrap_or(0);
     let acc_duration_ms = acc_last_timestamp - acc_first_timestamp;
 
-    let audio_first_timestamp = audio_data.first().map(|(start, _, _, _, _, _)| *start).unwrap_or(0);
-    let audio_last_timestamp = audio_data.last().map(|(_, end, _, _, _, _)| *end).unwrap_or(0);
     let audio_duration_ms = audio_last_timestamp - audio_first_timestamp;
 
     info!("Timestamp-based alignment:");
@@ -176,6 +405,10 @@ pub fn align_session_data_internal(
                     gy: first_point.gy, // 使用第一个点的gy值
                     gz: first_point.gz, // 使用第一个点的gz值
                     timestamp,
+                    orientation: first_point.orientation,
+                    node_id: first_point.node_id.clone(),
+                    temperature_c: first_point.temperature_c,
+                    arrival_timestamp: None,
                 });
             }
         }
@@ -213,6 +446,10 @@ pub fn align_session_data_internal(
                     gy: last_point.gy,  // 使用最后一个点的gy值
                     gz: last_point.gz,  // 使用最后一个点的gz值
                     timestamp,
+                    orientation: last_point.orientation,
+                    node_id: last_point.node_id.clone(),
+                    temperature_c: last_point.temperature_c,
+                    arrival_timestamp: None,
                 });
             }
         }
@@ -222,19 +459,30 @@ pub fn align_session_data_internal(
         aligned_data
     };
 
-    // 合并所有音频数据到一个连续的向量
-    let mut all_audio_samples = Vec::new();
-    let mut audio_sample_rate = 16000u32;
-    let mut audio_channels = 1u8;
-    let mut audio_format = "PCM_16".to_string();
+    (aligned_acc_data, time_diff_ms)
+}
 
-    for (_, _, samples, sample_rate, channels, format) in audio_data {
-        all_audio_samples.extend(samples);
-        audio_sample_rate = *sample_rate;
-        audio_channels = *channels;
-        audio_format = format.clone();
+/// 内部对齐算法（在数据库线程中运行）
+/// 以音频为基准，通过插值和移动来对齐加速度数据
+pub fn align_session_data_internal(
+    acc_data: &[DataPoint],
+    audio_data: &[(i64, i64, Vec<f64>, u32, u8, String)]
+) -> (Vec<DataPoint>, Vec<(i64, i64, Vec<f64>, u32, u8, String)>, i64) {
+    if acc_data.is_empty() || audio_data.is_empty() {
+        info!("Empty data provided, returning original data");
+        return (acc_data.to_vec(), audio_data.to_vec(), 0);
     }
 
+    let audio_first_timestamp = audio_data.first().map(|(start, _, _, _, _, _)| *start).unwrap_or(0);
+    let audio_last_timestamp = audio_data.last().map(|(_, end, _, _, _, _)| *end).unwrap_or(0);
+    let (aligned_acc_data, time_diff_ms) = align_acc_to_audio_range(acc_data, audio_first_timestamp, audio_last_timestamp);
+
+    // 合并所有音频数据到一个连续的向量，片段之间的间隔补静音
+    let all_audio_samples = merge_audio_blocks_with_gaps(audio_data.to_vec());
+    let (audio_sample_rate, audio_channels, audio_format) = audio_data.last()
+        .map(|(_, _, _, sample_rate, channels, format)| (*sample_rate, *channels, format.clone()))
+        .unwrap_or((DEFAULT_AUDIO_SAMPLE_RATE, 1, "PCM_16".to_string()));
+
     // 创建对齐后的音频数据（保持原格式）
     let aligned_audio_data = if all_audio_samples.is_empty() {
         Vec::new()
@@ -262,4 +510,270 @@ pub fn align_session_data_internal(
     (aligned_acc_data, aligned_audio_data, alignment_info)
 }
 
+/// 两个音频片段之间缺少的样本数：previous_end是前一片段最后一个样本的时间戳，
+/// 紧邻时下一片段起点比它晚一个样本间隔；重叠时为0，间隔最多按MAX_AUDIO_GAP_PADDING_MS计算
+fn audio_gap_samples(previous_end: i64, next_start: i64, sample_rate: u32) -> usize {
+    let gap_ms = (next_start - previous_end).min(MAX_AUDIO_GAP_PADDING_MS);
+    let gap_samples = (gap_ms as f64 * sample_rate as f64 / 1000.0).round() as i64 - 1;
+    gap_samples.max(0) as usize
+}
+
+/// 按时间顺序拼接音频片段，片段之间缺少的样本用0填充，使音频时间轴与加速度时间轴一致；
+/// 片段重叠或紧邻时直接拼接
+pub fn merge_audio_blocks_with_gaps(blocks: Vec<(i64, i64, Vec<f64>, u32, u8, String)>) -> Vec<f64> {
+    let mut merged = Vec::new();
+    let mut previous_end: Option<i64> = None;
+
+    for (start, end, samples, sample_rate, _, _) in blocks {
+        if let Some(previous_end) = previous_end {
+            merged.resize(merged.len() + audio_gap_samples(previous_end, start, sample_rate), 0.0);
+        }
+        merged.extend(samples);
+        previous_end = Some(end);
+    }
+
+    merged
+}
+
+/// 对比导出中每个session的列数（6轴IMU + 音频）
+const COMPARISON_COLUMNS: [&str; 7] = ["acc_x", "acc_y", "acc_z", "gyro_x", "gyro_y", "gyro_z", "audio"];
+
+/// 多session对比导出（在数据库线程中运行）
+/// 每个session先做加速度/音频对齐，再以各自起点为t=0重采样到公共采样率，
+/// 最后并排写入一个宽表CSV，列名形如 s1_acc_x, s2_acc_x；长度不同的列用空字段补齐
+pub fn export_sessions_comparison_internal(db_manager: &DatabaseManager, session_ids: &[String], options: &ExportConfig) -> Result<String, String> {
+    if session_ids.len() < 2 {
+        return Err("Comparison export needs at least 2 sessions".to_string());
+    }
+
+    // 加载并对齐每个session的数据
+    let mut sessions = Vec::new();
+    for session_id in session_ids {
+        let mut acc_data = db_manager.get_accelerometer_data_by_session(session_id)
+            .map_err(|e| format!("Failed to get accelerometer data for {}: {}", session_id, e))?;
+        let mut audio_data = db_manager.get_audio_data_by_session(session_id)
+            .map_err(|e| format!("Failed to get audio data for {}: {}", session_id, e))?;
+
+        if acc_data.is_empty() && audio_data.is_empty() {
+            return Err(format!("No data in session {}", session_id));
+        }
+
+        apply_timestamp_source(db_manager, session_id, &mut acc_data, audio_data.iter_mut().map(|(start, end, ..)| (start, end)));
+
+        let (aligned_acc_data, aligned_audio_data, _) = align_session_data_internal(&acc_data, &audio_data);
+        sessions.push((aligned_acc_data, aligned_audio_data));
+    }
+
+    // 公共采样率取各session中最高的加速度采样率
+    let common_rate = sessions.iter()
+        .filter_map(|(acc_data, _)| estimate_sample_rate(acc_data))
+        .fold(0.0f64, f64::max);
+    let common_rate = if common_rate > 0.0 { common_rate } else { 400.0 };
+
+    // 重采样：每个session得到7列
+    let mut columns: Vec<Vec<f64>> = Vec::new();
+    for (acc_data, audio_data) in &sessions {
+        let acc_times: Vec<f64> = acc_data.iter()
+            .map(|p| (p.timestamp - acc_data[0].timestamp) as f64 / 1000.0)
+            .collect();
+        let extractors: [fn(&DataPoint) -> f64; 6] = [|p| p.x, |p| p.y, |p| p.z, |p| p.gx, |p| p.gy, |p| p.gz];
+        for extractor in extractors {
+            let values: Vec<f64> = acc_data.iter().map(extractor).collect();
+            columns.push(resample_by_time(&acc_times, &values, common_rate));
+        }
+
+        let mut audio_samples = Vec::new();
+        let mut audio_rate = DEFAULT_AUDIO_SAMPLE_RATE as f64;
+        for (_, _, samples, sample_rate, _, _) in audio_data {
+            audio_samples.extend(samples);
+            audio_rate = *sample_rate as f64;
+        }
+        columns.push(resample_uniform(&audio_samples, audio_rate, common_rate));
+    }
+
+    // 确保导出目录存在
+    let export_dir = format!("{}/comparison", EXPORT_ROOT);
+    std::fs::create_dir_all(&export_dir)
+        .map_err(|e| format!("Failed to create comparison export directory: {}", e))?;
+
+    let filename = format!("{}/comparison_{}.csv", export_dir, chrono::Local::now().format("%Y%m%d_%H%M%S"));
+    let file = std::fs::File::create(&filename)
+        .map_err(|e| format!("Failed to create file: {}", e))?;
+
+    // 写入CSV头部
+    let mut header = vec!["t_s".to_string()];
+    for index in 1..=sessions.len() {
+        for column in COMPARISON_COLUMNS {
+            header.push(format!("s{}_{}", index, column));
+        }
+    }
+    let mut writer = csv_writer(file, &header, options)
+        .map_err(|e| format!("Failed to write CSV header: {}", e))?;
+
+    // 逐行写入，缺失的字段留空
+    let row_count = columns.iter().map(|c| c.len()).max().unwrap_or(0);
+    for row in 0..row_count {
+        let mut fields = Vec::with_capacity(columns.len() + 1);
+        fields.push(format!("{:.6}", row as f64 / common_rate));
+        for column in &columns {
+            fields.push(column.get(row).map(|v| v.to_string()).unwrap_or_default());
+        }
+        writer.write_record(&fields)
+            .map_err(|e| format!("Failed to write comparison row: {}", e))?;
+    }
+
+    writer.flush().map_err(|e| format!("Failed to flush file: {}", e))?;
+
+    info!("Exported comparison of {} sessions to {} ({} rows at {:.2} Hz)",
+          session_ids.len(), filename, row_count, common_rate);
+    Ok(filename)
+}
+
+/// 根据时间戳估算加速度采样率
+fn estimate_sample_rate(acc_data: &[DataPoint]) -> Option<f64> {
+    if acc_data.len() < 2 {
+        return None;
+    }
+    let duration_ms = acc_data.last().unwrap().timestamp - acc_data.first().unwrap().timestamp;
+    if duration_ms <= 0 {
+        return None;
+    }
+    Some((acc_data.len() - 1) as f64 * 1000.0 / duration_ms as f64)
+}
+
+/// 按时间（秒）线性插值重采样到目标采样率，times需单调不减
+fn resample_by_time(times: &[f64], values: &[f64], target_rate: f64) -> Vec<f64> {
+    if values.is_empty() {
+        return Vec::new();
+    }
+    if values.len() == 1 {
+        return vec![values[0]];
+    }
+
+    let duration = times[times.len() - 1];
+    let output_len = (duration * target_rate).floor() as usize + 1;
+    let mut output = Vec::with_capacity(output_len);
+    let mut source_index = 0;
+
+    for i in 0..output_len {
+        let t = i as f64 / target_rate;
+        while source_index + 2 < times.len() && times[source_index + 1] < t {
+            source_index += 1;
+        }
+        let (t0, t1) = (times[source_index], times[source_index + 1]);
+        let (v0, v1) = (values[source_index], values[source_index + 1]);
+        let value = if t1 > t0 {
+            let ratio = ((t - t0) / (t1 - t0)).clamp(0.0, 1.0);
+            v0 + (v1 - v0) * ratio
+        } else {
+            v1
+        };
+        output.push(value);
+    }
+
+    output
+}
+
+/// 对等间隔采样的数据线性插值重采样
+fn resample_uniform(values: &[f64], source_rate: f64, target_rate: f64) -> Vec<f64> {
+    let times: Vec<f64> = (0..values.len()).map(|i| i as f64 / source_rate).collect();
+    resample_by_time(&times, values, target_rate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(start: i64, end: i64, samples: Vec<f64>) -> (i64, i64, Vec<f64>, u32, u8, String) {
+        (start, end, samples, 1000, 1, "PCM_16".to_string())
+    }
 
+    #[test]
+    fn merge_pads_gap_between_two_blocks() {
+        // 1kHz：第一块最后一个样本在2ms，第二块从6ms开始，缺3、4、5ms三个样本
+        let merged = merge_audio_blocks_with_gaps(vec![
+            block(0, 2, vec![1.0, 2.0, 3.0]),
+            block(6, 7, vec![4.0, 5.0]),
+        ]);
+
+        assert_eq!(merged, vec![1.0, 2.0, 3.0, 0.0, 0.0, 0.0, 4.0, 5.0]);
+    }
+
+    #[test]
+    fn merge_concatenates_adjacent_and_overlapping_blocks() {
+        let merged = merge_audio_blocks_with_gaps(vec![
+            block(0, 1, vec![1.0, 2.0]),
+            block(2, 3, vec![3.0, 4.0]),
+            block(3, 4, vec![5.0, 6.0]),
+        ]);
+
+        assert_eq!(merged, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    }
+
+    #[test]
+    fn merge_caps_long_gap_padding() {
+        let merged = merge_audio_blocks_with_gaps(vec![
+            block(0, 0, vec![1.0]),
+            block(3_600_000, 3_600_000, vec![2.0]),
+        ]);
+
+        assert_eq!(merged.len(), 2 + MAX_AUDIO_GAP_PADDING_MS as usize - 1);
+        assert_eq!(merged.last(), Some(&2.0));
+    }
+
+    #[test]
+    fn csv_writer_quotes_only_the_header() {
+        let options = ExportConfig { csv_delimiter: ';', csv_quote_strings: true, ..ExportConfig::default() };
+        let header = ["acc_x", "audio_sample"].map(String::from);
+        let mut writer = csv_writer(Vec::new(), &header, &options).unwrap();
+        writer.write_record(["0.5", ""]).unwrap();
+        writer.write_record(["", "-1"]).unwrap();
+
+        let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(output, "\"acc_x\";\"audio_sample\"\n0.5;\n;-1\n");
+    }
+
+    /// 在临时目录中导出一个session，返回CSV内容；导出目录随后删除
+    fn export_to_temp_dir(db_manager: &DatabaseManager, session_id: &str, options: &ExportConfig) -> String {
+        let export_root = std::env::temp_dir().join(format!("sensehub_export_{}_{}", std::process::id(), session_id));
+        let entry = export_session_csv(db_manager, &export_root.to_string_lossy(), session_id, options, None).unwrap();
+        let content = std::fs::read_to_string(&entry.file_path).unwrap();
+        std::fs::remove_dir_all(&export_root).unwrap();
+        content
+    }
+
+    const HEADER: &str = "acc_x,acc_y,acc_z,gyro_x,gyro_y,gyro_z,audio_sample\n";
+
+    #[test]
+    fn export_writes_remaining_acc_rows_after_audio() {
+        let db_manager = DatabaseManager::open(":memory:").unwrap();
+        let acc_data: Vec<DataPoint> = (0..3)
+            .map(|i| DataPoint::new(i as f64, -0.5, 9.81, 0.25, 0.0, -1.0, 1000 + i as i64))
+            .collect();
+        db_manager.save_accelerometer_data(&acc_data, "acc_longer", "tester", "standard").unwrap();
+        db_manager.save_audio_data(&[1.0, -1.0], None, "acc_longer", Some(1000), Some(1002), "tester").unwrap();
+
+        let expected = format!(
+            "{}0,-0.5,9.81,0.25,0,-1,1\n1,-0.5,9.81,0.25,0,-1,-1\n2,-0.5,9.81,0.25,0,-1,\n",
+            HEADER
+        );
+        assert_eq!(export_to_temp_dir(&db_manager, "acc_longer", &ExportConfig::default()), expected);
+    }
+
+    #[test]
+    fn export_pads_audio_gaps_and_writes_remaining_audio_rows() {
+        let db_manager = DatabaseManager::open(":memory:").unwrap();
+        let acc_data = [DataPoint::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 1000)];
+        db_manager.save_accelerometer_data(&acc_data, "audio_longer", "tester", "standard").unwrap();
+        db_manager.save_audio_data(&[1.0], None, "audio_longer", Some(1000), Some(1000), "tester").unwrap();
+        db_manager.save_audio_data(&[-1.0], None, "audio_longer", Some(1001), Some(1001), "tester").unwrap();
+
+        // 16kHz下1ms的间隔补15个静音样本
+        let expected = format!(
+            "{}1,2,3,4,5,6,1\n{},,,,,,-1\n",
+            HEADER,
+            ",,,,,,0\n".repeat(15)
+        );
+        assert_eq!(export_to_temp_dir(&db_manager, "audio_longer", &ExportConfig::default()), expected);
+    }
+}
@@ -3,8 +3,19 @@ use env_logger::Builder;
 use log::Level;
 use std::io::Write;
 
+/// 未设置 RUST_LOG 时用于指定默认日志级别的环境变量
+pub const LOG_LEVEL_ENV: &str = "SENSEHUB_LOG_LEVEL";
+
 pub fn init_logger() {
-    Builder::from_env(env_logger::Env::default().default_filter_or("info"))
+    dotenv::dotenv().ok(); // 加载 .env 文件，使其中的日志级别也能生效
+
+    let default_level = std::env::var(LOG_LEVEL_ENV)
+        .ok()
+        .map(|level| level.trim().to_string())
+        .filter(|level| level.parse::<log::LevelFilter>().is_ok())
+        .unwrap_or_else(|| "info".to_string());
+
+    Builder::from_env(env_logger::Env::default().default_filter_or(default_level))
         .format(|buf, record| {
             let _time = Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
             let level_color = match record.level() {
@@ -1,6 +1,7 @@
 mod audio;
 mod logger;
 mod plotter;
+mod spectrogram;
 mod utils;
 mod types;
 mod database;
@@ -8,18 +9,24 @@ mod mqtt;
 mod app;
 mod config;
 
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
+use std::time::Duration;
 use crossbeam_channel::bounded;
 use eframe::egui;
 use log::{error, info, warn};
 
 use types::{DataPoint, AudioData, DatabaseTask, SaveResult};
-use database::run_database_handler;
-use mqtt::run_mqtt_client;
-use app::SensorDataApp;
-use config::ConfigManager;
+use database::{run_database_handler, DatabaseChannelHandoff, DatabaseOpenError, DatabaseStatus, SharedDatabaseStatus};
+use mqtt::{run_mqtt_client, ChannelSink, ChannelDropCounters, MqttControl, MqttStats, SensorSinks};
+#[cfg(feature = "mock")]
+use mqtt::{run_mock_source, MockSourceConfig};
+use app::{GuiChannels, SensorDataApp};
+use config::{AppConfig, ChannelConfig, ConfigManager, DatabaseConfig, OverflowStrategy, TimestampSource};
+
+/// 数据库线程异常退出（panic或返回错误）后最多自动重启的次数
+const MAX_DB_THREAD_RESTARTS: u32 = 5;
 
 fn setup_custom_fonts(ctx: &egui::Context) {
     // 配置字体以支持中文显示
@@ -111,11 +118,12 @@ fn main() {
     info!("SenseHub application starting");
 
     // 加载配置
-    let config_manager = ConfigManager::new();
-    let config = config_manager.get_config();
+    let config_manager = ConfigManager::load_or_default(config::DEFAULT_CONFIG_PATH);
+    let config = config_manager.get_config().clone();
 
     // 创建应用通道
     let (data_sender, data_receiver) = bounded::<DataPoint>(config.channels.data_channel_capacity);
+    let (display_sender, display_receiver) = bounded::<DataPoint>(config.channels.data_channel_capacity);
     let (audio_sender, audio_receiver) = bounded::<AudioData>(config.channels.audio_channel_capacity);
     let (db_task_sender, db_task_receiver) = bounded::<DatabaseTask>(config.channels.db_task_channel_capacity);
     let (save_result_sender, save_result_receiver) = bounded::<SaveResult>(config.channels.save_result_channel_capacity);
@@ -123,55 +131,142 @@ fn main() {
     // 创建共享的关闭信号
     let shutdown_signal = Arc::new(AtomicBool::new(false));
 
+    // GUI与MQTT线程共享的连接状态和手动重连信号
+    let mqtt_control = Arc::new(MqttControl::new());
+
+    // MQTT线程更新、GUI显示的消息解析统计
+    let mqtt_stats = Arc::new(Mutex::new(MqttStats::default()));
+
+    // GUI与数据库线程共享的数据库连接状态
+    let db_status = SharedDatabaseStatus::new();
+
+    // 数据库线程重启后，监控线程通过它把新通道交给GUI
+    let db_channel_handoff = DatabaseChannelHandoff::default();
+
+    // GUI关闭后释放，数据源线程据此得知接收端已不再使用
+    let gui_alive = Arc::new(());
+
+    // 按配置的溢出策略包装数据通道发送端
+    let overflow_strategy = config.channels.overflow_strategy;
+    let data_sink = ChannelSink::new("传感器数据", data_sender, data_receiver.clone(), overflow_strategy, Arc::downgrade(&gui_alive));
+    let audio_sink = ChannelSink::new("音频数据", audio_sender, audio_receiver.clone(), overflow_strategy, Arc::downgrade(&gui_alive));
+    let drop_counters = ChannelDropCounters {
+        data: data_sink.drop_counter(),
+        audio: audio_sink.drop_counter(),
+        data_received: data_sink.received_counter(),
+        audio_received: audio_sink.received_counter(),
+    };
+    // 显示通道只供绘图，满时总是丢弃最旧的点，保存通道按配置的策略处理
+    let display_sink = ChannelSink::new("传感器显示数据", display_sender, display_receiver.clone(), OverflowStrategy::DropOldest, Arc::downgrade(&gui_alive));
+    let sensor_sinks = SensorSinks::new(data_sink, display_sink, config.mqtt.ingest_decimation);
+
     // 启动后台线程
     let handles = start_background_threads(
-        data_sender,
-        audio_sender,
-        db_task_receiver,
-        save_result_sender,
-        shutdown_signal.clone(),
+        BackgroundChannels {
+            sensor_sinks,
+            audio_sink,
+            db_task_receiver,
+            save_result_sender,
+            shutdown_signal: shutdown_signal.clone(),
+            mqtt_control: mqtt_control.clone(),
+            mqtt_stats: mqtt_stats.clone(),
+            db_status: db_status.clone(),
+            db_channel_handoff: db_channel_handoff.clone(),
+        },
+        &config,
     );
 
     // 配置并启动GUI
     let gui_result = run_gui_application(
-        data_receiver,
-        audio_receiver,
-        db_task_sender,
-        save_result_receiver,
-        config,
+        GuiChannels {
+            data_receiver,
+            display_receiver,
+            ingest_decimation: config.mqtt.ingest_decimation,
+            audio_receiver,
+            db_task_sender,
+            save_result_receiver,
+            mqtt_control,
+            mqtt_stats,
+            drop_counters,
+            db_status,
+            db_channel_handoff,
+        },
+        config_manager,
     );
 
     // GUI关闭后的清理工作
+    drop(gui_alive);
     shutdown_and_cleanup(shutdown_signal, handles, gui_result);
 }
 
-fn start_background_threads(
-    data_sender: crossbeam_channel::Sender<DataPoint>,
-    audio_sender: crossbeam_channel::Sender<AudioData>,
+/// 后台线程持有的通道端和与GUI共享的句柄
+struct BackgroundChannels {
+    sensor_sinks: SensorSinks,
+    audio_sink: ChannelSink<AudioData>,
     db_task_receiver: crossbeam_channel::Receiver<DatabaseTask>,
     save_result_sender: crossbeam_channel::Sender<SaveResult>,
     shutdown_signal: Arc<AtomicBool>,
-) -> Vec<thread::JoinHandle<()>> {
+    mqtt_control: Arc<MqttControl>,
+    mqtt_stats: Arc<Mutex<MqttStats>>,
+    db_status: SharedDatabaseStatus,
+    db_channel_handoff: DatabaseChannelHandoff,
+}
+
+fn start_background_threads(channels: BackgroundChannels, config: &AppConfig) -> Vec<thread::JoinHandle<()>> {
+    let BackgroundChannels {
+        sensor_sinks,
+        audio_sink,
+        db_task_receiver,
+        save_result_sender,
+        shutdown_signal,
+        mqtt_control,
+        mqtt_stats,
+        db_status,
+        db_channel_handoff,
+    } = channels;
     let mut handles = Vec::new();
 
     // 启动MQTT客户端线程
-    let mqtt_data_sender = Arc::new(data_sender);
-    let mqtt_audio_sender = Arc::new(audio_sender);
+    let mqtt_data_sender = Arc::new(sensor_sinks);
+    let mqtt_audio_sender = Arc::new(audio_sink);
     let mqtt_shutdown = Arc::clone(&shutdown_signal);
+    let mqtt_config = config.mqtt.clone();
+    #[cfg(feature = "mock")]
+    let mock_source = MockSourceConfig::from_env_and_args();
     
     let mqtt_handle = thread::spawn(move || {
-        if let Err(e) = run_mqtt_client(mqtt_data_sender, mqtt_audio_sender, mqtt_shutdown) {
-            error!("MQTT thread failed: {}", e);
+        // 启用mock特性构建时，--mock 或 SENSEHUB_MOCK=1 使用模拟数据源代替MQTT
+        #[cfg(feature = "mock")]
+        if let Some(mock_config) = mock_source {
+            // 模拟数据源始终视为已连接
+            mqtt_control.set_connected(true);
+            if let Err(e) = run_mock_source(mqtt_data_sender, mqtt_audio_sender, mqtt_shutdown, mock_config) {
+                error!("Data source thread failed: {}", e);
+            }
+            return;
+        }
+
+        let result = run_mqtt_client(mqtt_data_sender, mqtt_audio_sender, mqtt_shutdown, mqtt_control, mqtt_stats, mqtt_config);
+        if let Err(e) = result {
+            error!("Data source thread failed: {}", e);
         }
     });
     handles.push(mqtt_handle);
 
-    // 启动数据库处理线程
-    let db_shutdown = Arc::clone(&shutdown_signal);
+    // 启动数据库处理线程，由监控线程在其崩溃后重启
+    let database_config = config.database.clone();
+    let channel_config = config.channels.clone();
+    let timestamp_source = config.mqtt.timestamp_source;
     let db_handle = thread::spawn(move || {
-        if let Err(e) = run_database_handler(db_task_receiver, save_result_sender, db_shutdown) {
-            error!("Database handler thread failed: {}", e);
-        }
+        supervise_database_thread(
+            (db_task_receiver, save_result_sender),
+            shutdown_signal,
+            database_config,
+            channel_config,
+            timestamp_source,
+            db_status,
+            db_channel_handoff,
+        );
     });
     handles.push(db_handle);
 
@@ -179,13 +274,72 @@ fn start_background_threads(
     handles
 }
 
-fn run_gui_application(
-    data_receiver: crossbeam_channel::Receiver<DataPoint>,
-    audio_receiver: crossbeam_channel::Receiver<AudioData>,
-    db_task_sender: crossbeam_channel::Sender<DatabaseTask>,
-    save_result_receiver: crossbeam_channel::Receiver<SaveResult>,
-    config: &config::AppConfig,
-) -> Result<(), eframe::Error> {
+/// 运行数据库处理线程并监控其退出：panic或返回错误时新建任务/结果通道并重启，
+/// 新通道交给GUI替换已断开的旧通道；重启次数用完后将数据库状态置为失败
+fn supervise_database_thread(
+    worker_channels: (crossbeam_channel::Receiver<DatabaseTask>, crossbeam_channel::Sender<SaveResult>),
+    shutdown_signal: Arc<AtomicBool>,
+    database_config: DatabaseConfig,
+    channel_config: ChannelConfig,
+    timestamp_source: TimestampSource,
+    db_status: SharedDatabaseStatus,
+    db_channel_handoff: DatabaseChannelHandoff,
+) {
+    let (mut task_receiver, mut result_sender) = worker_channels;
+    let mut restarts = 0;
+
+    loop {
+        let worker_shutdown = Arc::clone(&shutdown_signal);
+        let worker_config = database_config.clone();
+        let worker_status = db_status.clone();
+        let worker = thread::spawn(move || {
+            run_database_handler(task_receiver, result_sender, worker_shutdown, worker_config, timestamp_source, worker_status)
+                .map_err(|e| e.to_string())
+        });
+
+        let failure = match worker.join() {
+            Ok(Ok(())) => None,
+            Ok(Err(e)) => Some(e),
+            Err(_) => Some("thread panicked".to_string()),
+        };
+
+        // 正常退出（收到关闭信号或GUI已关闭）时不重启
+        let Some(failure) = failure else { break };
+        if shutdown_signal.load(Ordering::Relaxed) {
+            error!("Database handler thread failed during shutdown: {}", failure);
+            break;
+        }
+
+        if restarts >= MAX_DB_THREAD_RESTARTS {
+            error!("Database handler thread failed: {}; giving up after {} restarts", failure, restarts);
+            db_status.set(DatabaseStatus::Failed(DatabaseOpenError::Other(format!(
+                "Database thread stopped after {} restarts: {}", restarts, failure
+            ))));
+            break;
+        }
+
+        restarts += 1;
+        error!("Database handler thread failed: {}; restarting ({}/{})", failure, restarts, MAX_DB_THREAD_RESTARTS);
+
+        // 按重启次数逐步延长等待，期间仍响应关闭信号
+        for _ in 0..restarts * 10 {
+            if shutdown_signal.load(Ordering::Relaxed) {
+                return;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+
+        db_status.set(DatabaseStatus::Opening);
+        let (new_task_sender, new_task_receiver) = bounded::<DatabaseTask>(channel_config.db_task_channel_capacity);
+        let (new_result_sender, new_result_receiver) = bounded::<SaveResult>(channel_config.save_result_channel_capacity);
+        db_channel_handoff.publish(new_task_sender, new_result_receiver);
+        task_receiver = new_task_receiver;
+        result_sender = new_result_sender;
+    }
+}
+
+fn run_gui_application(channels: GuiChannels, config_manager: ConfigManager) -> Result<(), eframe::Error> {
+    let config = config_manager.get_config().clone();
     let mut viewport_builder = egui::ViewportBuilder::default()
         .with_inner_size([config.window.width, config.window.height])
         .with_resizable(config.window.resizable);
@@ -198,13 +352,8 @@ fn run_gui_application(
     // 如果配置了窗口位置，则设置位置
     if let (Some(x), Some(y)) = (config.window.x, config.window.y) {
         viewport_builder = viewport_builder.with_position([x, y]);
-    } else if let Some(y) = config.window.y {
-        // 如果只设置了y坐标，x坐标居中
-        viewport_builder = viewport_builder.with_position([
-            (1920.0 - config.window.width) / 2.0,  // 假设屏幕宽度1920，居中显示
-            y
-        ]);
     }
+    // 只设置了y坐标时，启动前无法得知显示器尺寸，由应用在首帧根据实际显示器宽度水平居中
 
     let options = eframe::NativeOptions {
         vsync: config.window.vsync,
@@ -225,12 +374,7 @@ fn run_gui_application(
             // 配置中文字体
             setup_custom_fonts(&cc.egui_ctx);
             
-            Ok(Box::new(SensorDataApp::new(
-                data_receiver,
-                audio_receiver,
-                db_task_sender,
-                save_result_receiver,
-            )))
+            Ok(Box::new(SensorDataApp::new(channels, config_manager)))
         }),
     )
 }
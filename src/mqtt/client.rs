@@ -1,48 +1,95 @@
-use std::env;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
-use std::thread;
-use crossbeam_channel::Sender;
-use dotenv::dotenv;
 use log::{info, warn, error, debug};
+use rand::Rng;
 use rumqttc::{Client, Event, LastWill, MqttOptions, Packet, QoS, ConnectionError};
 
+use crate::config::{MqttConfig, ReconnectStrategy, TopicKind};
 use crate::types::{DataPoint, AudioData};
+use super::sink::{ChannelSink, SensorSinks};
+use super::control::MqttControl;
+
+/// 自动重试的最大次数，之后等待手动重连
+pub const MQTT_MAX_RETRIES: u32 = 5;
+/// 指数退避的初始和最大等待时间（秒）
+const BACKOFF_BASE_SECS: f64 = 1.0;
+const BACKOFF_MAX_SECS: f64 = 30.0;
+
+/// 按重连策略计算第retry_count次（从1开始）重试前的等待时间
+fn reconnect_delay(strategy: ReconnectStrategy, retry_count: u32) -> Duration {
+    match strategy {
+        ReconnectStrategy::ExponentialBackoff => {
+            let backoff = (BACKOFF_BASE_SECS * 2f64.powi(retry_count.saturating_sub(1) as i32)).min(BACKOFF_MAX_SECS);
+            // ±20%的随机抖动，避免多个客户端在broker恢复后同时重连
+            let jitter = rand::rng().random_range(0.8..=1.2);
+            Duration::from_secs_f64(backoff * jitter)
+        }
+        ReconnectStrategy::Fixed(secs) => Duration::from_secs(secs),
+        ReconnectStrategy::Immediate => Duration::ZERO,
+    }
+}
+
+/// MQTT连接质量统计，由MQTT线程更新，GUI读取后显示解析错误率
+#[derive(Debug, Clone, Default)]
+pub struct MqttStats {
+    pub messages_received: u64,
+    pub parse_ok: u64,
+    pub parse_error: u64,
+    pub last_message_ms: i64, // 最后一条消息的主机接收时间（Unix毫秒），0表示尚未收到
+}
+
+impl MqttStats {
+    /// 解析错误占收到消息的百分比，尚未收到消息时为None
+    pub fn error_rate_percent(&self) -> Option<f64> {
+        (self.messages_received > 0)
+            .then(|| self.parse_error as f64 / self.messages_received as f64 * 100.0)
+    }
+
+    fn record(stats: &Mutex<MqttStats>, arrival_ms: i64, parsed: bool) {
+        if let Ok(mut stats) = stats.lock() {
+            stats.messages_received += 1;
+            if parsed {
+                stats.parse_ok += 1;
+            } else {
+                stats.parse_error += 1;
+            }
+            stats.last_message_ms = arrival_ms;
+        }
+    }
+}
 
 pub fn run_mqtt_client(
-    data_sender: Arc<Sender<DataPoint>>, 
-    audio_sender: Arc<Sender<AudioData>>,
-    shutdown_signal: Arc<AtomicBool>
+    data_sender: Arc<SensorSinks>, 
+    audio_sender: Arc<ChannelSink<AudioData>>,
+    shutdown_signal: Arc<AtomicBool>,
+    control: Arc<MqttControl>,
+    stats: Arc<Mutex<MqttStats>>,
+    mqtt_config: MqttConfig,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    dotenv().ok(); // 加载 .env 文件
-    
-    let mqtt_user = env::var("MQTT_USER").unwrap_or_else(|_| "guest".into());
-    let mqtt_pass = env::var("MQTT_PASS").unwrap_or_else(|_| "guest".into());
-    let mqtt_host = env::var("MQTT_HOST").unwrap_or_else(|_| "localhost".into());
-    let mqtt_port = env::var("MQTT_PORT")
-        .unwrap_or_else(|_| "1883".into())
-        .parse::<u16>()
-        .unwrap_or(1883);
-
-    info!("正在连接MQTT服务器: {}:{}", mqtt_host, mqtt_port);
-    debug!("MQTT用户名: {}", mqtt_user);
-
-    let max_retries = 5;
+    // 连接参数来自配置文件，已由 config::apply_env_overrides 应用环境变量覆盖
+    info!("正在连接MQTT服务器: {}:{}", mqtt_config.broker, mqtt_config.port);
+    debug!("MQTT用户名: {}", mqtt_config.username.as_deref().unwrap_or("guest"));
+
+    let max_retries = MQTT_MAX_RETRIES;
     let mut retry_count = 0;
 
-    while retry_count < max_retries && !shutdown_signal.load(Ordering::Relaxed) {
-        match attempt_mqtt_connection(
-            &mqtt_host,
-            mqtt_port,
-            &mqtt_user,
-            &mqtt_pass,
+    while !shutdown_signal.load(Ordering::Relaxed) {
+        let result = attempt_mqtt_connection(
+            &mqtt_config,
             data_sender.clone(),
             audio_sender.clone(),
             shutdown_signal.clone(),
-        ) {
+            &control,
+            &stats,
+        );
+        control.set_connected(false);
+
+        match result {
             Ok(_) => {
                 info!("MQTT连接成功关闭");
+                info!("通道溢出丢弃统计: 传感器数据 {} 条, 音频数据 {} 条",
+                      data_sender.dropped_count(), audio_sender.dropped_count());
                 return Ok(());
             }
             Err(e) => {
@@ -50,12 +97,18 @@ pub fn run_mqtt_client(
                 error!("MQTT连接尝试 {} 失败: {}", retry_count, e);
                 
                 if retry_count < max_retries {
-                    let delay = std::cmp::min(5 * retry_count, 30); // 最大延迟30秒
-                    warn!("将在{}秒后重试连接...", delay);
-                    thread::sleep(Duration::from_secs(delay as u64));
+                    let delay = reconnect_delay(mqtt_config.reconnect_strategy, retry_count);
+                    warn!("将在{:.1}秒后重试连接...", delay.as_secs_f64());
+                    if control.wait_for_retry(Some(delay), &shutdown_signal) {
+                        info!("收到手动重连请求，立即重试");
+                    }
                 } else {
-                    error!("达到最大重试次数，MQTT客户端停止");
-                    return Err(format!("MQTT连接失败，已重试{}次", max_retries).into());
+                    // 达到最大重试次数后不再自动重试，等待用户手动重连
+                    error!("达到最大重试次数({})，等待手动重连", max_retries);
+                    if control.wait_for_retry(None, &shutdown_signal) {
+                        info!("收到手动重连请求，重新开始连接");
+                        retry_count = 0;
+                    }
                 }
             }
         }
@@ -69,22 +122,25 @@ pub fn run_mqtt_client(
 }
 
 fn attempt_mqtt_connection(
-    host: &str,
-    port: u16,
-    user: &str,
-    pass: &str,
-    data_sender: Arc<Sender<DataPoint>>,
-    audio_sender: Arc<Sender<AudioData>>,
+    mqtt_config: &MqttConfig,
+    data_sender: Arc<SensorSinks>,
+    audio_sender: Arc<ChannelSink<AudioData>>,
     shutdown_signal: Arc<AtomicBool>,
+    control: &MqttControl,
+    stats: &Mutex<MqttStats>,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    let routes = &mqtt_config.routes;
     let mut mqtt_options = MqttOptions::new(
         "sensor-client-01",
-        host,
-        port
+        mqtt_config.broker.as_str(),
+        mqtt_config.port
     );
 
     mqtt_options
-        .set_credentials(user, pass)
+        .set_credentials(
+            mqtt_config.username.as_deref().unwrap_or("guest"),
+            mqtt_config.password.as_deref().unwrap_or("guest"),
+        )
         .set_keep_alive(Duration::from_secs(30))  // 使用更长的keep alive
         .set_last_will(LastWill::new(
             "sensors/status",
@@ -96,10 +152,12 @@ fn attempt_mqtt_connection(
     debug!("创建MQTT客户端连接...");
     let (client, mut connection) = Client::new(mqtt_options, 10);
     
-    // 订阅主题
-    client.subscribe("sensors", QoS::AtLeastOnce)?;
-    client.subscribe("audio", QoS::AtLeastOnce)?;
-    info!("已订阅MQTT主题: sensors, audio");
+    // 订阅路由表中的所有主题
+    for route in routes {
+        client.subscribe(route.topic.as_str(), QoS::AtLeastOnce)?;
+    }
+    info!("已订阅MQTT主题: {}", routes.iter().map(|route| format!("{} ({})", route.topic, route.kind.label())).collect::<Vec<_>>().join(", "));
+    let route_kind = |topic: &str| routes.iter().find(|route| route.topic == topic).map(|route| route.kind);
 
     let mut connected = false;
     let mut ping_failures = 0;
@@ -116,17 +174,22 @@ fn attempt_mqtt_connection(
             Ok(Event::Incoming(Packet::ConnAck(_))) => {
                 connected = true;
                 ping_failures = 0;
+                control.set_connected(true);
                 info!("MQTT连接建立成功");
             }
             Ok(Event::Incoming(Packet::PingResp)) => {
                 ping_failures = 0;
                 debug!("收到MQTT ping响应");
             }
-            Ok(Event::Incoming(Packet::Publish(publish))) if publish.topic == "sensors" => {
-                match parse_sensor_data(&publish.payload) {
-                    Ok(data) => {
+            Ok(Event::Incoming(Packet::Publish(publish))) if route_kind(&publish.topic) == Some(TopicKind::Accelerometer) => {
+                let arrival_ms = chrono::Utc::now().timestamp_millis();
+                let parsed = parse_sensor_data(&publish.payload);
+                MqttStats::record(stats, arrival_ms, parsed.is_ok());
+                match parsed {
+                    Ok(mut data) => {
+                        data.arrival_timestamp = Some(arrival_ms);
                         debug!("收到传感器数据: x={}, y={}, z={}", data.x, data.y, data.z);
-                        if let Err(_e) = data_sender.send(data) {
+                        if !data_sender.send(data) {
                             info!("传感器数据通道已断开，MQTT线程退出");
                             break;
                         }
@@ -134,11 +197,15 @@ fn attempt_mqtt_connection(
                     Err(e) => warn!("无效的传感器数据: {}", e),
                 }
             }
-            Ok(Event::Incoming(Packet::Publish(publish))) if publish.topic == "audio" => {
-                match parse_audio_data(&publish.payload) {
-                    Ok(data) => {
+            Ok(Event::Incoming(Packet::Publish(publish))) if route_kind(&publish.topic) == Some(TopicKind::Audio) => {
+                let arrival_ms = chrono::Utc::now().timestamp_millis();
+                let parsed = parse_audio_data(&publish.payload);
+                MqttStats::record(stats, arrival_ms, parsed.is_ok());
+                match parsed {
+                    Ok(mut data) => {
+                        data.arrival_timestamp = Some(arrival_ms);
                         debug!("收到音频数据: {} 字节", data.audio_data.len());
-                        if let Err(_e) = audio_sender.send(data) {
+                        if !audio_sender.send(data) {
                             info!("音频数据通道已断开，MQTT线程退出");
                             break;
                         }
@@ -0,0 +1,55 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use std::thread;
+
+/// 重试等待期间检查关闭/重连信号的间隔
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// GUI与MQTT线程共享的连接状态和手动重连信号
+#[derive(Debug, Default)]
+pub struct MqttControl {
+    connected: AtomicBool,
+    reconnect_requested: AtomicBool,
+}
+
+impl MqttControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    pub fn set_connected(&self, connected: bool) {
+        self.connected.store(connected, Ordering::Relaxed);
+    }
+
+    /// 请求MQTT线程放弃当前的退避等待，立即重连
+    pub fn request_reconnect(&self) {
+        self.reconnect_requested.store(true, Ordering::Relaxed);
+    }
+
+    /// 取出重连请求，返回是否有待处理的请求
+    pub fn take_reconnect_request(&self) -> bool {
+        self.reconnect_requested.swap(false, Ordering::Relaxed)
+    }
+
+    /// 等待指定时长（None表示一直等待），收到关闭信号或重连请求时提前返回
+    /// 返回true表示因重连请求而提前结束
+    pub fn wait_for_retry(&self, delay: Option<Duration>, shutdown_signal: &AtomicBool) -> bool {
+        let deadline = delay.map(|delay| Instant::now() + delay);
+
+        while !shutdown_signal.load(Ordering::Relaxed) {
+            if self.take_reconnect_request() {
+                return true;
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return false;
+            }
+            thread::sleep(WAIT_POLL_INTERVAL);
+        }
+
+        false
+    }
+}
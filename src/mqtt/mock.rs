@@ -0,0 +1,166 @@
+use std::env;
+use std::f64::consts::PI;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use std::thread;
+use base64::{Engine as _, engine::general_purpose};
+use log::info;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use crate::types::{DataPoint, AudioData};
+use super::sink::{ChannelSink, SensorSinks};
+
+/// 模拟音频采样率，与Android客户端一致
+const MOCK_AUDIO_SAMPLE_RATE: u32 = 16000;
+/// 每个模拟音频包的时长（毫秒）
+const MOCK_AUDIO_CHUNK_MS: u64 = 100;
+/// 发送循环的节拍
+const MOCK_TICK: Duration = Duration::from_millis(10);
+
+/// 模拟数据源配置
+#[derive(Debug, Clone, Copy)]
+pub struct MockSourceConfig {
+    pub sample_rate_hz: f64,
+    pub seed: u64,
+}
+
+impl MockSourceConfig {
+    /// 通过 `--mock` 命令行参数或 `SENSEHUB_MOCK=1` 环境变量启用模拟数据源
+    /// 采样率和随机种子分别由 `SENSEHUB_MOCK_RATE`、`SENSEHUB_MOCK_SEED` 指定
+    pub fn from_env_and_args() -> Option<Self> {
+        let enabled_by_arg = env::args().any(|arg| arg == "--mock");
+        let enabled_by_env = env::var("SENSEHUB_MOCK")
+            .map(|value| matches!(value.as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+
+        if !enabled_by_arg && !enabled_by_env {
+            return None;
+        }
+
+        let sample_rate_hz = env::var("SENSEHUB_MOCK_RATE")
+            .ok()
+            .and_then(|value| value.parse::<f64>().ok())
+            .filter(|rate| *rate > 0.0)
+            .unwrap_or(400.0);
+        let seed = env::var("SENSEHUB_MOCK_SEED")
+            .ok()
+            .and_then(|value| value.parse::<u64>().ok())
+            .unwrap_or(42);
+
+        Some(Self { sample_rate_hz, seed })
+    }
+}
+
+/// 合成波形参数，由随机种子决定，保证同一种子得到相同的波形
+struct MockWaveform {
+    frequencies: [f64; 6],
+    phases: [f64; 6],
+    tone_frequency: f64,
+}
+
+impl MockWaveform {
+    fn new(rng: &mut StdRng) -> Self {
+        let mut frequencies = [0.0; 6];
+        let mut phases = [0.0; 6];
+        for (frequency, phase) in frequencies.iter_mut().zip(phases.iter_mut()) {
+            *frequency = rng.random_range(0.5..3.0);
+            *phase = rng.random_range(0.0..2.0 * PI);
+        }
+
+        Self {
+            frequencies,
+            phases,
+            tone_frequency: rng.random_range(200.0..800.0),
+        }
+    }
+
+    fn data_point(&self, t: f64, rng: &mut StdRng, timestamp: i64) -> DataPoint {
+        let wave = |axis: usize, amplitude: f64| {
+            amplitude * (2.0 * PI * self.frequencies[axis] * t + self.phases[axis]).sin()
+        };
+        let mut noise = || rng.random_range(-0.02..0.02);
+
+        DataPoint::new(
+            wave(0, 0.5) + noise(),
+            wave(1, 0.3) + noise(),
+            9.81 + wave(2, 0.2) + noise(),
+            wave(3, 0.1) + noise(),
+            wave(4, 0.1) + noise(),
+            wave(5, 0.05) + noise(),
+            timestamp,
+        )
+    }
+
+    fn audio_chunk(&self, start_sample: u64, sample_count: usize, rng: &mut StdRng, timestamp: i64) -> AudioData {
+        let mut bytes = Vec::with_capacity(sample_count * 2);
+        for i in 0..sample_count {
+            let t = (start_sample + i as u64) as f64 / MOCK_AUDIO_SAMPLE_RATE as f64;
+            let value = 0.2 * (2.0 * PI * self.tone_frequency * t).sin() + rng.random_range(-0.01..0.01);
+            let sample = (value * i16::MAX as f64) as i16;
+            bytes.extend_from_slice(&sample.to_le_bytes());
+        }
+
+        AudioData::new(
+            general_purpose::STANDARD.encode(&bytes),
+            MOCK_AUDIO_SAMPLE_RATE,
+            1,
+            "PCM_16".to_string(),
+            sample_count,
+            timestamp,
+        )
+    }
+}
+
+/// 模拟数据源：按配置的采样率生成合成的传感器和音频数据，写入与MQTT客户端相同的通道
+pub fn run_mock_source(
+    data_sender: Arc<SensorSinks>,
+    audio_sender: Arc<ChannelSink<AudioData>>,
+    shutdown_signal: Arc<AtomicBool>,
+    config: MockSourceConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    info!("模拟数据源启动: 采样率 {:.1} Hz, 随机种子 {}", config.sample_rate_hz, config.seed);
+
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let waveform = MockWaveform::new(&mut rng);
+
+    let start = Instant::now();
+    let start_timestamp = chrono::Utc::now().timestamp_millis();
+    let audio_chunk_samples = (MOCK_AUDIO_SAMPLE_RATE as u64 * MOCK_AUDIO_CHUNK_MS / 1000) as usize;
+    let mut sent_points: u64 = 0;
+    let mut sent_audio_samples: u64 = 0;
+
+    while !shutdown_signal.load(Ordering::Relaxed) {
+        let elapsed = start.elapsed().as_secs_f64();
+
+        // 按经过的时间补齐应发送的传感器数据点，时间戳严格等间隔
+        let due_points = (elapsed * config.sample_rate_hz) as u64;
+        while sent_points < due_points {
+            let t = sent_points as f64 / config.sample_rate_hz;
+            let timestamp = start_timestamp + (t * 1000.0) as i64;
+            if !data_sender.send(waveform.data_point(t, &mut rng, timestamp)) {
+                info!("传感器数据通道已关闭，模拟数据源退出");
+                return Ok(());
+            }
+            sent_points += 1;
+        }
+
+        // 音频按固定长度的包发送
+        let due_audio_samples = (elapsed * MOCK_AUDIO_SAMPLE_RATE as f64) as u64;
+        while sent_audio_samples + audio_chunk_samples as u64 <= due_audio_samples {
+            let timestamp = start_timestamp + (sent_audio_samples * 1000 / MOCK_AUDIO_SAMPLE_RATE as u64) as i64;
+            let chunk = waveform.audio_chunk(sent_audio_samples, audio_chunk_samples, &mut rng, timestamp);
+            if !audio_sender.send(chunk) {
+                info!("音频数据通道已关闭，模拟数据源退出");
+                return Ok(());
+            }
+            sent_audio_samples += audio_chunk_samples as u64;
+        }
+
+        thread::sleep(MOCK_TICK);
+    }
+
+    info!("模拟数据源已停止: 共发送 {} 个传感器数据点, {} 个音频样本", sent_points, sent_audio_samples);
+    Ok(())
+}
@@ -1,3 +1,11 @@
 pub mod client;
+pub mod sink;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod control;
 
-pub use client::run_mqtt_client;
+pub use client::{run_mqtt_client, MqttStats, MQTT_MAX_RETRIES};
+pub use sink::{ChannelSink, ChannelDropCounters, SensorSinks};
+#[cfg(feature = "mock")]
+pub use mock::{run_mock_source, MockSourceConfig};
+pub use control::MqttControl;
@@ -0,0 +1,213 @@
+use std::sync::{Arc, Weak};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
+use crossbeam_channel::{Receiver, Sender, SendTimeoutError, TrySendError};
+use log::warn;
+
+use crate::config::OverflowStrategy;
+use crate::types::DataPoint;
+
+/// Block策略下单次发送的最长等待时间
+const BLOCK_SEND_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// 按溢出策略向有界通道发送数据的发送端
+pub struct ChannelSink<T> {
+    name: &'static str,
+    sender: Sender<T>,
+    drain_receiver: Option<Receiver<T>>, // 仅DropOldest策略使用，用于丢弃最旧的数据
+    gui_alive: Weak<()>, // GUI运行期间有效；DropOldest自己持有接收端，通道不会断开，改用它判断GUI是否已关闭
+    strategy: OverflowStrategy,
+    received: Arc<AtomicU64>, // 交给通道的数据总条数（含被丢弃的）
+    dropped: Arc<AtomicU64>,
+}
+
+impl<T> ChannelSink<T> {
+    pub fn new(name: &'static str, sender: Sender<T>, receiver: Receiver<T>, strategy: OverflowStrategy, gui_alive: Weak<()>) -> Self {
+        // 只有DropOldest需要持有接收端，其他策略不持有，以免影响通道断开检测
+        let drain_receiver = if strategy == OverflowStrategy::DropOldest {
+            Some(receiver)
+        } else {
+            None
+        };
+
+        Self {
+            name,
+            sender,
+            drain_receiver,
+            gui_alive,
+            strategy,
+            received: Arc::new(AtomicU64::new(0)),
+            dropped: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// 发送数据，返回false表示通道已断开
+    pub fn send(&self, item: T) -> bool {
+        self.received.fetch_add(1, Ordering::Relaxed);
+        match self.strategy {
+            OverflowStrategy::DropNewest => match self.sender.try_send(item) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_)) => {
+                    self.record_drop();
+                    true
+                }
+                Err(TrySendError::Disconnected(_)) => false,
+            },
+            OverflowStrategy::DropOldest => {
+                if self.gui_alive.strong_count() == 0 {
+                    return false;
+                }
+                let mut item = item;
+                loop {
+                    match self.sender.try_send(item) {
+                        Ok(()) => return true,
+                        Err(TrySendError::Full(returned)) => {
+                            // 先取出一个最旧的数据再重试
+                            if let Some(receiver) = &self.drain_receiver {
+                                if receiver.try_recv().is_ok() {
+                                    self.record_drop();
+                                }
+                            }
+                            item = returned;
+                        }
+                        Err(TrySendError::Disconnected(_)) => return false,
+                    }
+                }
+            }
+            OverflowStrategy::Block => match self.sender.send_timeout(item, BLOCK_SEND_TIMEOUT) {
+                Ok(()) => true,
+                Err(SendTimeoutError::Timeout(_)) => {
+                    self.record_drop();
+                    true
+                }
+                Err(SendTimeoutError::Disconnected(_)) => false,
+            },
+        }
+    }
+
+    /// 获取已丢弃的数据条数
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// 获取共享的丢弃计数器，供GUI显示通道饱和情况
+    pub fn drop_counter(&self) -> Arc<AtomicU64> {
+        Arc::clone(&self.dropped)
+    }
+
+    /// 获取共享的接收计数器，供GUI显示收到的数据总数
+    pub fn received_counter(&self) -> Arc<AtomicU64> {
+        Arc::clone(&self.received)
+    }
+
+    fn record_drop(&self) {
+        let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+        if dropped == 1 || dropped.is_multiple_of(1000) {
+            warn!("{}通道已满 ({:?})，累计丢弃 {} 条数据", self.name, self.strategy, dropped);
+        }
+    }
+}
+
+/// 传感器数据的两条路径：保存通道接收每个数据点，显示通道每ingest_decimation个数据点只接收1个，
+/// 高采样率下GUI只需绘制抽样后的数据，保存的数据不受抽样影响
+pub struct SensorSinks {
+    save: ChannelSink<DataPoint>,
+    display: ChannelSink<DataPoint>,
+    ingest_decimation: usize,
+    sent: AtomicUsize,
+}
+
+impl SensorSinks {
+    pub fn new(save: ChannelSink<DataPoint>, display: ChannelSink<DataPoint>, ingest_decimation: usize) -> Self {
+        Self {
+            save,
+            display,
+            ingest_decimation: ingest_decimation.max(1),
+            sent: AtomicUsize::new(0),
+        }
+    }
+
+    /// 发送到保存通道，并按抽样间隔转发到显示通道；返回false表示保存通道已断开
+    pub fn send(&self, point: DataPoint) -> bool {
+        let index = self.sent.fetch_add(1, Ordering::Relaxed);
+        if index.is_multiple_of(self.ingest_decimation) {
+            // 显示通道的结果不影响保存，通道满或断开时只是少画一个点
+            self.display.send(point.clone());
+        }
+        self.save.send(point)
+    }
+
+    /// 保存通道的丢弃数；显示通道的丢弃不影响数据完整性，不计入
+    pub fn dropped_count(&self) -> u64 {
+        self.save.dropped_count()
+    }
+}
+
+/// 传感器和音频通道的共享丢弃计数器，以及交给通道的数据总数
+#[derive(Debug, Clone)]
+pub struct ChannelDropCounters {
+    pub data: Arc<AtomicU64>,
+    pub audio: Arc<AtomicU64>,
+    pub data_received: Arc<AtomicU64>,
+    pub audio_received: Arc<AtomicU64>,
+}
+
+impl ChannelDropCounters {
+    pub fn data_received(&self) -> u64 {
+        self.data_received.load(Ordering::Relaxed)
+    }
+
+    pub fn audio_received(&self) -> u64 {
+        self.audio_received.load(Ordering::Relaxed)
+    }
+
+    pub fn data_dropped(&self) -> u64 {
+        self.data.load(Ordering::Relaxed)
+    }
+
+    pub fn audio_dropped(&self) -> u64 {
+        self.audio.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drop_oldest_reports_disconnect_after_gui_closes() {
+        let (sender, receiver) = crossbeam_channel::bounded(2);
+        let gui_alive = Arc::new(());
+        let sink = ChannelSink::new("test", sender, receiver.clone(), OverflowStrategy::DropOldest, Arc::downgrade(&gui_alive));
+
+        for value in 0..3 {
+            assert!(sink.send(value));
+        }
+        assert_eq!(sink.dropped_count(), 1);
+        assert_eq!(receiver.try_iter().collect::<Vec<_>>(), vec![1, 2]);
+
+        // 接收端克隆仍在sink中，通道本身不会断开
+        drop(receiver);
+        drop(gui_alive);
+        assert!(!sink.send(3));
+    }
+
+    #[test]
+    fn sensor_sinks_decimate_only_the_display_channel() {
+        let gui_alive = Arc::new(());
+        let (save_sender, save_receiver) = crossbeam_channel::bounded(16);
+        let (display_sender, display_receiver) = crossbeam_channel::bounded(16);
+        let sinks = SensorSinks::new(
+            ChannelSink::new("save", save_sender, save_receiver.clone(), OverflowStrategy::DropNewest, Arc::downgrade(&gui_alive)),
+            ChannelSink::new("display", display_sender, display_receiver.clone(), OverflowStrategy::DropOldest, Arc::downgrade(&gui_alive)),
+            3,
+        );
+
+        for timestamp in 0..7 {
+            assert!(sinks.send(DataPoint::new(0.0, 0.0, 0.0, 0.0, 0.0, 0.0, timestamp)));
+        }
+        let timestamps = |receiver: &Receiver<DataPoint>| receiver.try_iter().map(|point| point.timestamp).collect::<Vec<_>>();
+        assert_eq!(timestamps(&save_receiver), (0..7).collect::<Vec<_>>());
+        assert_eq!(timestamps(&display_receiver), vec![0, 3, 6]);
+    }
+}
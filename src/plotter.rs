@@ -1,11 +1,22 @@
-use egui_plot::{Line, Plot, PlotPoints};
-use egui::Color32;
-use std::collections::VecDeque;
-use crate::config::PlotConfig;
-
-/// 格式化数字为固定宽度的 y 轴标签
+use egui_plot::{GridInput, GridMark, Line, MarkerShape, Plot, PlotPoints, Points};
+use egui::{Color32, Stroke};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use crate::config::{AudioDisplayMode, AxisOffsets, AxisUnit, PlotConfig, SpectrogramColormap, YPaddingConfig, YRangeMode};
+use crate::spectrogram::SpectrogramCache;
+use crate::types::{DataPoint, DEFAULT_AUDIO_SAMPLE_RATE};
+
+/// 格式化数字为固定宽度的 y 轴标签，单位不是Raw时追加单位后缀
+fn format_fixed_width_y_label(value: f64, unit: AxisUnit) -> String {
+    let number = format_fixed_width_number(value);
+    match unit.suffix() {
+        "" => number,
+        suffix => format!("{} {}", number, suffix),
+    }
+}
 
-fn format_fixed_width_y_label(value: f64) -> String {
+fn format_fixed_width_number(value: f64) -> String {
     let abs_value = value.abs();
     // 根据数值大小和正负选择格式，全部固定为6字符宽度，并显式显示符号
     if abs_value >= 1000.0 {
@@ -26,8 +37,204 @@ fn format_fixed_width_y_label(value: f64) -> String {
     }
 }
 
+/// y轴标签的单位和换算系数：绘制的仍是原始值，只在刻度标签上换算
+#[derive(Debug, Clone, Copy)]
+struct AxisLabel {
+    unit: AxisUnit,
+    scale: f64,
+}
+
+impl AxisLabel {
+    const RAW: AxisLabel = AxisLabel { unit: AxisUnit::Raw, scale: 1.0 };
+
+    fn new(unit: AxisUnit, scale: Option<f64>) -> Self {
+        Self { unit, scale: scale.unwrap_or(1.0) }
+    }
+
+    fn format(&self, value: f64) -> String {
+        format_fixed_width_y_label(value * self.scale, self.unit)
+    }
+}
+
+/// 生成固定数量刻度的网格间隔函数，刻度均匀分布在当前可见范围内
+pub fn tick_spacer(tick_count: usize) -> impl Fn(GridInput) -> Vec<GridMark> {
+    let tick_count = tick_count.max(2);
+    move |input: GridInput| {
+        let (min, max) = input.bounds;
+        let step = (max - min) / (tick_count - 1) as f64;
+        if !step.is_finite() || step <= 0.0 {
+            return Vec::new();
+        }
+
+        (0..tick_count)
+            .map(|i| GridMark {
+                value: min + step * i as f64,
+                step_size: step,
+            })
+            .collect()
+    }
+}
+
+/// 居中滑动平均，窗口在两端自动截断；基于前缀和，复杂度O(n)，不修改原始数据
+pub fn moving_average(values: &[f64], window: usize) -> Vec<f64> {
+    if window <= 1 || values.is_empty() {
+        return values.to_vec();
+    }
+
+    let mut prefix = Vec::with_capacity(values.len() + 1);
+    let mut sum = 0.0;
+    prefix.push(sum);
+    for &value in values {
+        sum += value;
+        prefix.push(sum);
+    }
+
+    let half = window / 2;
+    (0..values.len())
+        .map(|i| {
+            let start = i.saturating_sub(half);
+            let end = (i + window - half).min(values.len());
+            (prefix[end] - prefix[start]) / (end - start) as f64
+        })
+        .collect()
+}
+
+/// 把数据均分为buckets段，返回每段的起始索引和最小/最大值，不修改原始数据
+pub fn min_max_envelope(values: &[f64], buckets: usize) -> Vec<(usize, f64, f64)> {
+    if values.is_empty() || buckets == 0 {
+        return Vec::new();
+    }
+
+    let chunk_size = values.len().div_ceil(buckets);
+    values
+        .chunks(chunk_size)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let (min, max) = chunk.iter().fold(
+                (f64::INFINITY, f64::NEG_INFINITY),
+                |(min, max), &val| (min.min(val), max.max(val))
+            );
+            (i * chunk_size, min, max)
+        })
+        .collect()
+}
+
+/// 把数据按block_size个样本分块，返回每块的起始索引和均方根值
+pub fn rms_envelope(values: &[f64], block_size: usize) -> Vec<(usize, f64)> {
+    if block_size == 0 {
+        return Vec::new();
+    }
+
+    values
+        .chunks(block_size)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let mean_square = chunk.iter().map(|v| v * v).sum::<f64>() / chunk.len() as f64;
+            (i * block_size, mean_square.sqrt())
+        })
+        .collect()
+}
+
+/// 历史音频波形的绘制数据：时间（秒）-幅值点、幅值范围和总时长
+#[derive(Debug, Clone, Default)]
+pub struct AudioWaveformPoints {
+    pub points: Vec<[f64; 2]>,
+    pub y_range: (f64, f64),
+    pub duration: f64,
+}
+
+impl AudioWaveformPoints {
+    /// buckets为Some时按桶绘制最小/最大值包络，点数只取决于桶数；样本不超过两倍桶数时仍绘制全部样本
+    fn new(samples: &[f64], sample_rate: f64, buckets: Option<usize>) -> Self {
+        let y_range = samples.iter().fold(
+            (f64::INFINITY, f64::NEG_INFINITY),
+            |(min, max), &val| (min.min(val), max.max(val))
+        );
+
+        let points = match buckets.filter(|&buckets| buckets > 0 && samples.len() > buckets * 2) {
+            // 每桶先画最小值再画最大值，折线在桶内形成竖线，整体即为波形包络
+            Some(buckets) => min_max_envelope(samples, buckets)
+                .into_iter()
+                .flat_map(|(start, min, max)| {
+                    let time = start as f64 / sample_rate;
+                    [[time, min], [time, max]]
+                })
+                .collect(),
+            None => samples
+                .iter()
+                .enumerate()
+                .map(|(i, &value)| [i as f64 / sample_rate, value])
+                .collect(),
+        };
+
+        Self {
+            points,
+            y_range,
+            duration: samples.len() as f64 / sample_rate,
+        }
+    }
+}
+
+/// 历史音频波形绘制数据缓存，采样率或桶数变化时自动重新计算；
+/// 音频数据本身不参与比较，替换音频数据的地方必须调用 `invalidate`
+#[derive(Debug, Clone, Default)]
+pub struct AudioWaveformCache {
+    source_key: Option<(u64, Option<usize>)>,
+    waveform: AudioWaveformPoints,
+}
+
+impl AudioWaveformCache {
+    /// 音频数据已被替换，下一次绘制时重新计算
+    pub fn invalidate(&mut self) {
+        self.source_key = None;
+    }
+
+    pub fn waveform(&mut self, samples: &[f64], sample_rate: f64, buckets: Option<usize>) -> &AudioWaveformPoints {
+        let key = (sample_rate.to_bits(), buckets);
+
+        if self.source_key != Some(key) {
+            self.source_key = Some(key);
+            self.waveform = AudioWaveformPoints::new(samples, sample_rate, buckets);
+        }
+
+        &self.waveform
+    }
+}
+
+/// 峰值之间的最小间隔（秒），避免同一次冲击的抖动被标成多个峰
+const PEAK_MIN_DISTANCE_SECONDS: f64 = 0.2;
+
+/// 查找高于阈值的局部极大值，返回索引（升序）
+/// 相距不足 min_distance 个样本的峰只保留较高的一个
+pub fn find_peaks(buf: &VecDeque<f64>, min_height: f64, min_distance: usize) -> Vec<usize> {
+    if buf.len() < 3 {
+        return Vec::new();
+    }
+
+    // 平顶峰取第一个样本：左侧严格上升、右侧不上升
+    let mut candidates: Vec<usize> = (1..buf.len() - 1)
+        .filter(|&i| buf[i] >= min_height && buf[i] > buf[i - 1] && buf[i] >= buf[i + 1])
+        .collect();
+
+    if min_distance > 1 && candidates.len() > 1 {
+        // 按高度从高到低保留，与已保留的峰距离过近的丢弃
+        candidates.sort_by(|&a, &b| buf[b].total_cmp(&buf[a]));
+        let mut kept: Vec<usize> = Vec::with_capacity(candidates.len());
+        for i in candidates {
+            if kept.iter().all(|&k| k.abs_diff(i) >= min_distance) {
+                kept.push(i);
+            }
+        }
+        kept.sort_unstable();
+        candidates = kept;
+    }
+
+    candidates
+}
+
 #[derive(Debug)]
 pub struct WaveformPlot {
+    // 保存用的缓冲区，来自保存通道，包含每个数据点
     buffer_x: VecDeque<f64>,
     buffer_y: VecDeque<f64>,
     buffer_z: VecDeque<f64>,
@@ -35,54 +242,122 @@ pub struct WaveformPlot {
     buffer_gy: VecDeque<f64>,  // 陀螺仪 Y 轴缓冲区
     buffer_gz: VecDeque<f64>,  // 陀螺仪 Z 轴缓冲区
     buffer_timestamp: VecDeque<i64>, // 添加时间戳缓冲区
+    buffer_orientation: VecDeque<Option<[f64; 4]>>, // 姿态四元数缓冲区（仅保存用，不绘制）
+    buffer_node_id: VecDeque<Option<String>>, // 节点ID缓冲区（仅保存用）
+    buffer_temperature: VecDeque<Option<f64>>, // 芯片温度缓冲区，设备不发送时为None
+    buffer_arrival_timestamp: VecDeque<Option<i64>>, // 主机接收时间戳缓冲区（仅保存用）
+    // 绘制用的缓冲区，来自显示通道，每ingest_decimation个数据点只有1个
+    display_x: VecDeque<f64>,
+    display_y: VecDeque<f64>,
+    display_z: VecDeque<f64>,
+    display_gx: VecDeque<f64>,
+    display_gy: VecDeque<f64>,
+    display_gz: VecDeque<f64>,
+    display_temperature: VecDeque<Option<f64>>,
+    display_max_samples: usize, // 显示窗口内显示通道的数据点数
     audio_buffer: VecDeque<f64>,
     audio_timestamps: VecDeque<i64>, // 添加音频时间戳缓冲区
     max_samples: usize,
     window_duration: f64, // 窗口持续时间（秒）
+    display_decimation: usize, // 绘制时每N个点取1个
+    axis_offsets: AxisOffsets, // 绘制时从各轴减去的零偏，缓冲区和保存的数据保持原始值
+    tick_count_x: usize,
+    tick_count_y: usize,
+    y_padding: YPaddingConfig,
+    show_peaks: bool,
+    peak_threshold: f64,
+    show_axes: bool,
+    show_grid: bool,
+    background_color: Option<[u8; 3]>, // None时使用主题默认背景
+    locked_y_ranges: RefCell<HashMap<String, (f64, f64)>>, // 锁定模式下各图只扩大不缩小的y轴范围
+    audio_spectrogram: RefCell<SpectrogramCache>, // 频谱图显示模式下的纹理缓存
+    save_max_samples: usize, // 保存窗口样本数
+    buffer_capacity: usize,  // 缓冲区容量，取显示窗口与保存窗口中较大者
     // 音频相关
     audio_max_samples: usize,
     audio_window_duration: f64,
+    audio_save_max_samples: usize,
+    audio_buffer_capacity: usize,
 }
 
 impl WaveformPlot {
-    pub fn new(sample_rate: usize, config: &PlotConfig) -> Self {
+    /// ingest_decimation为MQTT线程向显示通道转发数据点的间隔，决定显示缓冲区的采样率
+    pub fn new(sample_rate: usize, config: &PlotConfig, ingest_decimation: usize) -> Self {
         let window_seconds = config.window_duration_seconds;
+        let save_window_seconds = config.save_window_duration_seconds;
         let max_samples = (window_seconds * sample_rate as f64) as usize;
+        let display_max_samples = max_samples.div_ceil(ingest_decimation.max(1)).max(1);
+        let save_max_samples = (save_window_seconds * sample_rate as f64) as usize;
+        let buffer_capacity = max_samples.max(save_max_samples);
 
         // 音频缓冲区 - 直接使用16kHz音频数据，不下采样
         // 使用统一的窗口长度配置
-        let audio_sample_rate = 16000; // 16kHz完整采样率
+        let audio_sample_rate = DEFAULT_AUDIO_SAMPLE_RATE; // 16kHz完整采样率
         let audio_max_samples = (window_seconds * audio_sample_rate as f64) as usize;
+        let audio_save_max_samples = (save_window_seconds * audio_sample_rate as f64) as usize;
+        let audio_buffer_capacity = audio_max_samples.max(audio_save_max_samples);
 
         Self {
-            buffer_x: VecDeque::with_capacity(max_samples),
-            buffer_y: VecDeque::with_capacity(max_samples),
-            buffer_z: VecDeque::with_capacity(max_samples),
-            buffer_gx: VecDeque::with_capacity(max_samples),  // 初始化陀螺仪缓冲区
-            buffer_gy: VecDeque::with_capacity(max_samples),
-            buffer_gz: VecDeque::with_capacity(max_samples),
-            buffer_timestamp: VecDeque::with_capacity(max_samples), // 初始化时间戳缓冲区
-            audio_buffer: VecDeque::with_capacity(audio_max_samples),
-            audio_timestamps: VecDeque::with_capacity(audio_max_samples), // 初始化音频时间戳缓冲区
+            buffer_x: VecDeque::with_capacity(buffer_capacity),
+            buffer_y: VecDeque::with_capacity(buffer_capacity),
+            buffer_z: VecDeque::with_capacity(buffer_capacity),
+            buffer_gx: VecDeque::with_capacity(buffer_capacity),  // 初始化陀螺仪缓冲区
+            buffer_gy: VecDeque::with_capacity(buffer_capacity),
+            buffer_gz: VecDeque::with_capacity(buffer_capacity),
+            buffer_timestamp: VecDeque::with_capacity(buffer_capacity), // 初始化时间戳缓冲区
+            buffer_orientation: VecDeque::with_capacity(buffer_capacity),
+            buffer_node_id: VecDeque::with_capacity(buffer_capacity),
+            buffer_temperature: VecDeque::with_capacity(buffer_capacity),
+            buffer_arrival_timestamp: VecDeque::with_capacity(buffer_capacity),
+            display_x: VecDeque::with_capacity(display_max_samples),
+            display_y: VecDeque::with_capacity(display_max_samples),
+            display_z: VecDeque::with_capacity(display_max_samples),
+            display_gx: VecDeque::with_capacity(display_max_samples),
+            display_gy: VecDeque::with_capacity(display_max_samples),
+            display_gz: VecDeque::with_capacity(display_max_samples),
+            display_temperature: VecDeque::with_capacity(display_max_samples),
+            display_max_samples,
+            audio_buffer: VecDeque::with_capacity(audio_buffer_capacity),
+            audio_timestamps: VecDeque::with_capacity(audio_buffer_capacity), // 初始化音频时间戳缓冲区
             max_samples,
             window_duration: window_seconds,
+            display_decimation: config.display_decimation.max(1),
+            axis_offsets: AxisOffsets::default(),
+            tick_count_x: config.tick_count_x,
+            tick_count_y: config.tick_count_y,
+            y_padding: config.y_padding,
+            show_peaks: config.show_peaks,
+            peak_threshold: config.peak_threshold,
+            show_axes: config.show_axes,
+            show_grid: config.show_grid,
+            background_color: config.background_color,
+            locked_y_ranges: RefCell::new(HashMap::new()),
+            audio_spectrogram: RefCell::new(SpectrogramCache::default()),
+            save_max_samples,
+            buffer_capacity,
             audio_max_samples,
             audio_window_duration: window_seconds, // 使用统一的窗口长度
+            audio_save_max_samples,
+            audio_buffer_capacity,
         }
     }
 
-    pub fn add_data(&mut self, x: f64, y: f64, z: f64, gx: f64, gy: f64, gz: f64, timestamp: i64) {
+    pub fn add_data(&mut self, point: &DataPoint) {
         // 将新数据添加到缓冲区末尾
-        self.buffer_x.push_back(x);
-        self.buffer_y.push_back(y);
-        self.buffer_z.push_back(z);
-        self.buffer_gx.push_back(gx);
-        self.buffer_gy.push_back(gy);
-        self.buffer_gz.push_back(gz);
-        self.buffer_timestamp.push_back(timestamp);
-
-        // 如果超过最大样本数，移除最旧的数据（从前面移除）- O(1)操作
-        if self.buffer_x.len() > self.max_samples {
+        self.buffer_x.push_back(point.x);
+        self.buffer_y.push_back(point.y);
+        self.buffer_z.push_back(point.z);
+        self.buffer_gx.push_back(point.gx);
+        self.buffer_gy.push_back(point.gy);
+        self.buffer_gz.push_back(point.gz);
+        self.buffer_timestamp.push_back(point.timestamp);
+        self.buffer_orientation.push_back(point.orientation);
+        self.buffer_node_id.push_back(point.node_id.clone());
+        self.buffer_temperature.push_back(point.temperature_c);
+        self.buffer_arrival_timestamp.push_back(point.arrival_timestamp);
+
+        // 如果超过缓冲区容量，移除最旧的数据（从前面移除）- O(1)操作
+        if self.buffer_x.len() > self.buffer_capacity {
             self.buffer_x.pop_front();
             self.buffer_y.pop_front();
             self.buffer_z.pop_front();
@@ -90,9 +365,38 @@ impl WaveformPlot {
             self.buffer_gy.pop_front();
             self.buffer_gz.pop_front();
             self.buffer_timestamp.pop_front();
+            self.buffer_orientation.pop_front();
+            self.buffer_node_id.pop_front();
+            self.buffer_temperature.pop_front();
+            self.buffer_arrival_timestamp.pop_front();
+        }
+    }
+
+    /// 添加显示通道的数据点，只保留显示窗口内的部分
+    pub fn add_display_data(&mut self, point: &DataPoint) {
+        self.display_x.push_back(point.x);
+        self.display_y.push_back(point.y);
+        self.display_z.push_back(point.z);
+        self.display_gx.push_back(point.gx);
+        self.display_gy.push_back(point.gy);
+        self.display_gz.push_back(point.gz);
+        self.display_temperature.push_back(point.temperature_c);
+
+        if self.display_x.len() > self.display_max_samples {
+            self.display_x.pop_front();
+            self.display_y.pop_front();
+            self.display_z.pop_front();
+            self.display_gx.pop_front();
+            self.display_gy.pop_front();
+            self.display_gz.pop_front();
+            self.display_temperature.pop_front();
         }
     }
 
+    pub fn set_axis_offsets(&mut self, offsets: AxisOffsets) {
+        self.axis_offsets = offsets;
+    }
+
     pub fn add_audio_samples(&mut self, samples: &[i16], base_timestamp: i64, sample_rate: u32) {
         // 批量转换音频样本为归一化的f64值 (-1.0 到 1.0)
         let normalized_samples: Vec<f64> = samples
@@ -110,156 +414,380 @@ impl WaveformPlot {
         self.audio_buffer.extend(normalized_samples);
         self.audio_timestamps.extend(timestamps);
 
-        // 如果超过最大样本数，批量移除最旧的数据 - O(1)操作
-        while self.audio_buffer.len() > self.audio_max_samples {
+        // 如果超过缓冲区容量，批量移除最旧的数据 - O(1)操作
+        while self.audio_buffer.len() > self.audio_buffer_capacity {
             self.audio_buffer.pop_front();
             self.audio_timestamps.pop_front();
         }
     }
 
-    pub fn ui(&self, ui: &mut egui::Ui, config: &PlotConfig) {
+    pub fn ui(&mut self, ui: &mut egui::Ui, config: &PlotConfig) {
+        self.y_padding = config.y_padding;
+        self.show_peaks = config.show_peaks;
+        self.peak_threshold = config.peak_threshold;
+        self.show_axes = config.show_axes;
+        self.show_grid = config.show_grid;
+        self.background_color = config.background_color;
+
+        let acc_label = AxisLabel::new(config.acc_unit, config.acc_scale);
+        let gyro_label = AxisLabel::new(config.gyro_unit, config.gyro_scale);
+
+        // 右键菜单中请求的清空操作，在绘制完成后执行
+        let mut clear_accelerometer = false;
+        let mut clear_audio = false;
+
         egui::ScrollArea::vertical().show(ui, |ui| {
             ui.vertical(|ui| {
                 // 加速度计数据显示
-                ui.heading("Accelerometer");
-                self.plot_axis(ui, "ACC X Axis", &self.buffer_x, 
-                    Color32::from_rgb(config.colors.x_axis[0], config.colors.x_axis[1], config.colors.x_axis[2]));
-                self.plot_axis(ui, "ACC Y Axis", &self.buffer_y, 
-                    Color32::from_rgb(config.colors.y_axis[0], config.colors.y_axis[1], config.colors.y_axis[2]));
-                self.plot_axis(ui, "ACC Z Axis", &self.buffer_z, 
-                    Color32::from_rgb(config.colors.z_axis[0], config.colors.z_axis[1], config.colors.z_axis[2]));
+                ui.heading("Accelerometer").context_menu(|ui| {
+                    if ui.button("Clear accelerometer buffer").clicked() {
+                        clear_accelerometer = true;
+                        ui.close();
+                    }
+                });
+                let offsets = self.axis_offsets;
+                self.plot_axis(ui, "ACC X Axis", config.y_range.accelerometer, acc_label, &subtract_offset(&self.display_x, offsets.x), 
+                    Stroke::new(config.line_width.accelerometer, Color32::from_rgb(config.colors.x_axis[0], config.colors.x_axis[1], config.colors.x_axis[2])));
+                self.plot_axis(ui, "ACC Y Axis", config.y_range.accelerometer, acc_label, &subtract_offset(&self.display_y, offsets.y), 
+                    Stroke::new(config.line_width.accelerometer, Color32::from_rgb(config.colors.y_axis[0], config.colors.y_axis[1], config.colors.y_axis[2])));
+                self.plot_axis(ui, "ACC Z Axis", config.y_range.accelerometer, acc_label, &subtract_offset(&self.display_z, offsets.z), 
+                    Stroke::new(config.line_width.accelerometer, Color32::from_rgb(config.colors.z_axis[0], config.colors.z_axis[1], config.colors.z_axis[2])));
 
                 ui.separator();
                 
                 // 陀螺仪数据显示（可选）
                 if config.show_gyroscope {
                     ui.heading("Gyroscope");
-                    self.plot_axis(ui, "GYRO X Axis", &self.buffer_gx, 
-                        Color32::from_rgb(config.colors.gyro_x[0], config.colors.gyro_x[1], config.colors.gyro_x[2]));
-                    self.plot_axis(ui, "GYRO Y Axis", &self.buffer_gy, 
-                        Color32::from_rgb(config.colors.gyro_y[0], config.colors.gyro_y[1], config.colors.gyro_y[2]));
-                    self.plot_axis(ui, "GYRO Z Axis", &self.buffer_gz, 
-                        Color32::from_rgb(config.colors.gyro_z[0], config.colors.gyro_z[1], config.colors.gyro_z[2]));
+                    self.plot_axis(ui, "GYRO X Axis", config.y_range.gyroscope, gyro_label, &subtract_offset(&self.display_gx, offsets.gx), 
+                        Stroke::new(config.line_width.gyroscope, Color32::from_rgb(config.colors.gyro_x[0], config.colors.gyro_x[1], config.colors.gyro_x[2])));
+                    self.plot_axis(ui, "GYRO Y Axis", config.y_range.gyroscope, gyro_label, &subtract_offset(&self.display_gy, offsets.gy), 
+                        Stroke::new(config.line_width.gyroscope, Color32::from_rgb(config.colors.gyro_y[0], config.colors.gyro_y[1], config.colors.gyro_y[2])));
+                    self.plot_axis(ui, "GYRO Z Axis", config.y_range.gyroscope, gyro_label, &subtract_offset(&self.display_gz, offsets.gz), 
+                        Stroke::new(config.line_width.gyroscope, Color32::from_rgb(config.colors.gyro_z[0], config.colors.gyro_z[1], config.colors.gyro_z[2])));
+
+                    ui.separator();
+                }
+
+                // 芯片温度显示（可选，只在设备发送了温度时绘制）
+                if config.show_temperature && self.display_temperature.iter().any(Option::is_some) {
+                    ui.heading("Temperature");
+                    let temperatures: VecDeque<f64> = self.display_temperature.iter().flatten().copied().collect();
+                    self.plot_axis(ui, "TEMP (°C)", YRangeMode::Auto, AxisLabel::RAW, &temperatures,
+                        Stroke::new(config.line_width.gyroscope, Color32::from_rgb(220, 80, 40)));
 
                     ui.separator();
                 }
 
                 // 添加音频波形显示
-                ui.heading("Audio");
-                self.plot_audio(ui, "Audio Waveform", &self.audio_buffer, 
-                    Color32::from_rgb(config.colors.audio[0], config.colors.audio[1], config.colors.audio[2]));
+                ui.heading("Audio").context_menu(|ui| {
+                    if ui.button("Clear audio buffer").clicked() {
+                        clear_audio = true;
+                        ui.close();
+                    }
+                });
+                let audio_stroke = Stroke::new(config.line_width.audio, Color32::from_rgb(config.colors.audio[0], config.colors.audio[1], config.colors.audio[2]));
+                match config.audio_display_mode {
+                    AudioDisplayMode::Waveform => {
+                        self.plot_audio(ui, "Audio Waveform", config.y_range.audio, &self.audio_buffer, audio_stroke);
+                    }
+                    AudioDisplayMode::RmsEnvelope => {
+                        self.plot_audio_rms(ui, "Audio RMS", config.y_range.audio, &self.audio_buffer, audio_stroke);
+                    }
+                    AudioDisplayMode::Spectrogram => {
+                        self.plot_audio_spectrogram(ui, &self.audio_buffer, config.spectrogram_colormap);
+                    }
+                }
             });
         });
+
+        if clear_accelerometer {
+            self.clear_accelerometer_only();
+        }
+        if clear_audio {
+            self.clear_audio_only();
+        }
+    }
+
+    /// 只清空加速度计和陀螺仪缓冲区，保留音频数据
+    pub fn clear_accelerometer_only(&mut self) {
+        self.buffer_x.clear();
+        self.buffer_y.clear();
+        self.buffer_z.clear();
+        self.buffer_gx.clear();
+        self.buffer_gy.clear();
+        self.buffer_gz.clear();
+        self.buffer_timestamp.clear();
+        self.buffer_orientation.clear();
+        self.buffer_node_id.clear();
+        self.buffer_temperature.clear();
+        self.buffer_arrival_timestamp.clear();
+        self.display_x.clear();
+        self.display_y.clear();
+        self.display_z.clear();
+        self.display_gx.clear();
+        self.display_gy.clear();
+        self.display_gz.clear();
+        self.display_temperature.clear();
+        self.locked_y_ranges
+            .borrow_mut()
+            .retain(|title, _| !title.starts_with("ACC") && !title.starts_with("GYRO") && !title.starts_with("TEMP"));
+    }
+
+    /// 只清空音频缓冲区，保留加速度计数据
+    pub fn clear_audio_only(&mut self) {
+        self.audio_buffer.clear();
+        self.audio_timestamps.clear();
+        self.locked_y_ranges.borrow_mut().remove("Audio Waveform");
     }
 
-    fn plot_axis(&self, ui: &mut egui::Ui, title: &str, buffer: &VecDeque<f64>, color: Color32) {
+    fn plot_axis(&self, ui: &mut egui::Ui, title: &str, y_range_mode: YRangeMode, y_label: AxisLabel, buffer: &VecDeque<f64>, stroke: Stroke) {
         if buffer.is_empty() {
             return;
         }
 
+        // 只显示缓冲区末尾的显示窗口部分
+        let start = tail_start(buffer.len(), self.display_max_samples);
+        let dt = self.window_duration / (self.display_max_samples as f64);
+
+        // 峰值标记：▲画在原始数据点上，不受显示抽样影响
+        let peak_coords: Vec<[f64; 2]> = if self.show_peaks {
+            find_peaks(buffer, self.peak_threshold, self.peak_min_distance())
+                .into_iter()
+                .filter(|&i| i >= start)
+                .map(|i| [(i - start) as f64 * dt, buffer[i]])
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let buffer = buffer.range(start..);
+
         // 计算动态Y轴范围
-        let (y_min, y_max) = buffer.iter().fold(
+        let (y_min, y_max) = buffer.clone().fold(
             (f64::INFINITY, f64::NEG_INFINITY),
             |(min, max), &val| (min.min(val), max.max(val))
         );
 
-        let range = (y_max - y_min).max(0.1);
-        let y_min = y_min - range * 0.05;
-        let y_max = y_max + range * 0.05;
-
-        Plot::new(title)
-            .height(100.0)
-            .x_axis_formatter(|v, _| format!("{:.1}s", v.value))
-            .y_axis_formatter(|v, _| format_fixed_width_y_label(v.value))
-            .show_x(false)
-            .show_y(false)
-            .allow_drag(false)
-            .allow_zoom(false)
-            .show(ui, |plot_ui| {
-                // 计算时间点：最旧的数据在左侧（时间=0），最新的数据在右侧（时间=window_duration）
-                let data_len = buffer.len();
-                if data_len == 0 {
-                    return;
-                }
+        let (y_min, y_max) = self.resolve_y_range(title, y_range_mode, self.y_padding.apply(y_min, y_max));
+
+        self.with_plot_background(ui, |ui| {
+            self.apply_plot_style(Plot::new(title))
+                .height(100.0)
+                .x_axis_formatter(|v, _| format!("{:.1}s", v.value))
+                .y_axis_formatter(move |v, _| y_label.format(v.value))
+                .x_grid_spacer(tick_spacer(self.tick_count_x))
+                .y_grid_spacer(tick_spacer(self.tick_count_y))
+                .show_x(false)
+                .show_y(false)
+                .allow_drag(false)
+                .allow_zoom(false)
+                .show(ui, |plot_ui| {
+                    // 计算时间点：最旧的数据在左侧（时间=0），最新的数据在右侧（时间=window_duration）
+                    let data_len = buffer.len();
+                    if data_len == 0 {
+                        return;
+                    }
+
+                    // 从左到右的时间轴：最旧数据时间为0，向右递增（按显示抽样率取点）
+                    let points: Vec<[f64; 2]> = buffer
+                        .clone()
+                        .enumerate()
+                        .step_by(self.display_decimation)
+                        .map(|(i, &y)| {
+                            // 索引0是最旧的数据，索引data_len-1是最新的数据
+                            let time = i as f64 * dt; // 正时间，从0开始递增
+                            [time, y]
+                        })
+                        .collect();
+
+                    plot_ui.set_plot_bounds(egui_plot::PlotBounds::from_min_max(
+                        [0.0, y_min],
+                        [self.window_duration, y_max],
+                    ));
+
+                    plot_ui.line(Line::new(title, PlotPoints::from(points)).stroke(stroke));
+
+                    if !peak_coords.is_empty() {
+                        plot_ui.points(
+                            Points::new("peaks", PlotPoints::from(peak_coords))
+                                .shape(MarkerShape::Up)
+                                .filled(true)
+                                .radius(3.0)
+                                .color(stroke.color),
+                        );
+                    }
+                });
+        });
+    }
 
-                let dt = self.window_duration / (self.max_samples as f64);
-
-                // 从左到右的时间轴：最旧数据时间为0，向右递增
-                let points: Vec<[f64; 2]> = buffer
-                    .iter()
-                    .enumerate()
-                    .map(|(i, &y)| {
-                        // 索引0是最旧的数据，索引data_len-1是最新的数据
-                        let time = i as f64 * dt; // 正时间，从0开始递增
-                        [time, y]
-                    })
-                    .collect();
-
-                plot_ui.set_plot_bounds(egui_plot::PlotBounds::from_min_max(
-                    [0.0, y_min],
-                    [self.window_duration, y_max],
-                ));
-
-                plot_ui.line(Line::new(title, PlotPoints::from(points)).color(color).width(1.0));
-            });
+    /// 按配置设置坐标轴和网格线
+    fn apply_plot_style<'a>(&self, plot: Plot<'a>) -> Plot<'a> {
+        plot.show_axes(self.show_axes).show_grid(self.show_grid)
+    }
+
+    /// egui_plot的背景取自visuals.extreme_bg_color，配置了背景色时在局部作用域内替换
+    fn with_plot_background<R>(&self, ui: &mut egui::Ui, add_contents: impl FnOnce(&mut egui::Ui) -> R) -> R {
+        ui.scope(|ui| {
+            if let Some([r, g, b]) = self.background_color {
+                ui.visuals_mut().extreme_bg_color = Color32::from_rgb(r, g, b);
+            }
+            add_contents(ui)
+        })
+        .inner
+    }
+
+    /// 峰值最小间隔对应的样本数
+    fn peak_min_distance(&self) -> usize {
+        let sample_rate = self.display_max_samples as f64 / self.window_duration.max(f64::EPSILON);
+        (PEAK_MIN_DISTANCE_SECONDS * sample_rate).round().max(1.0) as usize
     }
 
-    fn plot_audio(&self, ui: &mut egui::Ui, title: &str, buffer: &VecDeque<f64>, color: Color32) {
+    fn plot_audio(&self, ui: &mut egui::Ui, title: &str, y_range_mode: YRangeMode, buffer: &VecDeque<f64>, stroke: Stroke) {
         if buffer.is_empty() {
             return;
         }
 
+        // 只显示缓冲区末尾的显示窗口部分
+        let buffer = buffer.range(tail_start(buffer.len(), self.audio_max_samples)..);
+
         // 计算音频数据的动态Y轴范围
-        let (y_min, y_max) = buffer.iter().fold(
+        let (y_min, y_max) = buffer.clone().fold(
             (f64::INFINITY, f64::NEG_INFINITY),
             |(min, max), &val| (min.min(val), max.max(val))
         );
 
-        let range = (y_max - y_min).max(0.1);
-        let y_min = y_min - range * 0.05;
-        let y_max = y_max + range * 0.05;
-
-        Plot::new(title)
-            .height(100.0)
-            .x_axis_formatter(|v, _| format!("{:.2}s", v.value))
-            .y_axis_formatter(|v, _| format_fixed_width_y_label(v.value))
-            .show_x(false)
-            .show_y(false)
-            .allow_drag(false)
-            .allow_zoom(false)
-            .show(ui, |plot_ui| {
-                // 计算时间点：最旧的数据在左侧（时间=0），最新的数据在右侧（时间=window_duration）
-                let data_len = buffer.len();
-                if data_len == 0 {
-                    return;
-                }
+        let (y_min, y_max) = self.resolve_y_range(title, y_range_mode, self.y_padding.apply(y_min, y_max));
+
+        self.with_plot_background(ui, |ui| {
+            self.apply_plot_style(Plot::new(title))
+                .height(100.0)
+                .x_axis_formatter(|v, _| format!("{:.2}s", v.value))
+                .y_axis_formatter(|v, _| format_fixed_width_y_label(v.value, AxisUnit::Raw))
+                .x_grid_spacer(tick_spacer(self.tick_count_x))
+                .y_grid_spacer(tick_spacer(self.tick_count_y))
+                .show_x(false)
+                .show_y(false)
+                .allow_drag(false)
+                .allow_zoom(false)
+                .show(ui, |plot_ui| {
+                    // 计算时间点：最旧的数据在左侧（时间=0），最新的数据在右侧（时间=window_duration）
+                    let data_len = buffer.len();
+                    if data_len == 0 {
+                        return;
+                    }
+
+                    let dt = self.audio_window_duration / (self.audio_max_samples as f64);
+
+                    // 从左到右的时间轴：最旧数据时间为0，向右递增
+                    let points: Vec<[f64; 2]> = buffer
+                        .clone()
+                        .enumerate()
+                        .map(|(i, &y)| {
+                            // 索引0是最旧的数据，索引data_len-1是最新的数据
+                            let time = i as f64 * dt; // 正时间，从0开始递增
+                            [time, y]
+                        })
+                        .collect();
+
+                    plot_ui.set_plot_bounds(egui_plot::PlotBounds::from_min_max(
+                        [0.0, y_min],
+                        [self.audio_window_duration, y_max],
+                    ));
+
+                    plot_ui.line(Line::new(title, PlotPoints::from(points)).stroke(stroke));
+                });
+        });
+    }
 
-                let dt = self.audio_window_duration / (self.audio_max_samples as f64);
-
-                // 从左到右的时间轴：最旧数据时间为0，向右递增
-                let points: Vec<[f64; 2]> = buffer
-                    .iter()
-                    .enumerate()
-                    .map(|(i, &y)| {
-                        // 索引0是最旧的数据，索引data_len-1是最新的数据
-                        let time = i as f64 * dt; // 正时间，从0开始递增
-                        [time, y]
-                    })
-                    .collect();
-
-                plot_ui.set_plot_bounds(egui_plot::PlotBounds::from_min_max(
-                    [0.0, y_min],
-                    [self.audio_window_duration, y_max],
-                ));
-
-                plot_ui.line(Line::new(title, PlotPoints::from(points)).color(color).width(1.0));
-            });
+    /// 按像素宽度分块计算RMS并绘制正负对称的包络，点数只取决于绘图宽度
+    fn plot_audio_rms(&self, ui: &mut egui::Ui, title: &str, y_range_mode: YRangeMode, buffer: &VecDeque<f64>, stroke: Stroke) {
+        if buffer.is_empty() {
+            return;
+        }
+
+        let samples: Vec<f64> = buffer.range(tail_start(buffer.len(), self.audio_max_samples)..).copied().collect();
+        let block_size = samples.len().div_ceil(ui.available_width().max(1.0) as usize).max(1);
+        let envelope = rms_envelope(&samples, block_size);
+
+        let max_rms = envelope.iter().fold(0.0f64, |max, &(_, rms)| max.max(rms));
+        let (y_min, y_max) = self.resolve_y_range(title, y_range_mode, self.y_padding.apply(-max_rms, max_rms));
+        let dt = self.audio_window_duration / (self.audio_max_samples as f64);
+
+        self.with_plot_background(ui, |ui| {
+            self.apply_plot_style(Plot::new(title))
+                .height(100.0)
+                .x_axis_formatter(|v, _| format!("{:.2}s", v.value))
+                .y_axis_formatter(|v, _| format_fixed_width_y_label(v.value, AxisUnit::Raw))
+                .x_grid_spacer(tick_spacer(self.tick_count_x))
+                .y_grid_spacer(tick_spacer(self.tick_count_y))
+                .show_x(false)
+                .show_y(false)
+                .allow_drag(false)
+                .allow_zoom(false)
+                .show(ui, |plot_ui| {
+                    let upper: Vec<[f64; 2]> = envelope.iter().map(|&(i, rms)| [i as f64 * dt, rms]).collect();
+                    let lower: Vec<[f64; 2]> = envelope.iter().map(|&(i, rms)| [i as f64 * dt, -rms]).collect();
+
+                    plot_ui.set_plot_bounds(egui_plot::PlotBounds::from_min_max(
+                        [0.0, y_min],
+                        [self.audio_window_duration, y_max],
+                    ));
+
+                    plot_ui.line(Line::new(title, PlotPoints::from(upper)).stroke(stroke));
+                    plot_ui.line(Line::new(title, PlotPoints::from(lower)).stroke(stroke));
+                });
+        });
+    }
+
+    /// 以频谱图显示显示窗口内的音频，音频数据变化时才重新计算纹理
+    fn plot_audio_spectrogram(&self, ui: &mut egui::Ui, buffer: &VecDeque<f64>, colormap: SpectrogramColormap) {
+        let samples: Vec<f64> = buffer.range(tail_start(buffer.len(), self.audio_max_samples)..).copied().collect();
+        let texture = self.audio_spectrogram.borrow_mut().texture(ui.ctx(), &samples, colormap).cloned();
+
+        match texture {
+            Some(texture) => {
+                ui.add(egui::Image::new(&texture).fit_to_exact_size(egui::vec2(ui.available_width(), 100.0)));
+            }
+            None => {
+                ui.colored_label(Color32::GRAY, "Not enough audio for a spectrogram");
+            }
+        }
+    }
+
+    /// 根据y轴范围模式确定最终显示范围，auto_range为按当前数据计算的范围
+    fn resolve_y_range(&self, title: &str, mode: YRangeMode, auto_range: (f64, f64)) -> (f64, f64) {
+        let mut locked_ranges = self.locked_y_ranges.borrow_mut();
+        match mode {
+            YRangeMode::Auto => {
+                locked_ranges.remove(title);
+                auto_range
+            }
+            YRangeMode::Fixed { min, max } => {
+                locked_ranges.remove(title);
+                (min, max)
+            }
+            YRangeMode::Locked => {
+                let locked = locked_ranges.entry(title.to_string()).or_insert(auto_range);
+                locked.0 = locked.0.min(auto_range.0);
+                locked.1 = locked.1.max(auto_range.1);
+                *locked
+            }
+        }
+    }
+
+    // 获取当前缓冲区数据的方法（按保存窗口长度截取）
+    pub fn get_current_accelerometer_data(&self) -> Vec<DataPoint> {
+        self.accelerometer_data_from(tail_start(self.buffer_x.len(), self.save_max_samples))
+    }
+
+    /// 缓冲区末尾最近seconds秒的加速度数据（原始值，未减零偏）
+    pub fn recent_accelerometer_data(&self, seconds: f64) -> Vec<DataPoint> {
+        let count = (seconds * self.max_samples as f64 / self.window_duration) as usize;
+        self.accelerometer_data_from(tail_start(self.buffer_x.len(), count))
     }
 
-    // 获取当前缓冲区数据的方法
-    pub fn get_current_accelerometer_data(&self) -> Vec<(f64, f64, f64, f64, f64, f64, i64)> {
+    fn accelerometer_data_from(&self, start: usize) -> Vec<DataPoint> {
         let mut data = Vec::new();
-        for i in 0..self.buffer_x.len() {
+        for i in start..self.buffer_x.len() {
             if let (Some(&x), Some(&y), Some(&z), Some(&gx), Some(&gy), Some(&gz), Some(&timestamp)) = (
                 self.buffer_x.get(i),
                 self.buffer_y.get(i),
@@ -269,29 +797,67 @@ impl WaveformPlot {
                 self.buffer_gz.get(i),
                 self.buffer_timestamp.get(i)
             ) {
-                data.push((x, y, z, gx, gy, gz, timestamp));
+                data.push(DataPoint {
+                    x,
+                    y,
+                    z,
+                    gx,
+                    gy,
+                    gz,
+                    timestamp,
+                    orientation: self.buffer_orientation.get(i).copied().flatten(),
+                    node_id: self.buffer_node_id.get(i).cloned().flatten(),
+                    temperature_c: self.buffer_temperature.get(i).copied().flatten(),
+                    arrival_timestamp: self.buffer_arrival_timestamp.get(i).copied().flatten(),
+                });
             }
         }
         data
     }
 
     pub fn get_current_audio_data(&self) -> Vec<f64> {
-        self.audio_buffer.iter().cloned().collect()
+        self.audio_buffer.range(self.audio_save_start()..).cloned().collect()
     }
 
     pub fn get_current_audio_data_with_timestamps(&self) -> Vec<(f64, i64)> {
-        self.audio_buffer.iter()
-            .zip(self.audio_timestamps.iter())
+        let start = self.audio_save_start();
+        self.audio_buffer.range(start..)
+            .zip(self.audio_timestamps.range(start..))
             .map(|(&sample, &timestamp)| (sample, timestamp))
             .collect()
     }
 
+    /// 音频缓冲区末尾最近的count个样本（不足时返回全部）
+    pub fn recent_audio_samples(&self, count: usize) -> impl Iterator<Item = f64> + '_ {
+        let start = self.audio_buffer.len().saturating_sub(count);
+        self.audio_buffer.range(start..).copied()
+    }
+
     pub fn get_current_audio_first_timestamp(&self) -> Option<i64> {
-        self.audio_timestamps.front().copied()
+        self.audio_timestamps.get(self.audio_save_start()).copied()
     }
 
     pub fn get_current_audio_last_timestamp(&self) -> Option<i64> {
         self.audio_timestamps.back().copied()
     }
 
-}
\ No newline at end of file
+    // 保存窗口在音频缓冲区中的起始索引
+    fn audio_save_start(&self) -> usize {
+        tail_start(self.audio_buffer.len(), self.audio_save_max_samples)
+    }
+
+}
+
+/// 绘制用：减去零偏后的缓冲区，零偏为0时不复制
+fn subtract_offset(buffer: &VecDeque<f64>, offset: f64) -> Cow<'_, VecDeque<f64>> {
+    if offset == 0.0 {
+        Cow::Borrowed(buffer)
+    } else {
+        Cow::Owned(buffer.iter().map(|value| value - offset).collect())
+    }
+}
+
+/// 计算缓冲区末尾 window 个样本的起始索引
+fn tail_start(len: usize, window: usize) -> usize {
+    len.saturating_sub(window)
+}
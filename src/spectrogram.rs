@@ -0,0 +1,166 @@
+use egui::{Color32, ColorImage, TextureHandle, TextureOptions};
+use crate::config::SpectrogramColormap;
+
+/// 每帧FFT长度（16kHz下约32ms）
+const FFT_SIZE: usize = 512;
+/// 默认帧移
+const HOP_SIZE: usize = 256;
+/// 图像最大列数，长音频会自动增大帧移
+const MAX_COLUMNS: usize = 1024;
+/// 显示的最低分贝，低于该值显示为色表最低色
+const DB_FLOOR: f32 = -90.0;
+
+/// Viridis 色表（20个参考点，均匀分布在0-1之间）
+const VIRIDIS: [[u8; 3]; 20] = [
+    [68, 1, 84], [72, 20, 103], [72, 38, 119], [69, 55, 129], [63, 71, 136],
+    [57, 85, 140], [50, 100, 142], [45, 113, 142], [39, 127, 142], [34, 140, 141],
+    [31, 152, 139], [32, 165, 134], [41, 177, 125], [60, 188, 114], [86, 198, 99],
+    [116, 208, 82], [149, 216, 64], [184, 222, 41], [220, 227, 25], [253, 231, 37],
+];
+
+/// Inferno 色表（20个参考点，均匀分布在0-1之间）
+const INFERNO: [[u8; 3]; 20] = [
+    [0, 0, 4], [10, 7, 34], [27, 12, 65], [47, 10, 91], [67, 10, 104],
+    [86, 16, 110], [105, 22, 110], [124, 29, 109], [143, 35, 105], [161, 42, 99],
+    [179, 50, 89], [195, 61, 78], [210, 74, 65], [223, 89, 51], [233, 107, 37],
+    [242, 127, 22], [247, 149, 8], [250, 173, 15], [248, 199, 46], [252, 255, 164],
+];
+
+/// 将归一化的分贝值（0-1）映射为颜色，参考点之间线性插值
+pub fn apply_colormap(normalized_db: f32, colormap: &SpectrogramColormap) -> Color32 {
+    let value = if normalized_db.is_finite() { normalized_db.clamp(0.0, 1.0) } else { 0.0 };
+
+    let table = match colormap {
+        SpectrogramColormap::Viridis => &VIRIDIS,
+        SpectrogramColormap::Inferno => &INFERNO,
+        SpectrogramColormap::Grayscale => {
+            let level = (value * 255.0).round() as u8;
+            return Color32::from_gray(level);
+        }
+    };
+
+    let position = value * (table.len() - 1) as f32;
+    let index = (position.floor() as usize).min(table.len() - 2);
+    let fraction = position - index as f32;
+    let (low, high) = (table[index], table[index + 1]);
+    let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * fraction).round() as u8;
+
+    Color32::from_rgb(lerp(low[0], high[0]), lerp(low[1], high[1]), lerp(low[2], high[2]))
+}
+
+/// 计算音频的频谱图图像：横轴为时间，纵轴为频率（低频在下）
+pub fn spectrogram_image(samples: &[f64], colormap: &SpectrogramColormap) -> Option<ColorImage> {
+    if samples.len() < FFT_SIZE {
+        return None;
+    }
+
+    let frame_span = samples.len() - FFT_SIZE;
+    let hop = HOP_SIZE.max(frame_span / MAX_COLUMNS + 1);
+    let columns = frame_span / hop + 1;
+    let bins = FFT_SIZE / 2;
+
+    // Hann窗
+    let window: Vec<f64> = (0..FFT_SIZE)
+        .map(|i| 0.5 - 0.5 * (2.0 * std::f64::consts::PI * i as f64 / (FFT_SIZE - 1) as f64).cos())
+        .collect();
+
+    let mut pixels = vec![Color32::BLACK; columns * bins];
+    let mut re = vec![0.0f64; FFT_SIZE];
+    let mut im = vec![0.0f64; FFT_SIZE];
+
+    for column in 0..columns {
+        let start = column * hop;
+        for i in 0..FFT_SIZE {
+            re[i] = samples[start + i] * window[i];
+            im[i] = 0.0;
+        }
+        fft_in_place(&mut re, &mut im);
+
+        for bin in 0..bins {
+            let magnitude = (re[bin] * re[bin] + im[bin] * im[bin]).sqrt() / (FFT_SIZE / 2) as f64;
+            let db = 20.0 * (magnitude + 1e-12).log10() as f32;
+            let normalized = (db - DB_FLOOR) / -DB_FLOOR;
+            let row = bins - 1 - bin;
+            pixels[row * columns + column] = apply_colormap(normalized, colormap);
+        }
+    }
+
+    Some(ColorImage::new([columns, bins], pixels))
+}
+
+/// 迭代式基2 FFT（长度必须为2的幂）
+fn fft_in_place(re: &mut [f64], im: &mut [f64]) {
+    let n = re.len();
+
+    // 位反转重排
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * std::f64::consts::PI / len as f64;
+        let (w_re, w_im) = (angle.cos(), angle.sin());
+        for start in (0..n).step_by(len) {
+            let (mut cur_re, mut cur_im) = (1.0, 0.0);
+            for k in 0..len / 2 {
+                let (a, b) = (start + k, start + k + len / 2);
+                let t_re = re[b] * cur_re - im[b] * cur_im;
+                let t_im = re[b] * cur_im + im[b] * cur_re;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+                let next_re = cur_re * w_re - cur_im * w_im;
+                cur_im = cur_re * w_im + cur_im * w_re;
+                cur_re = next_re;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// 频谱图纹理缓存，音频数据或色表变化时才重新计算
+#[derive(Clone, Default)]
+pub struct SpectrogramCache {
+    texture: Option<TextureHandle>,
+    source_key: Option<(usize, u64, u64, SpectrogramColormap)>,
+}
+
+impl std::fmt::Debug for SpectrogramCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SpectrogramCache")
+            .field("has_texture", &self.texture.is_some())
+            .field("source_key", &self.source_key)
+            .finish()
+    }
+}
+
+impl SpectrogramCache {
+    pub fn texture(&mut self, ctx: &egui::Context, samples: &[f64], colormap: SpectrogramColormap) -> Option<&TextureHandle> {
+        let key = (
+            samples.len(),
+            samples.first().map_or(0, |v| v.to_bits()),
+            samples.last().map_or(0, |v| v.to_bits()),
+            colormap,
+        );
+
+        if self.source_key != Some(key) {
+            self.source_key = Some(key);
+            self.texture = spectrogram_image(samples, &colormap)
+                .map(|image| ctx.load_texture("spectrogram", image, TextureOptions::LINEAR));
+        }
+
+        self.texture.as_ref()
+    }
+}
@@ -1,3 +1,6 @@
+/// 会话没有音频元数据时假定的采样率（Hz）
+pub const DEFAULT_AUDIO_SAMPLE_RATE: u32 = 16000;
+
 #[derive(serde::Deserialize, Clone, Debug)]
 pub struct AudioData {
     pub audio_data: String,  // Base64 encoded audio data
@@ -6,6 +9,9 @@ pub struct AudioData {
     pub format: String,
     pub samples: usize,
     pub timestamp: i64,
+    /// 主机收到该音频块时的Unix毫秒时间戳，不从负载中读取
+    #[serde(skip)]
+    pub arrival_timestamp: Option<i64>,
 }
 
 impl AudioData {
@@ -24,6 +30,7 @@ impl AudioData {
             format,
             samples,
             timestamp,
+            arrival_timestamp: None,
         }
     }
 }
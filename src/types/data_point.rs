@@ -7,10 +7,34 @@ pub struct DataPoint {
     pub gy: f64,  // 陀螺仪 Y 轴
     pub gz: f64,  // 陀螺仪 Z 轴
     pub timestamp: i64,
+    /// 固件预先计算的姿态四元数 [w, x, y, z]，部分设备不发送
+    #[serde(default)]
+    pub orientation: Option<[f64; 4]>,
+    /// 发送数据的传感器节点ID，旧固件不发送
+    #[serde(default)]
+    pub node_id: Option<String>,
+    /// IMU芯片内置温度传感器的读数（摄氏度），部分模块不发送
+    #[serde(default)]
+    pub temperature_c: Option<f64>,
+    /// 主机收到该消息时的Unix毫秒时间戳，不从负载中读取
+    #[serde(skip)]
+    pub arrival_timestamp: Option<i64>,
 }
 
 impl DataPoint {
     pub fn new(x: f64, y: f64, z: f64, gx: f64, gy: f64, gz: f64, timestamp: i64) -> Self {
-        Self { x, y, z, gx, gy, gz, timestamp }
+        Self { x, y, z, gx, gy, gz, timestamp, orientation: None, node_id: None, temperature_c: None, arrival_timestamp: None }
     }
 }
+
+/// 将四元数 [w, x, y, z] 分解为欧拉角 (roll, pitch, yaw)，单位为度
+pub fn quaternion_to_euler(q: [f64; 4]) -> (f64, f64, f64) {
+    let [w, x, y, z] = q;
+
+    let roll = (2.0 * (w * x + y * z)).atan2(1.0 - 2.0 * (x * x + y * y));
+    // 万向节锁附近对asin的参数做截断，避免NaN
+    let pitch = (2.0 * (w * y - z * x)).clamp(-1.0, 1.0).asin();
+    let yaw = (2.0 * (w * z + x * y)).atan2(1.0 - 2.0 * (y * y + z * z));
+
+    (roll.to_degrees(), pitch.to_degrees(), yaw.to_degrees())
+}
@@ -3,7 +3,7 @@ pub mod audio_data;
 pub mod results;
 pub mod tasks;
 
-pub use data_point::DataPoint;
-pub use audio_data::AudioData;
-pub use results::{SaveResult, ExportResult};
-pub use tasks::{DatabaseTask, ExportType};
+pub use data_point::{DataPoint, quaternion_to_euler};
+pub use audio_data::{AudioData, DEFAULT_AUDIO_SAMPLE_RATE};
+pub use results::{SaveResult, ExportResult, ExportProgress, SessionSizeEstimate, SessionInfo, SessionSummary, SessionStatistics, IntegrityIssue, IntegrityIssueKind, IntegrityReport};
+pub use tasks::{DatabaseTask, ExportType, LoadProgress, SessionLookup};
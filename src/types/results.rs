@@ -3,6 +3,8 @@
 pub struct SaveResult {
     pub acc_saved: usize,
     pub audio_saved: usize,
+    /// Accelerometer rows dropped to keep the session under its row limit
+    pub acc_truncated: usize,
     pub error: Option<String>,
 }
 
@@ -11,14 +13,21 @@ impl SaveResult {
         Self {
             acc_saved,
             audio_saved,
+            acc_truncated: 0,
             error: None,
         }
     }
 
+    pub fn with_truncated(mut self, acc_truncated: usize) -> Self {
+        self.acc_truncated = acc_truncated;
+        self
+    }
+
     pub fn error(error: String) -> Self {
         Self {
             acc_saved: 0,
             audio_saved: 0,
+            acc_truncated: 0,
             error: Some(error),
         }
     }
@@ -34,6 +43,8 @@ pub struct ExportResult {
     pub success_count: usize,
     pub error_count: usize,
     pub message: String,
+    /// Sessions that failed to export, with the error for each
+    pub failures: Vec<(String, String)>,
 }
 
 impl ExportResult {
@@ -42,6 +53,7 @@ impl ExportResult {
             success_count,
             error_count,
             message,
+            failures: Vec::new(),
         }
     }
 
@@ -50,6 +62,7 @@ impl ExportResult {
             success_count: count,
             error_count: 0,
             message: format!("Successfully exported {} sessions", count),
+            failures: Vec::new(),
         }
     }
 
@@ -58,6 +71,201 @@ impl ExportResult {
             success_count: 0,
             error_count: 0,
             message: "No new sessions to export".to_string(),
+            failures: Vec::new(),
+        }
+    }
+}
+
+/// Progress of a running export, sent after each session file is written
+#[derive(Debug, Clone)]
+pub struct ExportProgress {
+    pub files_done: usize,
+    pub files_total: usize,
+    pub bytes_written: u64,
+    /// Average write throughput since the export started
+    pub bytes_per_sec: f64,
+}
+
+impl ExportProgress {
+    pub fn fraction(&self) -> f32 {
+        if self.files_total == 0 {
+            0.0
+        } else {
+            self.files_done as f32 / self.files_total as f32
+        }
+    }
+}
+
+/// Estimated export size of a single session
+#[derive(Debug, Clone)]
+pub struct SessionSizeEstimate {
+    pub session_id: String,
+    pub acc_rows: usize,
+    pub audio_samples: u64,
+    pub audio_bytes: u64, // 数据库中音频块的实际大小（FLAC存储时小于样本数×2）
+    pub estimated_csv_bytes: u64,
+}
+
+impl SessionSizeEstimate {
+    /// 估算CSV中一行加速度/陀螺仪数据的平均字节数
+    const AVG_ACC_ROW_BYTES: u64 = 110;
+    /// 估算CSV中一个音频样本字段的平均字节数
+    const AVG_AUDIO_FIELD_BYTES: u64 = 22;
+
+    pub fn new(session_id: String, acc_rows: usize, audio_samples: u64, audio_bytes: u64) -> Self {
+        // 按样本数估算，与音频块的存储格式无关
+        let estimated_csv_bytes = acc_rows as u64 * Self::AVG_ACC_ROW_BYTES
+            + audio_samples * Self::AVG_AUDIO_FIELD_BYTES;
+
+        Self {
+            session_id,
+            acc_rows,
+            audio_samples,
+            audio_bytes,
+            estimated_csv_bytes,
+        }
+    }
+}
+
+/// Session metadata shown in the export dialog
+#[derive(Debug, Clone)]
+pub struct SessionInfo {
+    pub session_id: String,
+    pub username: String,
+    pub scenario: String,
+    pub acc_count: usize,
+    pub duration_ms: i64,
+    pub is_exported: bool,
+}
+
+/// Per-session metrics used to order the history session list
+#[derive(Debug, Clone)]
+pub struct SessionSummary {
+    pub session_id: String,
+    pub acc_rows: usize,
+    pub audio_bytes: u64,
+    pub duration_ms: i64,
+    pub is_exported: bool,
+}
+
+/// Running mean/variance of one axis, updated with Welford's online algorithm
+#[derive(Debug, Clone, Copy)]
+pub struct AxisStatistics {
+    pub count: usize,
+    pub mean: f64,
+    m2: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Default for AxisStatistics {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            mean: 0.0,
+            m2: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+}
+
+impl AxisStatistics {
+    pub fn push(&mut self, value: f64) {
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    /// Sample standard deviation; zero for fewer than two values
+    pub fn std_dev(&self) -> f64 {
+        if self.count < 2 {
+            0.0
+        } else {
+            (self.m2 / (self.count - 1) as f64).sqrt()
+        }
+    }
+}
+
+/// Per-axis statistics of a session's accelerometer and gyroscope data
+#[derive(Debug, Clone)]
+pub struct SessionStatistics {
+    pub session_id: String,
+    /// acc_x, acc_y, acc_z, gyro_x, gyro_y, gyro_z
+    pub axes: [AxisStatistics; 6],
+}
+
+impl SessionStatistics {
+    pub const AXIS_LABELS: [&'static str; 6] = ["ACC X", "ACC Y", "ACC Z", "GYRO X", "GYRO Y", "GYRO Z"];
+
+    pub fn new(session_id: String) -> Self {
+        Self {
+            session_id,
+            axes: [AxisStatistics::default(); 6],
+        }
+    }
+
+    pub fn push(&mut self, values: [f64; 6]) {
+        for (axis, value) in self.axes.iter_mut().zip(values) {
+            axis.push(value);
         }
     }
+
+    pub fn row_count(&self) -> usize {
+        self.axes[0].count
+    }
+}
+
+/// Kind of problem found by the database integrity check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegrityIssueKind {
+    MissingAudio,
+    MissingAccelerometer,
+    NonMonotonicTimestamps,
+    EmptyAudioBlob,
+    BlobLengthMismatch,
+    ImplausibleSampleRate,
+}
+
+impl IntegrityIssueKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            IntegrityIssueKind::MissingAudio => "No audio",
+            IntegrityIssueKind::MissingAccelerometer => "No accelerometer data",
+            IntegrityIssueKind::NonMonotonicTimestamps => "Non-monotonic timestamps",
+            IntegrityIssueKind::EmptyAudioBlob => "Empty audio blocks",
+            IntegrityIssueKind::BlobLengthMismatch => "Audio blob length mismatch",
+            IntegrityIssueKind::ImplausibleSampleRate => "Implausible sample rate",
+        }
+    }
+}
+
+/// One problem found in one session
+#[derive(Debug, Clone)]
+pub struct IntegrityIssue {
+    pub session_id: String,
+    pub kind: IntegrityIssueKind,
+    pub detail: String,
+}
+
+/// Result of scanning all sessions for data problems
+#[derive(Debug, Clone, Default)]
+pub struct IntegrityReport {
+    pub sessions_checked: usize,
+    pub issues: Vec<IntegrityIssue>,
+}
+
+impl IntegrityReport {
+    /// Sorted, de-duplicated IDs of the sessions with at least one issue
+    pub fn affected_sessions(&self) -> Vec<String> {
+        let mut session_ids: Vec<String> = self.issues.iter()
+            .map(|issue| issue.session_id.clone())
+            .collect();
+        session_ids.sort();
+        session_ids.dedup();
+        session_ids
+    }
 }
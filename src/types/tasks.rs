@@ -1,4 +1,50 @@
-use super::{DataPoint, AudioData, ExportResult};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use super::{DataPoint, AudioData, ExportResult, ExportProgress, SessionSizeEstimate, SessionInfo, SessionSummary, SessionStatistics, IntegrityReport};
+use crate::config::ExportConfig;
+
+/// 按session ID查到的 (用户名, 场景)，session不存在时为None
+pub type SessionLookup = Option<(String, String)>;
+
+/// 历史数据加载的进度和取消标志，GUI与数据库线程共享
+#[derive(Debug, Default)]
+pub struct LoadProgress {
+    cancelled: AtomicBool,
+    rows_loaded: AtomicUsize,
+    total_rows: AtomicUsize,
+}
+
+impl LoadProgress {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// 请求数据库线程在下一批数据读取后停止加载
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// 同一次加载可能包含多个任务（原始+对齐），总行数累加
+    pub fn add_total_rows(&self, rows: usize) {
+        self.total_rows.fetch_add(rows, Ordering::Relaxed);
+    }
+
+    pub fn add_loaded_rows(&self, rows: usize) {
+        self.rows_loaded.fetch_add(rows, Ordering::Relaxed);
+    }
+
+    pub fn rows_loaded(&self) -> usize {
+        self.rows_loaded.load(Ordering::Relaxed)
+    }
+
+    pub fn total_rows(&self) -> usize {
+        self.total_rows.load(Ordering::Relaxed)
+    }
+}
 
 /// Database task enumeration for async operations
 #[derive(Clone)]
@@ -15,6 +61,14 @@ pub enum DatabaseTask {
     },
     Export {
         export_type: ExportType,
+        options: ExportConfig,
+        response_sender: crossbeam_channel::Sender<ExportResult>,
+    },
+    /// 与Export相同，但每导出完一个session就发送一次进度
+    ExportWithProgress {
+        export_type: ExportType,
+        options: ExportConfig,
+        progress_sender: crossbeam_channel::Sender<ExportProgress>,
         response_sender: crossbeam_channel::Sender<ExportResult>,
     },
     GetSessions {
@@ -23,8 +77,8 @@ pub enum DatabaseTask {
     GetUnexportedSessions {
         response_sender: crossbeam_channel::Sender<Vec<String>>,
     },
-    GetAllSessionsWithExportStatus {
-        response_sender: crossbeam_channel::Sender<Vec<(String, bool)>>,
+    GetSessionsWithMetadata {
+        response_sender: crossbeam_channel::Sender<Vec<SessionInfo>>,
     },
     GetUsernames {
         response_sender: crossbeam_channel::Sender<Vec<String>>,
@@ -40,6 +94,13 @@ pub enum DatabaseTask {
         username: String,
         response_sender: crossbeam_channel::Sender<Vec<String>>,
     },
+    GetNodeIds {
+        response_sender: crossbeam_channel::Sender<Vec<String>>,
+    },
+    GetSessionsByNodeId {
+        node_id: String,
+        response_sender: crossbeam_channel::Sender<Vec<String>>,
+    },
     GetSessionsByUsernameAndScenario {
         username: String,
         scenario: String,
@@ -51,16 +112,78 @@ pub enum DatabaseTask {
     },
     LoadHistoryData {
         session_id: String,
+        progress: Arc<LoadProgress>,
         response_sender: crossbeam_channel::Sender<(Vec<DataPoint>, Vec<f64>)>,
     },
     LoadAlignedHistoryData {
         session_id: String,
+        progress: Arc<LoadProgress>,
         response_sender: crossbeam_channel::Sender<(Vec<DataPoint>, Vec<f64>, i64)>,
     },
+    /// 查询session所属的用户和场景，session不存在时返回None
+    ResolveSession {
+        session_id: String,
+        response_sender: crossbeam_channel::Sender<SessionLookup>,
+    },
     DeleteSession {
         session_id: String,
         response_sender: crossbeam_channel::Sender<Result<(), String>>,
     },
+    /// 依次删除多个session，任一失败时返回汇总的错误信息
+    BatchDelete {
+        session_ids: Vec<String>,
+        response_sender: crossbeam_channel::Sender<Result<(), String>>,
+    },
+    GetSessionSummaries {
+        session_ids: Vec<String>,
+        response_sender: crossbeam_channel::Sender<Vec<SessionSummary>>,
+    },
+    /// 设置session的人工审阅标记（持久化在session_metadata表）
+    SetReviewed {
+        session_id: String,
+        reviewed: bool,
+        response_sender: crossbeam_channel::Sender<Result<(), String>>,
+    },
+    /// 获取所有已审阅的session ID
+    GetReviewed {
+        response_sender: crossbeam_channel::Sender<Vec<String>>,
+    },
+    /// 逐行读取session的6轴数据计算统计量，不把整个session载入内存
+    ComputeSessionStatistics {
+        session_id: String,
+        response_sender: crossbeam_channel::Sender<SessionStatistics>,
+    },
+    GetAudioSampleRate {
+        session_id: String,
+        response_sender: crossbeam_channel::Sender<u32>,
+    },
+    GetSessionSizeEstimate {
+        session_id: String,
+        response_sender: crossbeam_channel::Sender<SessionSizeEstimate>,
+    },
+    /// 查询数据库文件的大小（字节），用于状态栏显示
+    GetDatabaseSize {
+        response_sender: crossbeam_channel::Sender<u64>,
+    },
+    /// 原数据库文件无法打开时，改用新的带时间戳的数据库文件
+    OpenFallbackDatabase,
+    /// 扫描所有session的数据问题（缺少数据、时间戳倒退、损坏的音频块、不合理的采样率）
+    CheckIntegrity {
+        response_sender: crossbeam_channel::Sender<Result<IntegrityReport, String>>,
+    },
+    /// 重新收集数据表的统计信息（ANALYZE），数据库线程每删除一定数量的session后也会自动执行
+    Reindex,
+    RenameScenario {
+        old_name: String,
+        new_name: String,
+        username: String,
+        response_sender: crossbeam_channel::Sender<Result<usize, String>>,
+    },
+    /// 把session的所有数据复制到新生成的session ID下，返回新ID
+    DuplicateSession {
+        session_id: String,
+        response_sender: crossbeam_channel::Sender<Result<String, String>>,
+    },
 }
 
 /// Export type specification
@@ -68,6 +191,9 @@ pub enum DatabaseTask {
 pub enum ExportType {
     SelectedSessions(Vec<String>),
     NewSessions,
+    Comparison(Vec<String>),
+    /// 只导出一个session中的一段时间范围，起止为相对第一个数据点的毫秒数
+    TimeRange { session_id: String, start_ms: i64, end_ms: i64 },
 }
 
 impl ExportType {
@@ -26,3 +26,38 @@ pub fn format_timestamp(timestamp_ms: i64) -> String {
         None => format!("Invalid timestamp: {}", timestamp_ms)
     }
 }
+
+/// 将字节数格式化为易读的大小，如 1.5 MB
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit_index = 0;
+
+    while size >= 1024.0 && unit_index < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit_index += 1;
+    }
+
+    if unit_index == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit_index])
+    }
+}
+
+/// 将毫秒时长格式化为易读的时长，如 1m 05.2s
+pub fn format_duration_ms(duration_ms: i64) -> String {
+    let total_seconds = duration_ms.max(0) as f64 / 1000.0;
+    if total_seconds >= 60.0 {
+        let minutes = (total_seconds / 60.0).floor();
+        format!("{}m {:04.1}s", minutes as u64, total_seconds - minutes * 60.0)
+    } else {
+        format!("{:.1}s", total_seconds)
+    }
+}
+
+/// 将时长格式化为时钟样式的 HH:MM:SS，用于录制计时
+pub fn format_elapsed_clock(elapsed: std::time::Duration) -> String {
+    let total_seconds = elapsed.as_secs();
+    format!("{:02}:{:02}:{:02}", total_seconds / 3600, total_seconds / 60 % 60, total_seconds % 60)
+}